@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Malformed puzzle input, located by line and column.
+///
+/// Parsers only see the input's contents, so the file is attached by the
+/// caller that read it, see [`ParseError::in_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<PathBuf>,
+    /// Line of the offending text, counting from 1.
+    pub line: usize,
+    /// Column of the offending text in characters, counting from 1.
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// An error for `text`, which must be a slice of `contents`; its line and
+    /// column follow from where the slice sits.
+    pub fn at(contents: &str, text: &str, message: impl Into<String>) -> ParseError {
+        let start = contents.as_ptr() as usize;
+        let offset = (text.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| offset + text.len() <= contents.len())
+            .unwrap_or(0);
+
+        let before = &contents[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        ParseError {
+            file: None,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn in_file(self, file: &Path) -> ParseError {
+        ParseError {
+            file: Some(file.to_path_buf()),
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(
+            f,
+            "{}:{}: {}: `{}`",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses `text`, a slice of `contents`, reporting where it is on failure.
+pub fn parse_at<T>(contents: &str, text: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse::<T>()
+        .map_err(|error| ParseError::at(contents, text, error.to_string()))
+}
@@ -0,0 +1,57 @@
+//! Random puzzle inputs for stress and differential testing.
+//!
+//! Every day can produce inputs of any size that its solver accepts and
+//! finds an answer for, in the same text format as the puzzle input. What
+//! `size` counts depends on the day, usually lines or items; days whose
+//! answers would outgrow their integer types, or whose solvers would run for
+//! minutes, cap it, as documented on each implementation.
+
+use crate::Solution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub trait Generator: Solution {
+    /// A valid puzzle input of roughly `size` lines or items.
+    fn generate(rng: &mut StdRng, size: usize) -> String;
+}
+
+type DayGenerator = fn(&mut StdRng, usize) -> String;
+
+pub fn day_generator(day: usize) -> Option<DayGenerator> {
+    use crate::*;
+
+    let generator: DayGenerator = match day {
+        1 => day01::Day01::generate,
+        2 => day02::Day02::generate,
+        3 => day03::Day03::generate,
+        4 => day04::Day04::generate,
+        5 => day05::Day05::generate,
+        6 => day06::Day06::generate,
+        7 => day07::Day07::generate,
+        8 => day08::Day08::generate,
+        9 => day09::Day09::generate,
+        10 => day10::Day10::generate,
+        11 => day11::Day11::generate,
+        12 => day12::Day12::generate,
+        13 => day13::Day13::generate,
+        14 => day14::Day14::generate,
+        15 => day15::Day15::generate,
+        16 => day16::Day16::generate,
+        17 => day17::Day17::generate,
+        18 => day18::Day18::generate,
+        19 => day19::Day19::generate,
+        20 => day20::Day20::generate,
+        21 => day21::Day21::generate,
+        22 => day22::Day22::generate,
+        23 => day23::Day23::generate,
+        24 => day24::Day24::generate,
+        25 => day25::Day25::generate,
+        _ => return None,
+    };
+    Some(generator)
+}
+
+/// Generates an input for `day`; the same seed always gives the same input.
+pub fn generate(day: usize, size: usize, seed: u64) -> Option<String> {
+    day_generator(day).map(|generator| generator(&mut StdRng::seed_from_u64(seed), size))
+}
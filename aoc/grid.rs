@@ -0,0 +1,387 @@
+//! Two-dimensional grids of cells, as used by the map-shaped puzzles.
+//!
+//! Coordinates are `(x, y)` with `y` growing downwards, like the rows of the
+//! puzzle input. A grid is either dense, holding a cell for every coordinate
+//! within its bounds, or sparse, holding only the cells that were inserted;
+//! a sparse grid grows its bounds to fit whatever is inserted.
+
+use crate::ParseError;
+use itertools::Either;
+use std::collections::HashMap;
+use std::iter;
+use std::mem;
+
+pub type Coordinate = (i32, i32);
+
+/// Offsets of the neighbours sharing an edge with a cell.
+pub const NEIGHBOURS_4: [Coordinate; 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the neighbours sharing an edge or a corner with a cell.
+pub const NEIGHBOURS_8: [Coordinate; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangle of coordinates, from `min` up to but not including `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    pub min: Coordinate,
+    pub max: Coordinate,
+}
+
+impl Bounds {
+    pub fn new(min: Coordinate, max: Coordinate) -> Bounds {
+        Bounds { min, max }
+    }
+
+    pub fn width(&self) -> i32 {
+        (self.max.0 - self.min.0).max(0)
+    }
+
+    pub fn height(&self) -> i32 {
+        (self.max.1 - self.min.1).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    pub fn contains(&self, coord: Coordinate) -> bool {
+        (self.min.0..self.max.0).contains(&coord.0) && (self.min.1..self.max.1).contains(&coord.1)
+    }
+
+    /// Maps any coordinate into the bounds, as if they were tiled endlessly.
+    pub fn wrap(&self, coord: Coordinate) -> Option<Coordinate> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((
+                self.min.0 + (coord.0 - self.min.0).rem_euclid(self.width()),
+                self.min.1 + (coord.1 - self.min.1).rem_euclid(self.height()),
+            ))
+        }
+    }
+
+    /// All coordinates within the bounds, row by row.
+    pub fn coordinates(&self) -> impl Iterator<Item = Coordinate> {
+        let (min, max) = (self.min, self.max);
+        (min.1..max.1).flat_map(move |y| (min.0..max.0).map(move |x| (x, y)))
+    }
+
+    fn including(&self, coord: Coordinate) -> Bounds {
+        if self.is_empty() {
+            Bounds::new(coord, (coord.0 + 1, coord.1 + 1))
+        } else {
+            Bounds::new(
+                (self.min.0.min(coord.0), self.min.1.min(coord.1)),
+                (self.max.0.max(coord.0 + 1), self.max.1.max(coord.1 + 1)),
+            )
+        }
+    }
+
+    fn intersection(&self, other: &Bounds) -> Bounds {
+        Bounds::new(
+            (self.min.0.max(other.min.0), self.min.1.max(other.min.1)),
+            (self.max.0.min(other.max.0), self.max.1.min(other.max.1)),
+        )
+    }
+
+    fn index(&self, coord: Coordinate) -> Option<usize> {
+        if self.contains(coord) {
+            Some(
+                (coord.1 - self.min.1) as usize * self.width() as usize
+                    + (coord.0 - self.min.0) as usize,
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cells<T> {
+    Dense(Vec<T>),
+    Sparse(HashMap<Coordinate, T>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    bounds: Bounds,
+    cells: Cells<T>,
+}
+
+impl<T> Grid<T> {
+    /// A dense grid of `width` by `height` cells with its top left at the
+    /// origin, filled from `cells` row by row.
+    pub fn dense(width: i32, height: i32, cells: Vec<T>) -> Grid<T> {
+        let bounds = Bounds::new((0, 0), (width, height));
+        assert_eq!(
+            cells.len(),
+            bounds.width() as usize * bounds.height() as usize,
+            "a dense grid needs a cell for every coordinate"
+        );
+        Grid {
+            bounds,
+            cells: Cells::Dense(cells),
+        }
+    }
+
+    /// An empty sparse grid that starts out with `bounds`.
+    pub fn sparse(bounds: Bounds) -> Grid<T> {
+        Grid {
+            bounds,
+            cells: Cells::Sparse(HashMap::new()),
+        }
+    }
+
+    /// Parses the rows of `text`, a slice of `contents`, into a dense grid.
+    ///
+    /// `cell` maps a character to its cell, or to `None` if it isn't valid,
+    /// which is reported with `expected` as the message. Empty rows are
+    /// reported too.
+    pub fn parse(
+        contents: &str,
+        text: &str,
+        expected: &str,
+        cell: impl Fn(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for row in text.lines() {
+            // A grid with rows has a width, or it would have no cells to
+            // wrap around or step between
+            if row.is_empty() {
+                return Err(ParseError::at(contents, row, "expected a row of cells"));
+            }
+            let mut row_width = 0;
+            for (index, c) in row.char_indices() {
+                let value = cell(c).ok_or_else(|| {
+                    ParseError::at(contents, &row[index..index + c.len_utf8()], expected)
+                })?;
+                cells.push(value);
+                row_width += 1;
+            }
+
+            match width {
+                Some(width) if width != row_width => {
+                    return Err(ParseError::at(
+                        contents,
+                        row,
+                        format!("expected a row of {} cells", width),
+                    ))
+                }
+                _ => width = Some(row_width),
+            }
+            height += 1;
+        }
+
+        Ok(Grid::dense(width.unwrap_or(0), height, cells))
+    }
+
+    /// Converts to a sparse grid of the cells that `keep` accepts, keeping
+    /// the bounds.
+    pub fn into_sparse(self, keep: impl Fn(&T) -> bool) -> Grid<T> {
+        let bounds = self.bounds;
+        let cells = match self.cells {
+            Cells::Dense(cells) => bounds.coordinates().zip(cells).collect(),
+            Cells::Sparse(cells) => cells,
+        };
+        Grid {
+            bounds,
+            cells: Cells::Sparse(cells.into_iter().filter(|(_, value)| keep(value)).collect()),
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    pub fn width(&self) -> i32 {
+        self.bounds.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bounds.height()
+    }
+
+    pub fn get(&self, coord: Coordinate) -> Option<&T> {
+        match &self.cells {
+            Cells::Dense(cells) => self.bounds.index(coord).map(|index| &cells[index]),
+            Cells::Sparse(cells) => cells.get(&coord),
+        }
+    }
+
+    pub fn get_mut(&mut self, coord: Coordinate) -> Option<&mut T> {
+        match &mut self.cells {
+            Cells::Dense(cells) => self.bounds.index(coord).map(move |index| &mut cells[index]),
+            Cells::Sparse(cells) => cells.get_mut(&coord),
+        }
+    }
+
+    /// Looks up a cell as if the grid were repeated endlessly in every
+    /// direction.
+    pub fn get_wrapped(&self, coord: Coordinate) -> Option<&T> {
+        self.bounds.wrap(coord).and_then(|coord| self.get(coord))
+    }
+
+    /// Whether the grid has a cell at `coord`.
+    pub fn contains(&self, coord: Coordinate) -> bool {
+        self.get(coord).is_some()
+    }
+
+    /// Sets a cell, returning the one it replaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dense grid doesn't cover `coord`.
+    pub fn insert(&mut self, coord: Coordinate, value: T) -> Option<T> {
+        match &mut self.cells {
+            Cells::Dense(cells) => {
+                let index = self
+                    .bounds
+                    .index(coord)
+                    .unwrap_or_else(|| panic!("{:?} is outside the dense grid", coord));
+                Some(mem::replace(&mut cells[index], value))
+            }
+            Cells::Sparse(cells) => {
+                self.bounds = self.bounds.including(coord);
+                cells.insert(coord, value)
+            }
+        }
+    }
+
+    /// The cells with their coordinates; a dense grid goes row by row, a
+    /// sparse one in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, &T)> {
+        match &self.cells {
+            Cells::Dense(cells) => Either::Left(self.bounds.coordinates().zip(cells)),
+            Cells::Sparse(cells) => {
+                Either::Right(cells.iter().map(|(coord, value)| (*coord, value)))
+            }
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// The cells at `offsets` from `coord` that the grid has.
+    pub fn neighbours<'a>(
+        &'a self,
+        coord: Coordinate,
+        offsets: &'a [Coordinate],
+    ) -> impl Iterator<Item = (Coordinate, &'a T)> + 'a {
+        offsets.iter().filter_map(move |offset| {
+            let neighbour = (coord.0 + offset.0, coord.1 + offset.1);
+            self.get(neighbour).map(|value| (neighbour, value))
+        })
+    }
+
+    pub fn neighbours4(&self, coord: Coordinate) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.neighbours(coord, &NEIGHBOURS_4)
+    }
+
+    pub fn neighbours8(&self, coord: Coordinate) -> impl Iterator<Item = (Coordinate, &T)> {
+        self.neighbours(coord, &NEIGHBOURS_8)
+    }
+
+    /// The cells seen from `from` looking in steps of `step`, nearest first,
+    /// up to the edge of the grid. `from` itself is not included.
+    pub fn ray(
+        &self,
+        from: Coordinate,
+        step: Coordinate,
+    ) -> impl Iterator<Item = (Coordinate, &T)> {
+        assert_ne!(step, (0, 0), "a ray needs a direction");
+        iter::successors(Some(from), move |coord| {
+            Some((coord.0 + step.0, coord.1 + step.1))
+        })
+        .skip(1)
+        .take_while(move |&coord| self.bounds.contains(coord))
+        .filter_map(move |coord| self.get(coord).map(|value| (coord, value)))
+    }
+
+    /// Rotates a quarter turn clockwise, within the same top left corner.
+    pub fn rotate(&mut self) {
+        let Bounds { min, max } = self.bounds;
+        let bounds = Bounds::new(min, (min.0 + self.height(), min.1 + self.width()));
+        self.remap(bounds, |(x, y)| {
+            Some((min.0 + (max.1 - 1 - y), min.1 + (x - min.0)))
+        });
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&mut self) {
+        let Bounds { min, max } = self.bounds;
+        self.remap(self.bounds, |(x, y)| Some((min.0 + (max.0 - 1 - x), y)));
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let Bounds { min, max } = self.bounds;
+        self.remap(self.bounds, |(x, y)| Some((x, min.1 + (max.1 - 1 - y))));
+    }
+
+    /// Keeps only the cells within `bounds`, moving them so that the top left
+    /// corner of `bounds` becomes the origin.
+    pub fn crop(&mut self, bounds: Bounds) {
+        let bounds = self.bounds.intersection(&bounds);
+        let cropped = Bounds::new((0, 0), (bounds.width(), bounds.height()));
+        self.remap(cropped, |(x, y)| {
+            if bounds.contains((x, y)) {
+                Some((x - bounds.min.0, y - bounds.min.1))
+            } else {
+                None
+            }
+        });
+    }
+
+    /// Draws the grid row by row, with `cell` picking the character for each
+    /// coordinate, including those a sparse grid has no cell for.
+    pub fn render(&self, cell: impl Fn(Option<&T>) -> char) -> String {
+        let mut rendered = String::new();
+        for y in self.bounds.min.1..self.bounds.max.1 {
+            for x in self.bounds.min.0..self.bounds.max.0 {
+                rendered.push(cell(self.get((x, y))));
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+
+    /// Moves every cell to the coordinate `map` gives it, dropping those it
+    /// gives none, and replaces the bounds. A dense grid must end up with a
+    /// cell for every coordinate of the new bounds.
+    fn remap(&mut self, bounds: Bounds, map: impl Fn(Coordinate) -> Option<Coordinate>) {
+        let cells = mem::replace(&mut self.cells, Cells::Sparse(HashMap::new()));
+        self.cells = match cells {
+            Cells::Dense(cells) => {
+                let mut moved = self
+                    .bounds
+                    .coordinates()
+                    .zip(cells)
+                    .filter_map(|(coord, value)| {
+                        map(coord).and_then(|coord| bounds.index(coord).map(|index| (index, value)))
+                    })
+                    .collect::<Vec<(usize, T)>>();
+                moved.sort_by_key(|(index, _)| *index);
+                Cells::Dense(moved.into_iter().map(|(_, value)| value).collect())
+            }
+            Cells::Sparse(cells) => Cells::Sparse(
+                cells
+                    .into_iter()
+                    .filter_map(|(coord, value)| map(coord).map(|coord| (coord, value)))
+                    .collect(),
+            ),
+        };
+        self.bounds = bounds;
+    }
+}
@@ -0,0 +1,89 @@
+//! Advent of Code 2020 solvers, one module per day.
+
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod log;
+pub mod memory;
+pub mod readme;
+pub mod render;
+pub mod runner;
+pub mod solution;
+
+pub use error::ParseError;
+pub use solution::{NoAnswer, Solution};
+
+#[path = "../day01/day01.rs"]
+pub mod day01;
+
+#[path = "../day02/day02.rs"]
+pub mod day02;
+
+#[path = "../day03/day03.rs"]
+pub mod day03;
+
+#[path = "../day04/day04.rs"]
+pub mod day04;
+
+#[path = "../day05/day05.rs"]
+pub mod day05;
+
+#[path = "../day06/day06.rs"]
+pub mod day06;
+
+#[path = "../day07/day07.rs"]
+pub mod day07;
+
+#[path = "../day08/day08.rs"]
+pub mod day08;
+
+#[path = "../day09/day09.rs"]
+pub mod day09;
+
+#[path = "../day10/day10.rs"]
+pub mod day10;
+
+#[path = "../day11/day11.rs"]
+pub mod day11;
+
+#[path = "../day12/day12.rs"]
+pub mod day12;
+
+#[path = "../day13/day13.rs"]
+pub mod day13;
+
+#[path = "../day14/day14.rs"]
+pub mod day14;
+
+#[path = "../day15/day15.rs"]
+pub mod day15;
+
+#[path = "../day16/day16.rs"]
+pub mod day16;
+
+#[path = "../day17/day17.rs"]
+pub mod day17;
+
+#[path = "../day18/day18.rs"]
+pub mod day18;
+
+#[path = "../day19/day19.rs"]
+pub mod day19;
+
+#[path = "../day20/day20.rs"]
+pub mod day20;
+
+#[path = "../day21/day21.rs"]
+pub mod day21;
+
+#[path = "../day22/day22.rs"]
+pub mod day22;
+
+#[path = "../day23/day23.rs"]
+pub mod day23;
+
+#[path = "../day24/day24.rs"]
+pub mod day24;
+
+#[path = "../day25/day25.rs"]
+pub mod day25;
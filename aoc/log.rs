@@ -0,0 +1,89 @@
+//! Leveled diagnostics for the solvers, written to stderr.
+//!
+//! Answers are the runner's business and always go to stdout; everything a
+//! solver has to say along the way goes through [`info!`], [`debug!`] or
+//! [`trace!`] and is only formatted when the level allows it. Nothing is
+//! logged until the level is raised with [`set_level`], so tests and
+//! benchmarks stay quiet.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Quiet,
+    /// Occasional progress and anything that went wrong.
+    Info,
+    /// Intermediate results, a handful of lines per part.
+    Debug,
+    /// Every step of a solver; very verbose on puzzle inputs.
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Level, String> {
+        match level {
+            "quiet" => Ok(Level::Quiet),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!(
+                "unknown log level `{}`, expected quiet, info, debug or trace",
+                level
+            )),
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Quiet as u8);
+
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Quiet,
+        1 => Level::Info,
+        2 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+pub fn enabled(level: Level) -> bool {
+    level != Level::Quiet && level <= self::level()
+}
+
+#[doc(hidden)]
+pub fn write(level: Level, args: fmt::Arguments) {
+    if enabled(level) {
+        eprintln!("{}", args);
+    }
+}
+
+/// Logs at [`Level::Info`].
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Info, format_args!($($arg)*))
+    };
+}
+
+/// Logs at [`Level::Debug`].
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Debug, format_args!($($arg)*))
+    };
+}
+
+/// Logs at [`Level::Trace`].
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Trace, format_args!($($arg)*))
+    };
+}
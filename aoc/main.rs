@@ -0,0 +1,639 @@
+use advent_of_code_2020::day01;
+use advent_of_code_2020::day01::Day01;
+use advent_of_code_2020::day02::policy;
+use advent_of_code_2020::day02::policy::{OccurrenceCount, PasswordPolicy, Positions};
+use advent_of_code_2020::day02::Day02;
+use advent_of_code_2020::day03;
+use advent_of_code_2020::day03::{Day03, Slope};
+use advent_of_code_2020::day08::debugger::Debugger;
+use advent_of_code_2020::day08::vm::{InstructionSet, Vm};
+use advent_of_code_2020::day08::Day08;
+use advent_of_code_2020::generate::generate;
+use advent_of_code_2020::info;
+use advent_of_code_2020::log;
+use advent_of_code_2020::log::Level;
+use advent_of_code_2020::memory;
+use advent_of_code_2020::memory::{CountingAllocator, Usage};
+use advent_of_code_2020::render;
+use advent_of_code_2020::render::{Format, RenderOptions};
+use advent_of_code_2020::runner::{default_input, json_error, run_parallel, Source, Status};
+use advent_of_code_2020::Solution;
+use std::env;
+use std::io;
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a day may run before it's reported as timed out, unless
+/// --timeout says otherwise; part 2 of day 20 never finishes on some inputs.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str = "Usage: aoc [DAYS...] [--part 1|2] [--input PATH] [--format table|json]
+           [--jobs N] [--timeout SECONDS] [--memory] [--log LEVEL] [--render DIR]
+           [--render-format gif|apng|terminal] [--fps N] [--frame-skip N]
+       aoc DAY --generate SIZE [--seed N]
+       aoc 8 --debug [--input PATH]
+       aoc 1 --audit [--part 1|2] [--input PATH]
+       aoc 2 --policy NAME... [--input PATH]
+       aoc 2 --violations [--policy NAME...] [--format table|json] [--input PATH]
+       aoc 3 (--slope SLOPE... | --search RIGHT,DOWN) [--input PATH]
+
+DAYS is a day number (7), an inclusive range (3-9) or `all`; defaults to all.
+A day given more than once runs once.
+The input defaults to dayNN/input.txt and can only be overridden for one day;
+--input - reads it from stdin.
+
+The other forms do something else with one day's input instead of solving
+it, one at a time; of them, only --violations can print JSON.
+
+--format json prints one JSON object per line instead of the table: a record
+per day and part with its answer, the parse and solve times in nanoseconds
+and the input path, or a record with an `error` for a day that failed.
+
+Days run concurrently, on as many threads as there are cores unless --jobs
+is given. A day that panics or runs for longer than --timeout, 60 seconds
+unless given and unlimited with 0, is reported as failed without stopping
+the others, and the table ends with a count of the days that passed and
+failed and the wall time of the whole run.
+
+--memory counts the heap allocations of each parse and part, adding the
+most memory in use at once and the number of allocations to the table, or
+parse_ and solve_peak_bytes and _allocations fields to the JSON records.
+
+--generate prints a random input for DAY instead of solving it, of roughly
+SIZE lines or items; see each day's generator for what SIZE counts. The same
+--seed always gives the same input; without one, the seed is random and
+reported on stderr.
+
+--debug loads the boot code of day 8 into a step debugger and reads its
+commands from stdin: step, back, continue, break, delete, watch, print,
+trace, state and quit.
+
+--audit lists every combination of two (part 1) and three (part 2) lines
+of the day 1 expense report summing to 2020, and fails unless each part has
+exactly one.
+
+--policy counts the passwords of day 2 that a policy accepts, and can be
+given more than once: count and positions are the rules of parts 1 and 2,
+length takes the numbers of each line as the allowed length, classes=N asks
+for N of lowercase, uppercase, digits and other characters, and
+forbid=TEXT,... rejects passwords containing any of the TEXTs.
+
+--violations lists every password of day 2 that the policies reject, those
+of both parts unless --policy is given, with its line and why; with --format
+json, as one object per line with its line, password, policy and reason.
+
+--slope counts the trees hit going down the day 3 forest at a slope, and can
+be given more than once to also multiply the counts as in part 2. RIGHT,DOWN
+jumps that many squares at a time, as in the puzzle; RIGHT/DOWN slides along
+the line instead, hitting every tree it passes through. --search tries every
+slope up to RIGHT squares right and DOWN down and lists the trees hit on the
+ways down with the fewest and the most; DOWN can be at most the forest's
+height, and RIGHT at most its width times DOWN.
+
+--log sets how much the solvers report on stderr: quiet, info (default),
+debug or trace. Answers always go to stdout.
+
+--render writes the visualisations of days 3, 11, 17, 20 and 24 to DIR as
+animated GIFs (default) or APNGs (--render-format apng), at 25 frames per
+second unless --fps is given. --frame-skip N keeps every (N+1)th frame.
+--render-format terminal plays them live on stderr instead, with no DIR
+needed, scaled to fit the COLUMNS and LINES of the terminal: each frame is
+drawn as the solver gets to it, slowing the solver down to --fps if need be.
+Days solved at the same time take turns at the terminal, and the wait for a
+turn counts towards --timeout.";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Table,
+    Json,
+}
+
+struct Options {
+    days: Vec<usize>,
+    parts: Vec<usize>,
+    input: Option<Source<'static>>,
+    output: Output,
+    jobs: usize,
+    timeout: Option<Duration>,
+    memory: bool,
+    debug: bool,
+    audit: bool,
+    policies: Vec<Box<dyn PasswordPolicy>>,
+    violations: bool,
+    slopes: Vec<Slope>,
+    search: Option<(i32, i32)>,
+    generate: Option<usize>,
+    seed: Option<u64>,
+    log_level: Level,
+    render: Option<RenderOptions>,
+}
+
+impl Options {
+    fn input_for(&self, day: usize) -> Source<'static> {
+        self.input
+            .clone()
+            .unwrap_or_else(|| Source::File(default_input(day)))
+    }
+}
+
+fn parse_days(spec: &str) -> Result<Vec<usize>, String> {
+    let parse_day = |day: &str| match day.parse::<usize>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("invalid day: {}", day)),
+    };
+
+    match spec.split_once('-') {
+        _ if spec == "all" => Ok((1..=25).collect()),
+        Some((first, last)) => match (parse_day(first)?, parse_day(last)?) {
+            (first, last) if first > last => Err(format!("invalid range of days: {}", spec)),
+            (first, last) => Ok((first..=last).collect()),
+        },
+        None => Ok(vec![parse_day(spec)?]),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut days = Vec::<usize>::new();
+    let mut parts = vec![1, 2];
+    let mut input = None;
+    let mut output = Output::Table;
+    let mut jobs = thread::available_parallelism().map_or(1, |jobs| jobs.get());
+    let mut timeout = Some(DEFAULT_TIMEOUT);
+    let mut memory = false;
+    let mut debug = false;
+    let mut audit = false;
+    let mut policies = Vec::new();
+    let mut violations = false;
+    let mut slopes = Vec::new();
+    let mut search = None;
+    let mut generate = None;
+    let mut seed = None;
+    let mut log_level = Level::Info;
+    let mut render_dir = None;
+    let mut render = RenderOptions::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "-p" | "--part" => {
+                parts = match args.next().map(|part| part.as_str()) {
+                    Some("1") => vec![1],
+                    Some("2") => vec![2],
+                    _ => return Err("--part expects 1 or 2".to_string()),
+                }
+            }
+            "-i" | "--input" => match args.next() {
+                Some(path) => input = Some(Source::from_arg(path)),
+                None => return Err("--input expects a path or -".to_string()),
+            },
+            "--format" => {
+                output = match args.next().map(|format| format.as_str()) {
+                    Some("table") => Output::Table,
+                    Some("json") => Output::Json,
+                    _ => return Err("--format expects table or json".to_string()),
+                }
+            }
+            "-j" | "--jobs" => {
+                jobs = match args.next().map(|jobs| jobs.parse::<usize>()) {
+                    Some(Ok(jobs)) if jobs > 0 => jobs,
+                    _ => return Err("--jobs expects a positive number".to_string()),
+                }
+            }
+            "--timeout" => match args.next().map(|seconds| seconds.parse::<f64>()) {
+                Some(Ok(0.0)) => timeout = None,
+                Some(Ok(seconds)) if seconds > 0.0 && seconds.is_finite() => {
+                    timeout = Some(Duration::from_secs_f64(seconds))
+                }
+                _ => return Err("--timeout expects a number of seconds, or 0".to_string()),
+            },
+            "--memory" => memory = true,
+            "--debug" => debug = true,
+            "--audit" => audit = true,
+            "--violations" => violations = true,
+            "--slope" => match args.next() {
+                Some(slope) => slopes.push(slope.parse()?),
+                None => return Err("--slope expects RIGHT,DOWN or RIGHT/DOWN".to_string()),
+            },
+            "--search" => {
+                let bounds = args.next().and_then(|bounds| bounds.split_once(','));
+                search = match bounds.map(|(right, down)| (right.parse(), down.parse())) {
+                    Some((Ok(right), Ok(down))) if right >= 0 && down > 0 => Some((right, down)),
+                    _ => return Err("--search expects RIGHT,DOWN with DOWN above 0".to_string()),
+                }
+            }
+            "--policy" => match args.next() {
+                Some(spec) => policies.push(policy::policy(spec)?),
+                None => return Err("--policy expects a policy name".to_string()),
+            },
+            "--log" => {
+                log_level = args
+                    .next()
+                    .ok_or_else(|| "--log expects quiet, info, debug or trace".to_string())?
+                    .parse::<Level>()?
+            }
+            "--render" => match args.next() {
+                Some(path) => render_dir = Some(PathBuf::from(path)),
+                None => return Err("--render expects a directory".to_string()),
+            },
+            "--render-format" => {
+                render.format = args
+                    .next()
+                    .ok_or_else(|| "--render-format expects gif, apng or terminal".to_string())?
+                    .parse::<Format>()?
+            }
+            "--fps" => {
+                render.frame_rate = match args.next().map(|fps| fps.parse::<u32>()) {
+                    Some(Ok(fps)) if fps > 0 => fps,
+                    _ => return Err("--fps expects a positive number".to_string()),
+                }
+            }
+            "--generate" => match args.next().map(|size| size.parse::<usize>()) {
+                Some(Ok(size)) => generate = Some(size),
+                _ => return Err("--generate expects a size".to_string()),
+            },
+            "--seed" => match args.next().map(|seed| seed.parse::<u64>()) {
+                Some(Ok(number)) => seed = Some(number),
+                _ => return Err("--seed expects a number".to_string()),
+            },
+            "--frame-skip" => {
+                render.frame_skip = match args.next().map(|skip| skip.parse::<usize>()) {
+                    Some(Ok(skip)) => skip,
+                    _ => return Err("--frame-skip expects a number".to_string()),
+                }
+            }
+            spec => days.extend(parse_days(spec)?),
+        }
+    }
+
+    if days.is_empty() {
+        days = (1..=25).collect();
+    }
+    // Each day runs once, where it was first given
+    let mut given = [false; 26];
+    days.retain(|&day| !mem::replace(&mut given[day], true));
+    // Each of these replaces the usual run with its own, except that
+    // --policy picks the policies --violations checks
+    let modes = [
+        ("--generate", generate.is_some()),
+        ("--debug", debug),
+        ("--audit", audit),
+        ("--policy", !policies.is_empty() && !violations),
+        ("--violations", violations),
+        ("--slope", !slopes.is_empty()),
+        ("--search", search.is_some()),
+    ]
+    .iter()
+    .filter(|&&(_, given)| given)
+    .map(|&(mode, _)| mode)
+    .collect::<Vec<_>>();
+    if let [first, second, ..] = modes.as_slice() {
+        return Err(format!("{} can't be used with {}", first, second));
+    }
+    match modes.as_slice() {
+        [mode] if output == Output::Json && *mode != "--violations" => {
+            return Err(format!("--format json can't be used with {}", mode))
+        }
+        _ => {}
+    }
+    if input.is_some() && days.len() != 1 {
+        return Err("--input can only be used with a single day".to_string());
+    }
+    if generate.is_some() && days.len() != 1 {
+        return Err("--generate can only be used with a single day".to_string());
+    }
+    if debug && days != [8] {
+        return Err("--debug only works with day 8".to_string());
+    }
+    if debug && matches!(input, Some(Source::Stdin)) {
+        return Err("--debug reads commands from stdin, so the program needs a file".to_string());
+    }
+    if audit && days != [1] {
+        return Err("--audit only works with day 1".to_string());
+    }
+    if !policies.is_empty() && days != [2] {
+        return Err("--policy only works with day 2".to_string());
+    }
+    if violations && days != [2] {
+        return Err("--violations only works with day 2".to_string());
+    }
+    if (!slopes.is_empty() || search.is_some()) && days != [3] {
+        return Err("--slope and --search only work with day 3".to_string());
+    }
+    if violations && policies.is_empty() {
+        policies = vec![Box::new(OccurrenceCount), Box::new(Positions)];
+    }
+
+    let render = match render_dir {
+        Some(output_dir) => Some(RenderOptions {
+            output_dir,
+            ..render
+        }),
+        None if render.format == Format::Terminal => Some(render),
+        None => None,
+    };
+
+    Ok(Options {
+        days,
+        parts,
+        input,
+        output,
+        jobs,
+        timeout,
+        memory,
+        debug,
+        audit,
+        policies,
+        violations,
+        slopes,
+        search,
+        generate,
+        seed,
+        log_level,
+        render,
+    })
+}
+
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{}B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.2}KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.2}MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// The peak and allocation columns of a table row, if memory was counted.
+fn memory_columns(usage: Option<Usage>) -> String {
+    match usage {
+        Some(usage) => format!(
+            "  {:>10}  {:>8}",
+            format_bytes(usage.peak),
+            usage.allocations
+        ),
+        None => String::new(),
+    }
+}
+
+/// Reads and parses the input of `S` from `source`.
+fn load<S: Solution>(source: &Source) -> Result<S::Input, String> {
+    let contents = source
+        .read()
+        .map_err(|error| format!("{}: {}", source, error))?;
+    S::parse(&contents).map_err(|error| source.locate(error).to_string())
+}
+
+/// Runs the day 8 debugger on the program in `source` with commands from
+/// stdin.
+fn debug(source: &Source) -> Result<(), String> {
+    let program = load::<Day08>(source)?;
+    let instruction_set = InstructionSet::default();
+    let mut debugger = Debugger::new(Vm::new(&program, &instruction_set));
+    debugger
+        .interact(io::stdin().lock(), &mut io::stdout())
+        .map_err(|error| error.to_string())
+}
+
+/// Lists the combinations of the day 1 expense report in `source` that
+/// sum to 2020 for each of `parts`, and whether each has exactly one.
+fn audit(source: &Source, parts: &[usize]) -> Result<bool, String> {
+    let expenses = load::<Day01>(source)?;
+    let ks = parts.iter().map(|part| part + 1).collect::<Vec<_>>();
+    let (report, unique) = day01::audit(&expenses, &ks, 2020);
+    print!("{}", report);
+    Ok(unique)
+}
+
+/// Counts the passwords of the day 2 database in `source` that each of
+/// `policies` accepts.
+fn check_policies(source: &Source, policies: &[Box<dyn PasswordPolicy>]) -> Result<(), String> {
+    let entries = load::<Day02>(source)?;
+    print!("{}", policy::report(policies, &entries));
+    Ok(())
+}
+
+/// Lists the passwords of the day 2 database in `source` that `policies`
+/// reject.
+fn report_violations(
+    source: &Source,
+    policies: &[Box<dyn PasswordPolicy>],
+    output: Output,
+) -> Result<(), String> {
+    let entries = load::<Day02>(source)?;
+    let violations = policy::violations(policies, &entries);
+    for violation in &violations {
+        match output {
+            Output::Table => println!("{}", violation),
+            Output::Json => println!("{}", violation.to_json()),
+        }
+    }
+    let summary = policy::summary(&violations, entries.len());
+    match output {
+        Output::Table => println!("\n{}", summary),
+        Output::Json => info!("{}", summary),
+    }
+    Ok(())
+}
+
+/// Goes down the day 3 forest in `source` at each of `slopes`, or at those
+/// within `search` with the fewest and most trees.
+fn toboggan(source: &Source, slopes: &[Slope], search: Option<(i32, i32)>) -> Result<(), String> {
+    let forest = load::<Day03>(source)?;
+    let report = match search {
+        Some((max_right, max_down)) => day03::search_report(&forest, max_right, max_down)?,
+        None => day03::slopes_report(&forest, slopes)?,
+    };
+    print!("{}", report);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("aoc: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    log::set_level(options.log_level);
+    if let Some(size) = options.generate {
+        let seed = options.seed.unwrap_or_else(rand::random);
+        if options.seed.is_none() {
+            info!("seed: {}", seed);
+        }
+        print!("{}", generate(options.days[0], size, seed).unwrap());
+        return;
+    }
+    if options.debug {
+        if let Err(message) = debug(&options.input_for(8)) {
+            eprintln!("aoc: {}", message);
+            process::exit(1);
+        }
+        return;
+    }
+    if options.audit {
+        match audit(&options.input_for(1), &options.parts) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            Err(message) => {
+                eprintln!("aoc: {}", message);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if !options.slopes.is_empty() || options.search.is_some() {
+        if let Err(message) = toboggan(&options.input_for(3), &options.slopes, options.search) {
+            eprintln!("aoc: {}", message);
+            process::exit(1);
+        }
+        return;
+    }
+    if options.violations {
+        let source = options.input_for(2);
+        if let Err(message) = report_violations(&source, &options.policies, options.output) {
+            eprintln!("aoc: {}", message);
+            process::exit(1);
+        }
+        return;
+    }
+    if !options.policies.is_empty() {
+        if let Err(message) = check_policies(&options.input_for(2), &options.policies) {
+            eprintln!("aoc: {}", message);
+            process::exit(1);
+        }
+        return;
+    }
+    if let Some(options) = options.render.clone() {
+        render::enable(options);
+    }
+    if options.memory {
+        memory::enable();
+    }
+
+    let (mut passed, mut failed, mut panicked, mut timed_out) = (0, 0, 0, 0);
+    let mut total = Duration::default();
+    let started = Instant::now();
+
+    if options.output == Output::Table {
+        let memory_headers = if options.memory {
+            format!("  {:>10}  {:>8}", "Peak", "Allocs")
+        } else {
+            String::new()
+        };
+        println!(
+            "{:>3}  {:<5}  {:<40}  {:>10}{}",
+            "Day", "Part", "Answer", "Time", memory_headers
+        );
+    }
+    let days = options
+        .days
+        .iter()
+        .map(|&day| (day, options.input_for(day)))
+        .collect();
+    run_parallel(
+        days,
+        &options.parts,
+        options.jobs,
+        options.timeout,
+        |outcome| {
+            let day = outcome.day;
+            let report = match &outcome.status {
+                Status::Solved(report) => report,
+                status => {
+                    match status {
+                        Status::Panicked(_) => panicked += 1,
+                        Status::TimedOut => timed_out += 1,
+                        _ => failed += 1,
+                    }
+                    let message = outcome.error().unwrap();
+                    match options.output {
+                        Output::Table => println!("{:>3}  error: {}", day, message),
+                        Output::Json => {
+                            println!("{}", json_error(day, &options.input_for(day), &message))
+                        }
+                    }
+                    return;
+                }
+            };
+            passed += 1;
+            if options.output == Output::Json {
+                for record in report.json_records(&options.input_for(day)) {
+                    println!("{}", record);
+                }
+                return;
+            }
+
+            total += report.parse_time;
+            println!(
+                "{:>3}  {:<5}  {:<40}  {:>10}{}",
+                day,
+                "parse",
+                "",
+                format_duration(report.parse_time),
+                memory_columns(report.parse_memory)
+            );
+            for part in &report.parts {
+                total += part.elapsed;
+                println!(
+                    "{:>3}  {:<5}  {:<40}  {:>10}{}",
+                    day,
+                    part.part,
+                    part.answer,
+                    format_duration(part.elapsed),
+                    memory_columns(part.memory)
+                );
+            }
+        },
+    );
+
+    let failures = failed + panicked + timed_out;
+    let summary = format!(
+        "{} passed, {} failed, {} panicked, {} timed out in {}",
+        passed,
+        failed,
+        panicked,
+        timed_out,
+        format_duration(started.elapsed())
+    );
+    match options.output {
+        Output::Table => {
+            println!(
+                "{:>3}  {:<5}  {:<40}  {:>10}",
+                "",
+                "total",
+                "",
+                format_duration(total)
+            );
+            println!("\n{}", summary);
+        }
+        Output::Json => info!("{}", summary),
+    }
+
+    if failures > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,98 @@
+//! Heap usage of the solvers, counted by a global allocator.
+//!
+//! [`CountingAllocator`] wraps the system allocator and keeps, for each
+//! thread, the bytes it has allocated and not yet freed, the most it ever
+//! had, and how many allocations it made. A binary opts in by installing it
+//! as its `#[global_allocator]` and calling [`enable`]; without both,
+//! [`measure`] reports nothing.
+//!
+//! Counts are per thread so that days running in parallel don't see each
+//! other's allocations. Memory freed on another thread than it was
+//! allocated on is subtracted from the thread that frees it.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct CountingAllocator;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record(size: isize, allocations: usize) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    // Allocations while a thread is being torn down can't be counted
+    let _ = CURRENT.try_with(|current| {
+        current.set(current.get() + size);
+        PEAK.with(|peak| peak.set(peak.get().max(current.get())));
+        ALLOCATIONS.with(|count| count.set(count.get() + allocations));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as isize, 1);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size() as isize, 1);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(-(layout.size() as isize), 0);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size as isize - layout.size() as isize, 1);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Starts counting; only has an effect with [`CountingAllocator`]
+/// installed.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The most heap memory in use at once, in bytes, above what was in use
+    /// before.
+    pub peak: usize,
+    /// Allocations and reallocations made.
+    pub allocations: usize,
+}
+
+/// Runs `f` and reports the heap memory it used on this thread, or `None`
+/// if counting is off.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Usage>) {
+    if !is_enabled() {
+        return (f(), None);
+    }
+
+    let start = CURRENT.with(Cell::get);
+    let allocations = ALLOCATIONS.with(Cell::get);
+    let outer_peak = PEAK.with(|peak| peak.replace(start));
+    let result = f();
+    let peak = PEAK.with(|peak| peak.replace(outer_peak.max(peak.get())));
+
+    let usage = Usage {
+        peak: (peak - start).max(0) as usize,
+        allocations: ALLOCATIONS.with(Cell::get) - allocations,
+    };
+    (result, Some(usage))
+}
@@ -0,0 +1,283 @@
+//! Extracts the worked examples from a day's puzzle description.
+//!
+//! The `dayNN/README` files are plain-text copies of the puzzle pages, so
+//! this works on the conventions of the puzzle text rather than on markup:
+//!
+//! - An example input is the block of lines after a sentence that ends in a
+//!   colon and mentions an example ("For example, suppose you have the
+//!   following list:"). The block runs until the first line that reads like
+//!   prose.
+//! - Its answer is the last value in the last sentence that states a result
+//!   ("so the correct answer is 514579") before the next example. Blocks in
+//!   between that come before any result, such as the intermediate states of
+//!   a simulation, are illustrations of the same example.
+//! - Part two starts out on the last example of part one, since it is
+//!   usually revisited ("using the above example again").
+//! - Lines of the form `1 + 2 * 3 becomes 7.` are examples of their own.
+//!
+//! These are heuristics: they find most examples, and callers should expect
+//! the odd misread.
+
+/// An example input together with the answer the puzzle text gives for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub part: usize,
+    pub input: String,
+    pub answer: String,
+}
+
+/// Phrases that mark a sentence as stating the result of an example.
+const RESULT_MARKERS: [&str; 11] = [
+    "answer",
+    "in this example",
+    "in the above example",
+    "in this larger example",
+    "in the above game",
+    "according to the above",
+    "a total of",
+    "you count",
+    "produces",
+    "producing",
+    "score is",
+];
+
+enum Chunk {
+    Prose(String),
+    Example(String),
+    Worked(Vec<(String, String)>),
+}
+
+/// Splits prose into sentences, also ending one at a colon that ends a line.
+fn sentences(prose: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = Vec::new();
+    for line in prose.lines() {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        for (index, word) in words.iter().enumerate() {
+            current.push(*word);
+            if word.ends_with(['.', '!', '?']) || (index + 1 == words.len() && word.ends_with(':'))
+            {
+                sentences.push(current.join(" "));
+                current.clear();
+            }
+        }
+    }
+    if !current.is_empty() {
+        sentences.push(current.join(" "));
+    }
+    sentences
+}
+
+/// Lowercases a sentence and drops parenthesised asides, which tend to
+/// carry intermediate numbers rather than results.
+fn normalize(sentence: &str) -> String {
+    let mut depth = 0;
+    sentence
+        .chars()
+        .filter(|&c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => {
+                    depth -= 1;
+                    return false;
+                }
+                _ => (),
+            }
+            depth == 0
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn is_result(sentence: &str) -> bool {
+    RESULT_MARKERS
+        .iter()
+        .any(|marker| sentence.contains(marker))
+}
+
+/// Examples referring back to one "above" repeat it rather than add one.
+fn introduces_example(sentence: &str) -> bool {
+    let sentence = normalize(sentence);
+    sentence.ends_with(':')
+        && (sentence.contains("example") || sentence.contains("suppose"))
+        && !sentence.contains("above")
+        && !is_result(&sentence)
+}
+
+/// The value a result sentence ends on: a number or a list like `a,b,c`.
+fn stated_result(sentence: &str) -> Option<String> {
+    let sentence = normalize(sentence);
+    if !is_result(&sentence) {
+        return None;
+    }
+
+    sentence
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| ".,:;!?".contains(c)))
+        .rfind(|word| {
+            !word.is_empty()
+                && (word.chars().all(|c| c.is_ascii_digit())
+                    || (word.contains(',')
+                        && word.chars().all(|c| c.is_ascii_lowercase() || c == ',')))
+        })
+        .map(str::to_string)
+}
+
+/// Sentences start with a capital letter followed by lowercase letters,
+/// which puzzle inputs rarely do; short labels like `Player 1:` are kept.
+fn is_prose(line: &str) -> bool {
+    let mut chars = line.chars();
+    let starts_like_sentence = match (chars.next(), chars.next()) {
+        (Some(first), Some(second)) => first.is_ascii_uppercase() && second.is_ascii_lowercase(),
+        _ => false,
+    };
+    starts_like_sentence && line.split_whitespace().count() >= 3
+}
+
+/// Splits `input becomes answer.` into its two halves.
+fn worked_line(line: &str) -> Option<(String, String)> {
+    let (input, answer) = line.trim().rsplit_once(" becomes ")?;
+    let answer = answer.strip_suffix('.')?;
+    if answer.is_empty() || !answer.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((input.to_string() + "\n", answer.to_string()))
+}
+
+/// Splits one part of the description into prose and example blocks.
+fn chunks(section: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut prose = String::new();
+    let mut lines = section.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        prose.push_str(line);
+        prose.push('\n');
+        if !line.trim_end().ends_with(':') {
+            continue;
+        }
+
+        let mut block = Vec::new();
+        while let Some(line) = lines.peek() {
+            if is_prose(line) {
+                break;
+            }
+            block.push(*line);
+            lines.next();
+        }
+        let first = block.iter().position(|line| !line.trim().is_empty());
+        let last = block.iter().rposition(|line| !line.trim().is_empty());
+        let content = match (first, last) {
+            (Some(first), Some(last)) => &block[first..=last],
+            _ => &[],
+        };
+
+        let worked = content
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| worked_line(line))
+            .collect::<Option<Vec<_>>>()
+            .filter(|worked| !worked.is_empty());
+        let introduced = sentences(&prose)
+            .last()
+            .map_or(false, |sentence| introduces_example(sentence));
+
+        let chunk = match worked {
+            Some(worked) => Chunk::Worked(worked),
+            None if introduced && !content.is_empty() => Chunk::Example(content.join("\n") + "\n"),
+            None => {
+                for line in block {
+                    prose.push_str(line);
+                    prose.push('\n');
+                }
+                continue;
+            }
+        };
+        chunks.push(Chunk::Prose(prose.split_off(0)));
+        chunks.push(chunk);
+    }
+    chunks.push(Chunk::Prose(prose));
+    chunks
+}
+
+/// Pairs the examples in one part of the description with their results.
+///
+/// `revisited` is the example carried over from part one; it is answered by
+/// results stated before part two introduces an example of its own.
+fn section_examples(part: usize, section: &str, revisited: Option<String>) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut carried_over = revisited.is_some();
+    let mut current = revisited;
+    let mut answer = None;
+
+    for chunk in chunks(section) {
+        match chunk {
+            Chunk::Prose(prose) => {
+                if current.is_some() {
+                    answer = sentences(&prose)
+                        .iter()
+                        .filter_map(|sentence| stated_result(sentence))
+                        .next_back()
+                        .or(answer);
+                }
+            }
+            Chunk::Example(input) => {
+                if answer.is_some() || carried_over {
+                    if let (Some(input), Some(answer)) = (current.take(), answer.take()) {
+                        examples.push(Example {
+                            part,
+                            input,
+                            answer,
+                        });
+                    }
+                    current = None;
+                    carried_over = false;
+                }
+                if current.is_none() {
+                    current = Some(input);
+                }
+            }
+            Chunk::Worked(worked) => {
+                examples.extend(worked.into_iter().map(|(input, answer)| Example {
+                    part,
+                    input,
+                    answer,
+                }));
+            }
+        }
+    }
+
+    if let (Some(input), Some(answer)) = (current, answer) {
+        examples.push(Example {
+            part,
+            input,
+            answer,
+        });
+    }
+    examples
+}
+
+/// Finds the examples and their stated answers in a day's README.
+pub fn examples(readme: &str) -> Vec<Example> {
+    let readme = readme
+        .lines()
+        .filter(|line| !line.starts_with("Your puzzle answer was"))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let (part1, part2) = match readme.find("--- Part Two ---") {
+        Some(index) => readme.split_at(index),
+        None => (readme.as_str(), ""),
+    };
+
+    let last_example = chunks(part1)
+        .into_iter()
+        .filter_map(|chunk| match chunk {
+            Chunk::Example(input) => Some(input),
+            _ => None,
+        })
+        .next_back();
+
+    let mut examples = section_examples(1, part1, None);
+    examples.extend(section_examples(2, part2, last_example));
+    examples
+}
@@ -0,0 +1,567 @@
+//! Renders puzzle visualisations to animated GIF or APNG files, or plays
+//! them live in the terminal.
+//!
+//! A solver draws each step of its visualisation on a [`Canvas`] and adds it
+//! to an [`Animation`], which writes all of them to one file when finished,
+//! or draws each on stderr with ANSI colours as it is added, no faster than
+//! the frame rate.
+//! Rendering is off until [`enable`] is called, typically by the `aoc`
+//! runner, so that tests and benchmarks don't pay for drawing frames.
+
+use crate::grid::Coordinate;
+use gif::{ExtensionData, Repeat};
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub type Color = (u8, u8, u8);
+
+pub const WHITE: Color = (0xFF, 0xFF, 0xFF);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    Apng,
+    /// Played on stderr as the frames are drawn rather than written to a
+    /// file.
+    Terminal,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Format, String> {
+        match format {
+            "gif" => Ok(Format::Gif),
+            "apng" => Ok(Format::Apng),
+            "terminal" => Ok(Format::Terminal),
+            _ => Err(format!(
+                "unknown format `{}`, expected gif, apng or terminal",
+                format
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Gif => write!(f, "gif"),
+            Format::Apng => write!(f, "apng"),
+            Format::Terminal => write!(f, "terminal"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Directory the animations are written to, created if missing; unused
+    /// when playing them in the terminal.
+    pub output_dir: PathBuf,
+    pub format: Format,
+    /// Frames per second.
+    pub frame_rate: u32,
+    /// Frames dropped after each one that is kept; 0 keeps every frame.
+    pub frame_skip: usize,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            output_dir: PathBuf::from("frames"),
+            format: Format::Gif,
+            frame_rate: 25,
+            frame_skip: 0,
+        }
+    }
+}
+
+static OPTIONS: RwLock<Option<RenderOptions>> = RwLock::new(None);
+
+/// Held by the animation playing in the terminal, so that animations of
+/// days solved at the same time take turns rather than drawing over each
+/// other.
+static TERMINAL: Mutex<()> = Mutex::new(());
+
+/// Turns rendering on for the animations started from now on.
+pub fn enable(options: RenderOptions) {
+    *OPTIONS.write().unwrap() = Some(options);
+}
+
+pub fn disable() {
+    *OPTIONS.write().unwrap() = None;
+}
+
+/// A picture of `width` by `height` cells, each drawn as a square of
+/// `scale` by `scale` pixels.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    scale: u32,
+    background: Color,
+    cells: Vec<Color>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, scale: u32, background: Color) -> Canvas {
+        Canvas {
+            width,
+            height,
+            scale,
+            background,
+            cells: vec![background; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Colors one cell; cells off the canvas are ignored.
+    pub fn put(&mut self, (x, y): Coordinate, color: Color) {
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = color;
+        }
+    }
+
+    /// Draws a sprite given as rows of characters with its top left corner
+    /// at `top_left`. `palette` colors the characters; any other character
+    /// is transparent.
+    pub fn stamp(&mut self, top_left: Coordinate, sprite: &str, palette: &[(char, Color)]) {
+        for (y, row) in sprite.lines().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if let Some((_, color)) = palette.iter().find(|(symbol, _)| *symbol == c) {
+                    self.put((top_left.0 + x as i32, top_left.1 + y as i32), *color);
+                }
+            }
+        }
+    }
+}
+
+/// A frame stored as runs of palette indices, scaled to its final size.
+struct Frame {
+    width: u32,
+    height: u32,
+    runs: Vec<(u32, u8)>,
+}
+
+impl Frame {
+    /// Writes the frame's pixels into a `width` wide buffer that is at least
+    /// as large as the frame.
+    fn paint(&self, pixels: &mut [u8], width: u32) {
+        let mut position = 0;
+        for &(length, index) in &self.runs {
+            for offset in position..position + length {
+                let (x, y) = (offset % self.width, offset / self.width);
+                pixels[(y * width + x) as usize] = index;
+            }
+            position += length;
+        }
+    }
+}
+
+/// A terminal an animation is played on, and how far it has got.
+struct Terminal {
+    w: Box<dyn Write>,
+    /// `(columns, lines)` in characters.
+    size: (u32, u32),
+    frame_rate: u32,
+    /// The turn at the terminal, taken with the first frame.
+    turn: Option<MutexGuard<'static, ()>>,
+    started: Instant,
+    shown: u32,
+    /// Lines the last frame took, to move back up over.
+    rows: u32,
+    error: Option<io::Error>,
+}
+
+impl Terminal {
+    /// Draws `canvas` over the previous frame, once it is time for the next
+    /// one, each character showing two pixels stacked on top of each other
+    /// with 24-bit ANSI colours. Canvases too large for the terminal are
+    /// scaled down to fit.
+    fn draw(&mut self, canvas: &Canvas) -> io::Result<()> {
+        if self.turn.is_none() {
+            self.turn = Some(TERMINAL.lock().unwrap_or_else(PoisonError::into_inner));
+            self.started = Instant::now();
+            // Hide the cursor while playing
+            write!(self.w, "\x1b[?25l")?;
+        }
+
+        let (columns, lines) = self.size;
+        let (width, height) = (canvas.width * canvas.scale, canvas.height * canvas.scale);
+        let div_ceil = |a: u32, b: u32| (a + b - 1) / b;
+        let step = div_ceil(width, columns.max(1))
+            .max(div_ceil(height, 2 * lines.saturating_sub(1).max(1)))
+            .max(1);
+        let (columns, rows) = (div_ceil(width, step), div_ceil(height, 2 * step));
+        let color = |(x, y): (u32, u32)| {
+            let (x, y) = (x * step / canvas.scale, y * step / canvas.scale);
+            if y < canvas.height {
+                canvas.cells[(y * canvas.width + x) as usize]
+            } else {
+                canvas.background
+            }
+        };
+
+        let mut text = String::new();
+        if self.rows > 0 {
+            text.push_str(&format!("\x1b[{}F", self.rows));
+        }
+        for row in 0..rows {
+            let mut previous = None;
+            for x in 0..columns {
+                let cell = (color((x, 2 * row)), color((x, 2 * row + 1)));
+                if previous != Some(cell) {
+                    let ((r, g, b), (r2, g2, b2)) = cell;
+                    text.push_str(&format!(
+                        "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                        r, g, b, r2, g2, b2
+                    ));
+                    previous = Some(cell);
+                }
+                text.push('▀');
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        // Clears what's left of a larger previous frame
+        text.push_str("\x1b[J");
+
+        let period = Duration::from_secs(1) / self.frame_rate.max(1);
+        if let Some(wait) =
+            (self.started + period * self.shown).checked_duration_since(Instant::now())
+        {
+            thread::sleep(wait);
+        }
+        self.w.write_all(text.as_bytes())?;
+        self.w.flush()?;
+        self.shown += 1;
+        self.rows = rows;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        if self.turn.is_some() {
+            write!(self.w, "\x1b[0m\x1b[?25h")?;
+            self.w.flush()?;
+        }
+        self.turn = None;
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    /// Shows the cursor again if the animation wasn't finished, say because
+    /// its solver panicked.
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// The frames of one visualisation, written to `<output_dir>/<name>.gif`
+/// or `.png` when finished, or played in the terminal as they are added.
+pub struct Animation {
+    name: String,
+    options: Option<RenderOptions>,
+    terminal: Option<Terminal>,
+    frames: Vec<Frame>,
+    palette: Vec<Color>,
+    background: u8,
+    offered: usize,
+}
+
+impl Animation {
+    /// Starts an animation, which does nothing if rendering is off.
+    pub fn new(name: &str) -> Animation {
+        let options = OPTIONS.read().unwrap().clone();
+        let terminal = match &options {
+            Some(options) if options.format == Format::Terminal => Some(Terminal {
+                w: Box::new(io::stderr()),
+                size: terminal_size(),
+                frame_rate: options.frame_rate,
+                turn: None,
+                started: Instant::now(),
+                shown: 0,
+                rows: 0,
+                error: None,
+            }),
+            _ => None,
+        };
+        Animation {
+            name: name.to_string(),
+            options,
+            terminal,
+            frames: Vec::new(),
+            palette: Vec::new(),
+            background: 0,
+            offered: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.options.is_some()
+    }
+
+    /// Plays the animation on `w`, a terminal of `(columns, lines)`
+    /// characters, rather than on stderr, if it is played in the terminal.
+    pub fn play_on(mut self, w: impl Write + 'static, size: (u32, u32)) -> Animation {
+        if let Some(terminal) = &mut self.terminal {
+            terminal.w = Box::new(w);
+            terminal.size = size;
+        }
+        self
+    }
+
+    /// Adds the canvas `draw` returns as the next frame. `draw` isn't called
+    /// when rendering is off or the frame is skipped.
+    pub fn frame(&mut self, draw: impl FnOnce() -> Canvas) {
+        let frame_skip = match &self.options {
+            Some(options) => options.frame_skip,
+            None => return,
+        };
+        let offered = self.offered;
+        self.offered += 1;
+        if offered % (frame_skip + 1) != 0 {
+            return;
+        }
+
+        let canvas = draw();
+        if let Some(terminal) = &mut self.terminal {
+            // A terminal that can't be written to isn't played on any more
+            if terminal.error.is_none() {
+                terminal.error = terminal.draw(&canvas).err();
+            }
+            return;
+        }
+        if self.frames.is_empty() {
+            self.background = self.palette_index(canvas.background);
+        }
+
+        let mut runs = Vec::<(u32, u8)>::new();
+        for row in canvas.cells.chunks(canvas.width.max(1) as usize) {
+            for _ in 0..canvas.scale {
+                for &color in row {
+                    let index = self.palette_index(color);
+                    match runs.last_mut() {
+                        Some((length, last)) if *last == index => *length += canvas.scale,
+                        _ => runs.push((canvas.scale, index)),
+                    }
+                }
+            }
+        }
+
+        self.frames.push(Frame {
+            width: canvas.width * canvas.scale,
+            height: canvas.height * canvas.scale,
+            runs,
+        });
+    }
+
+    /// Writes the animation, returning where it went, or `None` if
+    /// rendering is off, there were no frames or it was played in the
+    /// terminal.
+    pub fn finish(mut self) -> io::Result<Option<PathBuf>> {
+        if let Some(terminal) = &mut self.terminal {
+            terminal.finish()?;
+            return Ok(None);
+        }
+        let options = match &self.options {
+            Some(options) if !self.frames.is_empty() => options,
+            _ => return Ok(None),
+        };
+
+        let extension = match options.format {
+            Format::Gif => "gif",
+            Format::Apng => "png",
+            Format::Terminal => unreachable!("terminal animations are played as they go"),
+        };
+        fs::create_dir_all(&options.output_dir)?;
+        let path = options
+            .output_dir
+            .join(format!("{}.{}", self.name, extension));
+        let mut file = BufWriter::new(File::create(&path)?);
+        if options.format == Format::Gif {
+            self.write_gif(&mut file, options.frame_rate)?;
+        } else {
+            self.write_apng(&mut file, options.frame_rate)?;
+        }
+        file.flush()?;
+        Ok(Some(path))
+    }
+
+    /// Index of `color` in the palette, adding it if there is room and
+    /// falling back on the closest color otherwise.
+    fn palette_index(&mut self, color: Color) -> u8 {
+        if let Some(index) = self.palette.iter().position(|&known| known == color) {
+            return index as u8;
+        }
+        if self.palette.len() < 256 {
+            self.palette.push(color);
+            return (self.palette.len() - 1) as u8;
+        }
+
+        let distance = |known: &Color| {
+            let channel = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            channel(known.0, color.0) + channel(known.1, color.1) + channel(known.2, color.2)
+        };
+        (0..self.palette.len())
+            .min_by_key(|&index| distance(&self.palette[index]))
+            .unwrap() as u8
+    }
+
+    /// The size of the animation, large enough for every frame; smaller
+    /// frames are padded on the right and bottom with the background.
+    fn size(&self) -> (u32, u32) {
+        let width = self
+            .frames
+            .iter()
+            .map(|frame| frame.width)
+            .max()
+            .unwrap_or(0);
+        let height = self
+            .frames
+            .iter()
+            .map(|frame| frame.height)
+            .max()
+            .unwrap_or(0);
+        (width, height)
+    }
+
+    fn padded_frames(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let (width, height) = self.size();
+        self.frames.iter().map(move |frame| {
+            let mut pixels = vec![self.background; width as usize * height as usize];
+            frame.paint(&mut pixels, width);
+            pixels
+        })
+    }
+
+    fn write_gif(&self, w: &mut impl Write, frame_rate: u32) -> io::Result<()> {
+        let (width, height) = self.size();
+        if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frames are too large for a GIF",
+            ));
+        }
+
+        let palette = self
+            .palette
+            .iter()
+            .flat_map(|&(r, g, b)| vec![r, g, b])
+            .collect::<Vec<u8>>();
+        let mut encoder = gif::Encoder::new(w, width as u16, height as u16, &palette)?;
+        encoder.write_extension(ExtensionData::Repetitions(Repeat::Infinite))?;
+
+        // GIF delays are in hundredths of a second
+        let delay = (100 / frame_rate.max(1)).max(1) as u16;
+        for pixels in self.padded_frames() {
+            let mut frame =
+                gif::Frame::from_indexed_pixels(width as u16, height as u16, &pixels, None);
+            frame.delay = delay;
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an APNG: a PNG whose image data is the first frame, followed
+    /// by the frame control and frame data chunks of the animation.
+    fn write_apng(&self, w: &mut impl Write, frame_rate: u32) -> io::Result<()> {
+        let (width, height) = self.size();
+
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // 8-bit palette indices, no interlacing
+        header.extend_from_slice(&[8, 3, 0, 0, 0]);
+        write_chunk(w, b"IHDR", &header)?;
+
+        let palette = self
+            .palette
+            .iter()
+            .flat_map(|&(r, g, b)| vec![r, g, b])
+            .collect::<Vec<u8>>();
+        write_chunk(w, b"PLTE", &palette)?;
+
+        let mut animation_control = Vec::new();
+        animation_control.extend_from_slice(&(self.frames.len() as u32).to_be_bytes());
+        // Loop forever
+        animation_control.extend_from_slice(&0u32.to_be_bytes());
+        write_chunk(w, b"acTL", &animation_control)?;
+
+        let mut sequence = 0u32;
+        for (index, pixels) in self.padded_frames().enumerate() {
+            let mut frame_control = Vec::new();
+            frame_control.extend_from_slice(&sequence.to_be_bytes());
+            frame_control.extend_from_slice(&width.to_be_bytes());
+            frame_control.extend_from_slice(&height.to_be_bytes());
+            frame_control.extend_from_slice(&0u32.to_be_bytes());
+            frame_control.extend_from_slice(&0u32.to_be_bytes());
+            frame_control.extend_from_slice(&1u16.to_be_bytes());
+            frame_control.extend_from_slice(&(frame_rate.max(1) as u16).to_be_bytes());
+            // Neither dispose nor blend, each frame covers the whole image
+            frame_control.extend_from_slice(&[0, 0]);
+            write_chunk(w, b"fcTL", &frame_control)?;
+            sequence += 1;
+
+            let mut scanlines = Vec::with_capacity(pixels.len() + height as usize);
+            for row in pixels.chunks(width.max(1) as usize) {
+                // No filtering
+                scanlines.push(0);
+                scanlines.extend_from_slice(row);
+            }
+            let data = deflate::deflate_bytes_zlib(&scanlines);
+
+            if index == 0 {
+                write_chunk(w, b"IDAT", &data)?;
+            } else {
+                let mut frame_data = sequence.to_be_bytes().to_vec();
+                frame_data.extend_from_slice(&data);
+                write_chunk(w, b"fdAT", &frame_data)?;
+                sequence += 1;
+            }
+        }
+
+        write_chunk(w, b"IEND", &[])
+    }
+}
+
+/// The terminal's size in characters from `COLUMNS` and `LINES`, which
+/// shells set but don't always export, defaulting to 80 by 24.
+fn terminal_size() -> (u32, u32) {
+    let variable = |name, default| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    (variable("COLUMNS", 80), variable("LINES", 24))
+}
+
+fn write_chunk(w: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc.finalize().to_be_bytes())
+}
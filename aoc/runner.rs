@@ -0,0 +1,383 @@
+use crate::memory;
+use crate::memory::Usage;
+use crate::{ParseError, Solution};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fmt::Write;
+use std::io;
+use std::io::Read;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct PartResult {
+    pub part: usize,
+    pub answer: String,
+    pub elapsed: Duration,
+    /// Heap usage, when counting allocations.
+    pub memory: Option<Usage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub day: usize,
+    pub parse_time: Duration,
+    pub parse_memory: Option<Usage>,
+    pub parts: Vec<PartResult>,
+}
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, Clone)]
+pub enum Source<'a> {
+    File(PathBuf),
+    Stdin,
+    Text(&'a str),
+}
+
+impl<'a> Source<'a> {
+    /// A command-line input argument: a path, or `-` for stdin.
+    pub fn from_arg(arg: &str) -> Source<'static> {
+        match arg {
+            "-" => Source::Stdin,
+            path => Source::File(PathBuf::from(path)),
+        }
+    }
+
+    /// Reads the whole input; stdin can only be read once.
+    pub fn read(&self) -> io::Result<Cow<'a, str>> {
+        match self {
+            Source::File(path) => std::fs::read_to_string(path).map(Cow::Owned),
+            Source::Stdin => {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents)?;
+                Ok(Cow::Owned(contents))
+            }
+            Source::Text(text) => Ok(Cow::Borrowed(text)),
+        }
+    }
+
+    /// Attaches the file name, if there is one, to an error in this input.
+    pub fn locate(&self, error: ParseError) -> ParseError {
+        match self {
+            Source::File(path) => error.in_file(path),
+            _ => error,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Source<'a> {
+    fn from(text: &'a str) -> Source<'a> {
+        Source::Text(text)
+    }
+}
+
+impl fmt::Display for Source<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "-"),
+            Source::Text(_) => write!(f, "<string>"),
+        }
+    }
+}
+
+/// Reads the input from `source` and runs the requested parts of `day`.
+///
+/// Unreadable and malformed input are both reported as a message naming
+/// the source.
+pub fn run(day: usize, source: &Source, parts: &[usize]) -> Result<DayReport, String> {
+    let runner = day_runner(day).ok_or_else(|| format!("no solver for day {}", day))?;
+    let contents = source
+        .read()
+        .map_err(|error| format!("{}: {}", source, error))?;
+    runner(&contents, parts).map_err(|error| source.locate(error).to_string())
+}
+
+/// How a day ended when run by [`run_parallel`].
+#[derive(Debug, Clone)]
+pub enum Status {
+    Solved(DayReport),
+    /// The input could not be read or parsed.
+    Failed(String),
+    /// A solver panicked, with the panic message.
+    Panicked(String),
+    /// The day was still running when its time was up.
+    TimedOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub day: usize,
+    pub status: Status,
+    /// Wall time from starting the day to its result, or to the timeout.
+    pub elapsed: Duration,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        matches!(self.status, Status::Solved(_))
+    }
+
+    /// Why the day failed, or `None` if it was solved.
+    pub fn error(&self) -> Option<String> {
+        match &self.status {
+            Status::Solved(_) => None,
+            Status::Failed(message) => Some(message.clone()),
+            Status::Panicked(message) => Some(format!("panicked: {}", message)),
+            Status::TimedOut => Some(format!("timed out after {:.2?}", self.elapsed)),
+        }
+    }
+}
+
+/// Runs `days` on up to `threads` threads at a time and hands each outcome
+/// to `report` in the order the days were given, as soon as it and every
+/// day before it have finished.
+///
+/// A panic is caught and reported as that day's outcome. A day that runs
+/// past `timeout` is reported as timed out and its thread is abandoned,
+/// since threads can't be stopped; it keeps running until it finishes or
+/// the process exits, and a replacement thread takes its place.
+pub fn run_parallel(
+    days: Vec<(usize, Source<'static>)>,
+    parts: &[usize],
+    threads: usize,
+    timeout: Option<Duration>,
+    mut report: impl FnMut(Outcome),
+) {
+    let total = days.len();
+    let mut pending = days.into_iter().enumerate();
+    let (sender, receiver) = mpsc::channel();
+    let mut running = HashMap::<usize, (usize, Instant)>::new();
+    let mut finished = BTreeMap::<usize, Outcome>::new();
+    let mut next = 0;
+
+    while next < total {
+        while running.len() < threads.max(1) {
+            let (index, (day, source)) = match pending.next() {
+                Some(job) => job,
+                None => break,
+            };
+            let sender = sender.clone();
+            let parts = parts.to_vec();
+            running.insert(index, (day, Instant::now()));
+            thread::Builder::new()
+                .name(format!("day{:02}", day))
+                .spawn(move || {
+                    let status =
+                        match panic::catch_unwind(AssertUnwindSafe(|| run(day, &source, &parts))) {
+                            Ok(Ok(report)) => Status::Solved(report),
+                            Ok(Err(message)) => Status::Failed(message),
+                            Err(payload) => Status::Panicked(panic_message(payload.as_ref())),
+                        };
+                    // Nobody is listening any more if the day timed out
+                    let _ = sender.send((index, status));
+                })
+                .expect("failed to spawn a thread");
+        }
+
+        let deadline = timeout.and_then(|timeout| {
+            running
+                .values()
+                .map(|&(_, started)| started + timeout)
+                .min()
+        });
+        let received = match deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok(),
+            None => receiver.recv().ok(),
+        };
+
+        match received {
+            Some((index, status)) => {
+                // Results of days that already timed out are dropped
+                if let Some((day, started)) = running.remove(&index) {
+                    let elapsed = started.elapsed();
+                    finished.insert(
+                        index,
+                        Outcome {
+                            day,
+                            status,
+                            elapsed,
+                        },
+                    );
+                }
+            }
+            None => {
+                let now = Instant::now();
+                let timeout = timeout.unwrap_or_default();
+                let expired = running
+                    .iter()
+                    .filter(|(_, &(_, started))| now >= started + timeout)
+                    .map(|(&index, _)| index)
+                    .collect::<Vec<_>>();
+                for index in expired {
+                    let (day, started) = running.remove(&index).unwrap();
+                    let outcome = Outcome {
+                        day,
+                        status: Status::TimedOut,
+                        elapsed: now - started,
+                    };
+                    finished.insert(index, outcome);
+                }
+            }
+        }
+
+        while let Some(outcome) = finished.remove(&next) {
+            report(outcome);
+            next += 1;
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".to_string(),
+        },
+    }
+}
+
+impl DayReport {
+    /// One JSON object per part, each on its own line, for `--format json`.
+    ///
+    /// Times are whole nanoseconds; the parse time is repeated on every
+    /// part so that each record stands alone. When allocations were
+    /// counted, the peak heap usage in bytes and the number of allocations
+    /// of parsing and solving follow the times.
+    pub fn json_records<'a>(&'a self, input: &Source) -> impl Iterator<Item = String> + 'a {
+        let input = json_string(&input.to_string());
+        self.parts.iter().map(move |part| {
+            let mut memory = String::new();
+            if let (Some(parse), Some(solve)) = (self.parse_memory, part.memory) {
+                memory = format!(
+                    ",\"parse_peak_bytes\":{},\"parse_allocations\":{},\"solve_peak_bytes\":{},\"solve_allocations\":{}",
+                    parse.peak, parse.allocations, solve.peak, solve.allocations
+                );
+            }
+            format!(
+                "{{\"day\":{},\"part\":{},\"answer\":{},\"parse_ns\":{},\"solve_ns\":{}{},\"input\":{}}}",
+                self.day,
+                part.part,
+                json_string(&part.answer),
+                self.parse_time.as_nanos(),
+                part.elapsed.as_nanos(),
+                memory,
+                input
+            )
+        })
+    }
+}
+
+/// The JSON record for a day whose input could not be read or parsed.
+pub fn json_error(day: usize, input: &Source, message: &str) -> String {
+    format!(
+        "{{\"day\":{},\"error\":{},\"input\":{}}}",
+        day,
+        json_string(message),
+        json_string(&input.to_string())
+    )
+}
+
+/// `text` as a quoted JSON string.
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+type DayRunner = fn(&str, &[usize]) -> Result<DayReport, ParseError>;
+
+/// Parses the input once and runs the requested parts, timing each step
+/// and, if [`memory`] counting is on, measuring its heap usage.
+///
+/// Malformed input fails before any part runs.
+pub fn run_day<S: Solution>(contents: &str, parts: &[usize]) -> Result<DayReport, ParseError> {
+    let start = Instant::now();
+    let (input, parse_memory) = memory::measure(|| S::parse(contents));
+    let input = input?;
+    let parse_time = start.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let (answer, memory) = memory::measure(|| match part {
+                1 => S::part1(&input).to_string(),
+                _ => S::part2(&input).to_string(),
+            });
+            PartResult {
+                part,
+                answer,
+                elapsed: start.elapsed(),
+                memory,
+            }
+        })
+        .collect();
+
+    Ok(DayReport {
+        day: S::DAY,
+        parse_time,
+        parse_memory,
+        parts,
+    })
+}
+
+pub fn day_runner(day: usize) -> Option<DayRunner> {
+    use crate::*;
+
+    let runner: DayRunner = match day {
+        1 => run_day::<day01::Day01>,
+        2 => run_day::<day02::Day02>,
+        3 => run_day::<day03::Day03>,
+        4 => run_day::<day04::Day04>,
+        5 => run_day::<day05::Day05>,
+        6 => run_day::<day06::Day06>,
+        7 => run_day::<day07::Day07>,
+        8 => run_day::<day08::Day08>,
+        9 => run_day::<day09::Day09>,
+        10 => run_day::<day10::Day10>,
+        11 => run_day::<day11::Day11>,
+        12 => run_day::<day12::Day12>,
+        13 => run_day::<day13::Day13>,
+        14 => run_day::<day14::Day14>,
+        15 => run_day::<day15::Day15>,
+        16 => run_day::<day16::Day16>,
+        17 => run_day::<day17::Day17>,
+        18 => run_day::<day18::Day18>,
+        19 => run_day::<day19::Day19>,
+        20 => run_day::<day20::Day20>,
+        21 => run_day::<day21::Day21>,
+        22 => run_day::<day22::Day22>,
+        23 => run_day::<day23::Day23>,
+        24 => run_day::<day24::Day24>,
+        25 => run_day::<day25::Day25>,
+        _ => return None,
+    };
+    Some(runner)
+}
+
+/// The checked-in puzzle input for a day, `dayNN/input.txt`.
+pub fn default_input(day: usize) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("day{:02}/input.txt", day))
+}
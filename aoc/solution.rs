@@ -0,0 +1,30 @@
+use crate::ParseError;
+use std::fmt;
+use std::fmt::Display;
+
+/// A puzzle solver for one day of the calendar.
+///
+/// The input is parsed once and shared by both parts, so callers can time
+/// or reuse the parsed representation independently of the solvers.
+pub trait Solution {
+    /// Day of the calendar, 1 to 25.
+    const DAY: usize;
+
+    type Input;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(contents: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Part1;
+    fn part2(input: &Self::Input) -> Self::Part2;
+}
+
+/// Answer for puzzles that have no second part, such as day 25.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoAnswer;
+
+impl Display for NoAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "-")
+    }
+}
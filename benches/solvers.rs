@@ -0,0 +1,64 @@
+use advent_of_code_2020::runner::default_input;
+use advent_of_code_2020::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Benchmarks parsing and both parts of a day against its `input.txt`.
+///
+/// Parsing is measured on its own, and the parts run on a single parsed
+/// input so that their numbers only cover the solver.
+fn bench_day<S: Solution>(c: &mut Criterion, sample_size: usize, parts: &[usize]) {
+    let contents = std::fs::read_to_string(default_input(S::DAY))
+        .expect("Something went wrong reading the file");
+
+    let mut group = c.benchmark_group(format!("day{:02}", S::DAY));
+    group.sample_size(sample_size);
+
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&contents))));
+
+    let input = S::parse(&contents).expect("Something went wrong parsing the input");
+    if parts.contains(&1) {
+        group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&input))));
+    }
+    if parts.contains(&2) {
+        group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&input))));
+    }
+    group.finish();
+}
+
+fn days_01_to_10(c: &mut Criterion) {
+    bench_day::<day01::Day01>(c, 100, &[1, 2]);
+    bench_day::<day02::Day02>(c, 100, &[1, 2]);
+    bench_day::<day03::Day03>(c, 100, &[1, 2]);
+    bench_day::<day04::Day04>(c, 100, &[1, 2]);
+    bench_day::<day05::Day05>(c, 100, &[1, 2]);
+    bench_day::<day06::Day06>(c, 100, &[1, 2]);
+    bench_day::<day07::Day07>(c, 100, &[1, 2]);
+    bench_day::<day08::Day08>(c, 100, &[1, 2]);
+    bench_day::<day09::Day09>(c, 100, &[1, 2]);
+    bench_day::<day10::Day10>(c, 100, &[1, 2]);
+}
+
+fn days_11_to_19(c: &mut Criterion) {
+    bench_day::<day11::Day11>(c, 20, &[1, 2]);
+    bench_day::<day12::Day12>(c, 100, &[1, 2]);
+    bench_day::<day13::Day13>(c, 100, &[1, 2]);
+    bench_day::<day14::Day14>(c, 100, &[1, 2]);
+    bench_day::<day15::Day15>(c, 10, &[1, 2]);
+    bench_day::<day16::Day16>(c, 100, &[1, 2]);
+    bench_day::<day17::Day17>(c, 10, &[1, 2]);
+    bench_day::<day18::Day18>(c, 100, &[1, 2]);
+    bench_day::<day19::Day19>(c, 50, &[1, 2]);
+}
+
+fn days_20_to_25(c: &mut Criterion) {
+    // Part 2 of day 20 doesn't terminate, so only part 1 is benchmarked.
+    bench_day::<day20::Day20>(c, 10, &[1]);
+    bench_day::<day21::Day21>(c, 100, &[1, 2]);
+    bench_day::<day22::Day22>(c, 20, &[1, 2]);
+    bench_day::<day23::Day23>(c, 10, &[1, 2]);
+    bench_day::<day24::Day24>(c, 100, &[1, 2]);
+    bench_day::<day25::Day25>(c, 10, &[1, 2]);
+}
+
+criterion_group!(benches, days_01_to_10, days_11_to_19, days_20_to_25);
+criterion_main!(benches);
@@ -1,40 +1,254 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
 use itertools::Itertools;
-use std::env;
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    contents
-        .lines()
-        .map(|x| x.parse::<usize>().unwrap())
-        .combinations(2)
-        .filter_map(|combination| match combination.iter().sum() {
-            2020usize => Some(combination.iter().product()),
-            _ => None,
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: usize = 1;
+
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        contents.lines().map(|x| parse_at(contents, x)).collect()
+    }
+
+    fn part1(expenses: &Vec<usize>) -> usize {
+        product(&k_sum(expenses, 2, 2020).expect("no two entries sum to 2020"))
+    }
+
+    fn part2(expenses: &Vec<usize>) -> usize {
+        product(&k_sum(expenses, 3, 2020).expect("no three entries sum to 2020"))
+    }
+}
+
+fn product(entries: &[Entry]) -> usize {
+    entries.iter().map(|entry| entry.value).product()
+}
+
+/// An entry in the expense report and the line it's on, counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub line: usize,
+    pub value: usize,
+}
+
+/// Finds `k` entries summing to `target`, in the order of their lines.
+///
+/// The entries are sorted once, then all but the last two are chosen in
+/// turn and the last two found walking in from both ends, so this takes
+/// time `O(n^(k-1))` for `k` of at least 2 rather than `O(n^k)`.
+pub fn k_sum(expenses: &[usize], k: usize, target: usize) -> Option<Vec<Entry>> {
+    let mut sorted = expenses
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| Entry {
+            line: index + 1,
+            value,
         })
-        .next()
-        .unwrap()
+        .collect::<Vec<_>>();
+    sorted.sort_unstable_by_key(|entry| entry.value);
+
+    let mut entries = find(&sorted, k, target)?;
+    entries.sort_unstable_by_key(|entry| entry.line);
+    Some(entries)
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+/// Finds `k` of the `sorted` entries summing to `target`.
+fn find(sorted: &[Entry], k: usize, target: usize) -> Option<Vec<Entry>> {
+    match k {
+        0 if target == 0 => Some(Vec::new()),
+        0 => None,
+        1 => {
+            let index = sorted
+                .binary_search_by_key(&target, |entry| entry.value)
+                .ok()?;
+            Some(vec![sorted[index]])
+        }
+        2 if sorted.len() >= 2 => {
+            let (mut low, mut high) = (0, sorted.len() - 1);
+            while low < high {
+                let sum = sorted[low].value + sorted[high].value;
+                if sum == target {
+                    return Some(vec![sorted[low], sorted[high]]);
+                } else if sum < target {
+                    low += 1;
+                } else {
+                    high -= 1;
+                }
+            }
+            None
+        }
+        _ => {
+            for (index, &first) in sorted.iter().enumerate() {
+                // Every entry after this one is at least as large
+                if first.value.saturating_mul(k) > target {
+                    break;
+                }
+                if let Some(mut rest) = find(&sorted[index + 1..], k - 1, target - first.value) {
+                    rest.push(first);
+                    return Some(rest);
+                }
+            }
+            None
+        }
+    }
+}
 
-    contents
-        .lines()
-        .map(|x| x.parse::<usize>().unwrap())
-        .combinations(3)
-        .filter_map(|combination| match combination.iter().sum() {
-            2020usize => Some(combination.iter().product()),
-            _ => None,
+/// Finds every combination of `k` entries on different lines summing to
+/// `target`, each in the order of its lines and all in the order of their
+/// first lines, then second and so on.
+///
+/// A value can only be used as often as it appears, so `1010` only pairs
+/// up to 2020 if it's on two lines, and then the pair is found once.
+pub fn all_k_sums(expenses: &[usize], k: usize, target: usize) -> Vec<Vec<Entry>> {
+    let entries = expenses
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| Entry {
+            line: index + 1,
+            value,
         })
-        .next()
-        .unwrap()
+        .collect::<Vec<_>>();
+    let mut solutions = find_all(&entries, k, target);
+    solutions.sort_unstable_by(|a, b| {
+        let lines = |entries: &[Entry]| entries.iter().map(|entry| entry.line).collect::<Vec<_>>();
+        lines(a).cmp(&lines(b))
+    });
+    solutions
+}
+
+/// Finds every `k` of the `entries`, which are in line order, summing to
+/// `target`, in line order.
+fn find_all(entries: &[Entry], k: usize, target: usize) -> Vec<Vec<Entry>> {
+    match k {
+        0 if target == 0 => vec![Vec::new()],
+        0 => Vec::new(),
+        1 => entries
+            .iter()
+            .filter(|entry| entry.value == target)
+            .map(|&entry| vec![entry])
+            .collect(),
+        2 => {
+            // Each entry pairs with those before it that make up the rest
+            let mut seen = HashMap::<usize, Vec<Entry>>::new();
+            let mut pairs = Vec::new();
+            for &entry in entries.iter().filter(|entry| entry.value <= target) {
+                if let Some(earlier) = seen.get(&(target - entry.value)) {
+                    pairs.extend(earlier.iter().map(|&first| vec![first, entry]));
+                }
+                seen.entry(entry.value).or_default().push(entry);
+            }
+            pairs
+        }
+        _ => entries
+            .iter()
+            .enumerate()
+            .filter(|(_, first)| first.value <= target)
+            .flat_map(|(index, &first)| {
+                find_all(&entries[index + 1..], k - 1, target - first.value)
+                    .into_iter()
+                    .map(move |mut rest| {
+                        rest.insert(0, first);
+                        rest
+                    })
+            })
+            .collect(),
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+/// Lists every combination of `k` entries summing to `target` for each `k`
+/// in `ks`, and whether there is exactly one, as the puzzle assumes.
+pub fn audit(expenses: &[usize], ks: &[usize], target: usize) -> (String, bool) {
+    let mut report = String::new();
+    let mut unique = true;
+    for &k in ks {
+        let solutions = all_k_sums(expenses, k, target);
+        let verdict = match solutions.len() {
+            0 => "none, expected exactly one".to_string(),
+            1 => "1 combination".to_string(),
+            count => {
+                let values = solutions
+                    .iter()
+                    .map(|entries| {
+                        entries
+                            .iter()
+                            .map(|entry| entry.value)
+                            .sorted()
+                            .collect::<Vec<_>>()
+                    })
+                    .unique()
+                    .count();
+                format!(
+                    "{} combinations ({} distinct by value), expected exactly one",
+                    count, values
+                )
+            }
+        };
+        unique &= solutions.len() == 1;
+
+        report.push_str(&format!(
+            "{} entries summing to {}: {}\n",
+            k, target, verdict
+        ));
+        for entries in &solutions {
+            report.push_str(&format!(
+                "  lines {}: {} (product {})\n",
+                entries.iter().map(|entry| entry.line).join(", "),
+                entries.iter().map(|entry| entry.value).join(" + "),
+                product(entries)
+            ));
+        }
+    }
+    (report, unique)
+}
+
+impl Generator for Day01 {
+    /// `size` expenses with exactly one pair and one triple summing to 2020.
+    ///
+    /// The filler entries are all above 1010, so no two or three of them add
+    /// up to 2020, and are checked against the planted entries.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let planted = loop {
+            let a = rng.gen_range(1011, 1900);
+            let x = rng.gen_range(1, 600);
+            let y = rng.gen_range(1, 600);
+            let planted = vec![a, 2020 - a, x, y, 2020 - x - y];
+            let solutions = |k| {
+                planted
+                    .iter()
+                    .combinations(k)
+                    .filter(|entries| entries.iter().copied().sum::<usize>() == 2020)
+                    .count()
+            };
+            if solutions(2) == 1 && solutions(3) == 1 {
+                break planted;
+            }
+        };
+
+        let mut expenses = planted.clone();
+        while expenses.len() < size.max(planted.len()) {
+            let filler = rng.gen_range(1011, 2020);
+            let completes_pair = planted.iter().any(|&entry| entry + filler == 2020);
+            let completes_triple = planted
+                .iter()
+                .tuple_combinations()
+                .any(|(a, b)| a + b + filler == 2020);
+            if !completes_pair && !completes_triple {
+                expenses.push(filler);
+            }
+        }
+        expenses.shuffle(rng);
+        expenses
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
 }
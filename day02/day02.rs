@@ -1,55 +1,90 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use regex::Regex;
-use std::env;
-
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let re = Regex::new(r"(?P<min>\d+)-(?P<max>\d+)\s(?P<letter>\w):\s(?P<password>\w+)").unwrap();
-
-    contents.lines().fold(0, |valid_passwords, line| {
-        let parsed = re.captures(line).unwrap();
-        let min = parsed["min"].parse::<i32>().unwrap();
-        let max = parsed["max"].parse::<i32>().unwrap();
-        let letter = parsed["letter"].chars().next().unwrap();
-        let filtered_password = parsed["password"]
-            .chars()
-            .fold(0, |sum, c| sum + if letter == c { 1 } else { 0 });
-
-        valid_passwords
-            + if filtered_password >= min && filtered_password <= max {
-                1
-            } else {
-                0
-            }
-    })
+
+pub mod policy;
+
+use policy::{count_valid, OccurrenceCount, Positions};
+
+pub struct Day02;
+
+#[derive(Debug, Clone)]
+pub struct PasswordEntry {
+    pub min: usize,
+    pub max: usize,
+    pub letter: char,
+    pub password: String,
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let re = Regex::new(r"(?P<min>\d+)-(?P<max>\d+)\s(?P<letter>\w):\s(?P<password>\w+)").unwrap();
-
-    contents.lines().fold(0, |valid_passwords, line| {
-        let parsed = re.captures(line).unwrap();
-        let min = parsed["min"].parse::<usize>().unwrap();
-        let max = parsed["max"].parse::<usize>().unwrap();
-        let letter = parsed["letter"].chars().next().unwrap();
-        let contains_first = parsed["password"].chars().nth(min - 1usize).unwrap() == letter;
-        let contains_second = parsed["password"].chars().nth(max - 1usize).unwrap() == letter;
-
-        valid_passwords
-            + if (contains_first || contains_second) && (contains_first != contains_second) {
-                1
-            } else {
-                0
-            }
-    })
+impl Solution for Day02 {
+    const DAY: usize = 2;
+
+    type Input = Vec<PasswordEntry>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<PasswordEntry>, ParseError> {
+        let re = Regex::new(
+            r"^(?P<min>\d+)-(?P<max>\d+)\s(?P<letter>\w):\s(?P<password>\S+)(?P<rest>.*)$",
+        )
+        .unwrap();
+
+        contents
+            .lines()
+            .map(|line| {
+                let parsed = re.captures(line).ok_or_else(|| {
+                    ParseError::at(contents, line, "expected `min-max letter: password`")
+                })?;
+                let rest = parsed.name("rest").unwrap().as_str();
+                if !rest.is_empty() {
+                    return Err(ParseError::at(
+                        contents,
+                        rest,
+                        "expected the password to end the line",
+                    ));
+                }
+                Ok(PasswordEntry {
+                    min: parse_at(contents, &parsed["min"])?,
+                    max: parse_at(contents, &parsed["max"])?,
+                    letter: parsed["letter"].chars().next().unwrap(),
+                    password: parsed["password"].to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn part1(entries: &Vec<PasswordEntry>) -> usize {
+        count_valid(&OccurrenceCount, entries)
+    }
+
+    fn part2(entries: &Vec<PasswordEntry>) -> usize {
+        count_valid(&Positions, entries)
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day02 {
+    /// `size` policies and passwords; both positions of a policy always fall
+    /// within its password.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        (0..size)
+            .map(|_| {
+                let letter = rng.gen_range(b'a', b'z' + 1) as char;
+                let min = rng.gen_range(1, 10);
+                let max = rng.gen_range(min + 1, 20);
+                let password = (0..rng.gen_range(max, 21))
+                    .map(|_| {
+                        if rng.gen_bool(0.4) {
+                            letter
+                        } else {
+                            rng.gen_range(b'a', b'z' + 1) as char
+                        }
+                    })
+                    .collect::<String>();
+                format!("{}-{} {}: {}\n", min, max, letter, password)
+            })
+            .collect()
+    }
 }
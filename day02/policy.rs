@@ -0,0 +1,310 @@
+//! The rules a password in the database can be checked against.
+//!
+//! Each line of the database gives two numbers and a letter before its
+//! password; the puzzle's two policies read those differently, and the
+//! others here use them where they can or take their own settings, given
+//! after an `=` in their name.
+
+use super::PasswordEntry;
+use crate::runner::json_string;
+use std::fmt;
+
+pub trait PasswordPolicy: Send + Sync {
+    /// The name the policy is selected by, with any settings.
+    fn name(&self) -> String;
+
+    /// Why the policy rejects `entry`, or `None` if it accepts it.
+    fn violation(&self, entry: &PasswordEntry) -> Option<String>;
+
+    fn is_valid(&self, entry: &PasswordEntry) -> bool {
+        self.violation(entry).is_none()
+    }
+}
+
+fn times(count: usize) -> String {
+    match count {
+        1 => "once".to_string(),
+        count => format!("{} times", count),
+    }
+}
+
+/// Part 1: the letter occurs between `min` and `max` times.
+pub struct OccurrenceCount;
+
+impl PasswordPolicy for OccurrenceCount {
+    fn name(&self) -> String {
+        "count".to_string()
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let occurrences = entry
+            .password
+            .chars()
+            .filter(|&c| c == entry.letter)
+            .count();
+        if (entry.min..=entry.max).contains(&occurrences) {
+            None
+        } else {
+            Some(format!(
+                "'{}' occurs {}, allowed {}-{}",
+                entry.letter,
+                times(occurrences),
+                entry.min,
+                entry.max
+            ))
+        }
+    }
+}
+
+/// Part 2: the letter is at exactly one of positions `min` and `max`,
+/// counting from 1. Position 0, or a position past the end of the password,
+/// is a mistake in the database rather than a letter that doesn't match, and
+/// is rejected as such.
+pub struct Positions;
+
+impl PasswordPolicy for Positions {
+    fn name(&self) -> String {
+        "positions".to_string()
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let length = entry.password.chars().count();
+        for &position in &[entry.min, entry.max] {
+            if position == 0 {
+                return Some("positions start at 1".to_string());
+            }
+            if position > length {
+                return Some(format!(
+                    "position {} is past the end of a {}-character password",
+                    position, length
+                ));
+            }
+        }
+        let matches =
+            |position: usize| entry.password.chars().nth(position - 1) == Some(entry.letter);
+        match (matches(entry.min), matches(entry.max)) {
+            (true, true) => Some(format!(
+                "positions {} and {} both match",
+                entry.min, entry.max
+            )),
+            (false, false) => Some(format!(
+                "neither position {} nor {} matches",
+                entry.min, entry.max
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// The password is between `min` and `max` characters long.
+pub struct Length;
+
+impl PasswordPolicy for Length {
+    fn name(&self) -> String {
+        "length".to_string()
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let length = entry.password.chars().count();
+        if (entry.min..=entry.max).contains(&length) {
+            None
+        } else {
+            Some(format!(
+                "{} characters long, allowed {}-{}",
+                length, entry.min, entry.max
+            ))
+        }
+    }
+}
+
+/// The password mixes at least `required` of lowercase and uppercase
+/// letters, digits and anything else.
+pub struct CharacterClasses {
+    pub required: usize,
+}
+
+impl CharacterClasses {
+    fn classes(password: &str) -> usize {
+        let tests: [fn(&char) -> bool; 4] = [
+            char::is_ascii_lowercase,
+            char::is_ascii_uppercase,
+            char::is_ascii_digit,
+            |c| !c.is_ascii_alphanumeric(),
+        ];
+        tests
+            .iter()
+            .filter(|test| password.chars().any(|c| test(&c)))
+            .count()
+    }
+}
+
+impl PasswordPolicy for CharacterClasses {
+    fn name(&self) -> String {
+        format!("classes={}", self.required)
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        let classes = Self::classes(&entry.password);
+        if classes >= self.required {
+            None
+        } else {
+            Some(format!(
+                "uses {} of the character classes, needs {}",
+                classes, self.required
+            ))
+        }
+    }
+}
+
+/// The password contains none of `substrings`.
+pub struct Forbidden {
+    pub substrings: Vec<String>,
+}
+
+impl PasswordPolicy for Forbidden {
+    fn name(&self) -> String {
+        format!("forbid={}", self.substrings.join(","))
+    }
+
+    fn violation(&self, entry: &PasswordEntry) -> Option<String> {
+        self.substrings
+            .iter()
+            .find(|substring| entry.password.contains(substring.as_str()))
+            .map(|substring| format!("contains '{}'", substring))
+    }
+}
+
+/// The policies [`policy`] knows, as they are written.
+pub const POLICIES: &[&str] = &[
+    "count",
+    "positions",
+    "length",
+    "classes=N",
+    "forbid=TEXT,...",
+];
+
+/// Selects a policy by its name, followed by `=` and its settings for
+/// `classes` and `forbid`.
+pub fn policy(spec: &str) -> Result<Box<dyn PasswordPolicy>, String> {
+    let (name, settings) = match spec.split_once('=') {
+        Some((name, settings)) => (name, Some(settings)),
+        None => (spec, None),
+    };
+    match (name, settings) {
+        ("count", None) => Ok(Box::new(OccurrenceCount)),
+        ("positions", None) => Ok(Box::new(Positions)),
+        ("length", None) => Ok(Box::new(Length)),
+        ("classes", Some(required)) => match required.parse() {
+            Ok(required) if (1..=4).contains(&required) => {
+                Ok(Box::new(CharacterClasses { required }))
+            }
+            _ => Err(format!(
+                "`{}` expects a number of classes from 1 to 4",
+                spec
+            )),
+        },
+        ("forbid", Some(substrings)) if !substrings.is_empty() => Ok(Box::new(Forbidden {
+            substrings: substrings.split(',').map(str::to_string).collect(),
+        })),
+        _ => Err(format!(
+            "unknown policy `{}`, expected one of {}",
+            spec,
+            POLICIES.join(", ")
+        )),
+    }
+}
+
+/// How many of `entries` `policy` accepts.
+pub fn count_valid(policy: &dyn PasswordPolicy, entries: &[PasswordEntry]) -> usize {
+    entries
+        .iter()
+        .filter(|entry| policy.is_valid(entry))
+        .count()
+}
+
+/// A line for each of `policies` saying how many of `entries` it accepts,
+/// for `--policy`.
+pub fn report(policies: &[Box<dyn PasswordPolicy>], entries: &[PasswordEntry]) -> String {
+    policies
+        .iter()
+        .map(|policy| {
+            format!(
+                "{}: {} of {} passwords valid\n",
+                policy.name(),
+                count_valid(policy.as_ref(), entries),
+                entries.len()
+            )
+        })
+        .collect()
+}
+
+/// A password in the database that a policy rejects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The line of the database, counting from 1.
+    pub line: usize,
+    pub password: String,
+    pub policy: String,
+    pub reason: String,
+}
+
+impl Violation {
+    /// One JSON object, for `--format json`.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"line\":{},\"password\":{},\"policy\":{},\"reason\":{}}}",
+            self.line,
+            json_string(&self.password),
+            json_string(&self.policy),
+            json_string(&self.reason)
+        )
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: `{}` breaks {}: {}",
+            self.line, self.password, self.policy, self.reason
+        )
+    }
+}
+
+/// Every way `entries` break `policies`, by line and then in the order of
+/// the policies.
+pub fn violations(
+    policies: &[Box<dyn PasswordPolicy>],
+    entries: &[PasswordEntry],
+) -> Vec<Violation> {
+    entries
+        .iter()
+        .enumerate()
+        .flat_map(|(index, entry)| {
+            policies.iter().filter_map(move |policy| {
+                policy.violation(entry).map(|reason| Violation {
+                    line: index + 1,
+                    password: entry.password.clone(),
+                    policy: policy.name(),
+                    reason,
+                })
+            })
+        })
+        .collect()
+}
+
+/// How many `violations` there are and in how many of `passwords`, which
+/// ends the `--violations` report.
+pub fn summary(violations: &[Violation], passwords: usize) -> String {
+    let mut rejected = violations
+        .iter()
+        .map(|violation| violation.line)
+        .collect::<Vec<_>>();
+    rejected.dedup();
+    format!(
+        "{} violations in {} of {} passwords",
+        violations.len(),
+        rejected.len(),
+        passwords
+    )
+}
@@ -1,9 +1,13 @@
-use image::ImageBuffer;
-use std::collections::HashMap;
-use std::env;
-
-type Coordinate = (i32, i32);
-struct HillPath {
+use crate::generate::Generator;
+use crate::grid::{Coordinate, Grid};
+use crate::render::{Animation, Canvas, Color, WHITE};
+use crate::{info, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+pub struct HillPath {
     current: Coordinate,
     next: Coordinate,
     dimensions: Coordinate,
@@ -11,7 +15,7 @@ struct HillPath {
 }
 
 impl HillPath {
-    fn new(start_pos: Coordinate, dimensions: Coordinate, step: Coordinate) -> HillPath {
+    pub fn new(start_pos: Coordinate, dimensions: Coordinate, step: Coordinate) -> HillPath {
         HillPath {
             current: start_pos,
             next: start_pos,
@@ -42,95 +46,278 @@ impl Iterator for HillPath {
     }
 }
 
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let mut tree_map = HashMap::<Coordinate, u32>::new();
-
-    let mut dimensions: Coordinate = (0, 0);
-    contents.lines().enumerate().for_each(|(y, row)| {
-        dimensions.1 = y as i32 + 1;
-        row.chars().enumerate().for_each(|(x, value)| {
-            if (x as i32 + 1) > dimensions.0 {
-                dimensions.0 = x as i32 + 1;
-            }
-            if value != '.' {
-                let coord = (x as i32, y as i32);
-                tree_map.entry(coord).or_insert(1);
-            }
-        });
-    });
+pub struct Day03;
 
-    let start_coord = (0 as i32, 0 as i32);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Square {
+    Open,
+    Tree,
+}
 
-    HillPath::new(start_coord, dimensions, (3, 1)).fold(0, |sum, (pos, _)| {
-        if tree_map.contains_key(&pos) {
-            sum + 1
-        } else {
-            sum
+pub type Forest = Grid<Square>;
+
+/// How the toboggan goes down the forest from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slope {
+    /// Jumps right and down by a whole number of squares at a time, as in
+    /// the puzzle, only touching the squares it lands on. Written `3,1`.
+    Step(Coordinate),
+    /// Slides along the line going right by the first number for every
+    /// second down, touching every square the line passes through, so it
+    /// can go a fraction of a square right per row. Written `1/3`.
+    Line(Coordinate),
+}
+
+/// The slopes of part 2.
+pub const PART2_SLOPES: [Slope; 5] = [
+    Slope::Step((1, 1)),
+    Slope::Step((3, 1)),
+    Slope::Step((5, 1)),
+    Slope::Step((7, 1)),
+    Slope::Step((1, 2)),
+];
+
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(slope: &str) -> Result<Slope, String> {
+        let parse = |separator, make: fn(Coordinate) -> Slope| {
+            let (right, down) = slope.split_once(separator)?;
+            match (right.parse(), down.parse()) {
+                (Ok(right), Ok(down)) if down > 0 => Some(make((right, down))),
+                _ => None,
+            }
+        };
+        parse(',', Slope::Step)
+            .or_else(|| parse('/', Slope::Line))
+            .ok_or_else(|| {
+                format!(
+                    "invalid slope `{}`, expected RIGHT,DOWN or RIGHT/DOWN with DOWN above 0",
+                    slope
+                )
+            })
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Slope::Step((right, down)) => write!(f, "{},{}", right, down),
+            Slope::Line((right, down)) => write!(f, "{}/{}", right, down),
         }
+    }
+}
+
+/// A way down the forest and the trees hit on it, in the order they're hit.
+/// The forest repeats to the right and left, so the trees' coordinates are
+/// on the repeated map rather than wrapped into the forest itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trajectory {
+    pub slope: Slope,
+    pub trees: Vec<Coordinate>,
+}
+
+/// The trees hit going down `forest` at `slope`, or an error if the slope
+/// doesn't go down, as it would never leave the forest, or goes so far that
+/// its squares don't fit in a [`Coordinate`].
+pub fn trajectory(forest: &Forest, slope: Slope) -> Result<Trajectory, String> {
+    let dimensions = (forest.width(), forest.height());
+    let (Slope::Step((right, down)) | Slope::Line((right, down))) = slope;
+    if down <= 0 {
+        return Err(format!("slope `{}` doesn't go down", slope));
+    }
+    // Steps go at most one past the bottom row, and lines keep an error
+    // term of up to three times as far as they go
+    let fits = |distance: i32| {
+        distance
+            .checked_mul(dimensions.1 + 1)
+            .and_then(|distance| distance.checked_mul(4))
+            .is_some()
+    };
+    if !fits(right) || !fits(down) {
+        return Err(format!(
+            "slope `{}` goes too far for a forest {} squares high",
+            slope, dimensions.1
+        ));
+    }
+
+    let squares: Box<dyn Iterator<Item = Coordinate>> = match slope {
+        Slope::Step(step) => Box::new(
+            HillPath::new((0, 0), dimensions, step)
+                .map(move |(pos, repeats)| (pos.0 + dimensions.0 * repeats as i32, pos.1)),
+        ),
+        // Aiming as far down as the line goes right keeps it on the slope
+        // exactly, wherever it leaves the forest
+        Slope::Line((right, down)) => Box::new(
+            LineOfSight::new((0, 0), (right * dimensions.1, down * dimensions.1))
+                .take_while(move |pos| pos.1 < dimensions.1),
+        ),
+    };
+    Ok(Trajectory {
+        slope,
+        trees: squares
+            .filter(|&pos| forest.get_wrapped(pos) == Some(&Square::Tree))
+            .collect(),
     })
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let mut tree_map = HashMap::<Coordinate, u32>::new();
-
-    let mut dimensions: Coordinate = (0, 0);
-    contents.lines().enumerate().for_each(|(y, row)| {
-        dimensions.1 = y as i32 + 1;
-        row.chars().enumerate().for_each(|(x, value)| {
-            if (x as i32 + 1) > dimensions.0 {
-                dimensions.0 = x as i32 + 1;
-            }
-            if value != '.' {
-                let coord = (x as i32, y as i32);
-                tree_map.entry(coord).or_insert(1);
-            }
-        });
-    });
+fn count_trees(forest: &Forest, slope: Coordinate) -> usize {
+    trajectory(forest, Slope::Step(slope)).unwrap().trees.len()
+}
 
-    let start_coord = (0 as i32, 0 as i32);
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
 
-    let slopes = vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+/// Every slope going right by at most `max_right` squares and down by at
+/// most `max_down`: each step, and each line that isn't one of the steps
+/// or another line. They're made as they're needed, as there can be far too
+/// many to keep.
+pub fn slopes_within(max_right: i32, max_down: i32) -> impl Iterator<Item = Slope> + Clone {
+    let pairs =
+        (1..=max_down).flat_map(move |down| (0..=max_right).map(move |right| (right, down)));
+    let steps = pairs.clone().map(Slope::Step);
+    let lines = pairs
+        .filter(|&(right, down)| right > 0 && gcd(right, down) == 1 && (right, down) != (1, 1))
+        .map(Slope::Line);
+    steps.chain(lines)
+}
 
-    slopes.iter().fold(1, |product, slope| {
-        product
-            * HillPath::new(start_coord, dimensions, *slope).fold(0, |sum, (pos, _)| {
-                if tree_map.contains_key(&pos) {
-                    sum + 1
+/// The trajectories hitting the fewest and the most trees of those down
+/// `slopes`, the earliest of any that tie, or `None` if there are no
+/// slopes. Fails on the first slope [`trajectory`] can't follow.
+pub fn search(
+    forest: &Forest,
+    slopes: impl IntoIterator<Item = Slope>,
+) -> Result<Option<(Trajectory, Trajectory)>, String> {
+    let mut extremes = None;
+    for slope in slopes {
+        let trajectory = trajectory(forest, slope)?;
+        extremes = match extremes {
+            None => Some((trajectory.clone(), trajectory)),
+            Some((fewest, most)) => {
+                let count = trajectory.trees.len();
+                if count < fewest.trees.len() {
+                    Some((trajectory, most))
+                } else if count > most.trees.len() {
+                    Some((fewest, trajectory))
                 } else {
-                    sum
+                    Some((fewest, most))
                 }
-            })
-    })
+            }
+        };
+    }
+    Ok(extremes)
 }
 
-type Color = (u8, u8, u8);
-
-fn draw_symbol(pixels: &mut Vec<(i32, i32, Color)>, top_left: (i32, i32), pattern: &str) {
-    pattern.split('\n').enumerate().for_each(|(y, row)| {
-        row.chars()
-            .map(|value| value.to_digit(10).unwrap() as i32)
-            .enumerate()
-            .for_each(|(x, value)| {
-                let color = match value {
-                    1 => (0x33, 0xCC, 0x44),
-                    2 => (0x8A, 0x26, 0x3C),
-                    3 => (0xAA, 0x36, 0x3C),
-                    _ => (0xCC, 0x66, 0x44),
-                };
-
-                if value != 0 {
-                    pixels.push(((top_left.0 + x as i32), (top_left.1 + y as i32), color));
-                }
-            });
-    });
+fn list_trees(trajectory: &Trajectory) -> String {
+    trajectory
+        .trees
+        .iter()
+        .map(|(x, y)| format!("({},{})", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-fn draw_scaled_sled(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32)) {
-    let symbol = "0000000000000000
+/// The trees hit down each of `slopes`, and their product as in part 2 if
+/// there are several, for `--slope`.
+pub fn slopes_report(forest: &Forest, slopes: &[Slope]) -> Result<String, String> {
+    let mut report = String::new();
+    let mut product = 1;
+    for &slope in slopes {
+        let trajectory = trajectory(forest, slope)?;
+        product *= trajectory.trees.len();
+        report.push_str(&format!("{}: {} trees\n", slope, trajectory.trees.len()));
+    }
+    if slopes.len() > 1 {
+        report.push_str(&format!("product: {}\n", product));
+    }
+    Ok(report)
+}
+
+/// The ways down hitting the fewest and the most trees of the slopes up to
+/// `max_right` squares right and `max_down` down, and the trees they hit,
+/// for `--search`.
+///
+/// Going down past the bottom row, or right by more than the forest's width
+/// for every row down, only wraps around to squares closer slopes already
+/// touch, so bounds beyond those are rejected.
+pub fn search_report(forest: &Forest, max_right: i32, max_down: i32) -> Result<String, String> {
+    let (width, height) = (forest.width(), forest.height());
+    if max_down > height {
+        return Err(format!(
+            "can't search more than {} squares down a forest {} high",
+            height, height
+        ));
+    }
+    let widest = i64::from(width) * i64::from(max_down);
+    if i64::from(max_right) > widest {
+        return Err(format!(
+            "can't search more than {} squares right going {} down a forest {} wide",
+            widest, max_down, width
+        ));
+    }
+    let (fewest, most) = search(forest, slopes_within(max_right, max_down))?
+        .ok_or_else(|| "no slopes to search".to_string())?;
+    Ok([("fewest", fewest), ("most", most)]
+        .iter()
+        .map(|(label, trajectory)| {
+            format!(
+                "{}: {} hits {} trees: {}\n",
+                label,
+                trajectory.slope,
+                trajectory.trees.len(),
+                list_trees(trajectory)
+            )
+        })
+        .collect())
+}
+
+impl Solution for Day03 {
+    const DAY: usize = 3;
+
+    type Input = Forest;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Forest, ParseError> {
+        Grid::parse(
+            contents,
+            contents,
+            "expected `.` or `#`",
+            |value| match value {
+                '.' => Some(Square::Open),
+                '#' => Some(Square::Tree),
+                _ => None,
+            },
+        )
+    }
+
+    fn part1(forest: &Forest) -> usize {
+        draw_forest(forest);
+        count_trees(forest, (3, 1))
+    }
+
+    fn part2(forest: &Forest) -> usize {
+        PART2_SLOPES
+            .iter()
+            .map(|&slope| trajectory(forest, slope).unwrap().trees.len())
+            .product()
+    }
+}
+
+const SPRITE_PALETTE: [(char, Color); 3] = [
+    ('1', (0x33, 0xCC, 0x44)),
+    ('2', (0x8A, 0x26, 0x3C)),
+    ('3', (0xAA, 0x36, 0x3C)),
+];
+
+const SLED: &str = "\
+0000000000000000
 0000000000033000
 3300000000003330
 2300000000000033
@@ -147,12 +334,8 @@ fn draw_scaled_sled(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32)) {
 0000000000000000
 0000000000000000";
 
-    let top_left = (center.0 - 8, center.1 - 8);
-    draw_symbol(pixels, top_left, symbol);
-}
-
-fn draw_tree(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32), scale: i32) {
-    let symbol = "0000000110000000
+const TREE: &str = "\
+0000000110000000
 0000001110000000
 0000001111000000
 0000011111100000
@@ -168,13 +351,9 @@ fn draw_tree(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32), scale: i32
 0110001221000110
 1000002222000001
 0000002222000000";
-    let top_left = (center.0 * scale - scale / 2, center.1 * scale - scale / 2);
-
-    draw_symbol(pixels, top_left, symbol);
-}
 
-fn draw_fallen_tree(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32), scale: i32) {
-    let symbol = "0000000000000000
+const FALLEN_TREE: &str = "\
+0000000000000000
 0000000000000000
 0000000000000000
 0000000000000000
@@ -190,12 +369,8 @@ fn draw_fallen_tree(pixels: &mut Vec<(i32, i32, Color)>, center: (i32, i32), sca
 0111111111122222
 1111111111112200
 0111111111111000";
-    let top_left = (center.0 * scale - scale / 2, center.1 * scale - scale / 2);
-
-    draw_symbol(pixels, top_left, symbol);
-}
 
-struct LineOfSight {
+pub struct LineOfSight {
     next: Coordinate,
     end: Coordinate,
     x_diff: i32,
@@ -206,7 +381,7 @@ struct LineOfSight {
 }
 
 impl LineOfSight {
-    fn new(start_pos: Coordinate, end_pos: Coordinate) -> LineOfSight {
+    pub fn new(start_pos: Coordinate, end_pos: Coordinate) -> LineOfSight {
         let x_diff = (end_pos.0 - start_pos.0).abs();
         let y_diff = -(end_pos.1 - start_pos.1).abs();
 
@@ -253,27 +428,15 @@ impl Iterator for LineOfSight {
     }
 }
 
-fn draw_forest(inputfile: String) {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let mut tree_map = HashMap::<Coordinate, u32>::new();
-
-    contents.lines().enumerate().for_each(|(y, row)| {
-        row.chars().enumerate().for_each(|(x, value)| {
-            if value != '.' {
-                let coord = (x as i32, y as i32);
-                tree_map.entry(coord).or_insert(1);
-            }
-        });
-    });
+/// Animates the sled going down the slope of part 1, knocking over the
+/// trees it hits.
+pub fn draw_forest(forest: &Forest) {
+    let mut animation = Animation::new("day03");
+    if !animation.is_enabled() {
+        return;
+    }
 
-    let x_min = tree_map.iter().map(|(pos, _)| pos.0).min().unwrap();
-    let x_max = tree_map.iter().map(|(pos, _)| pos.0).max().unwrap();
-    let y_min = tree_map.iter().map(|(pos, _)| pos.1).min().unwrap();
-    let y_max = tree_map.iter().map(|(pos, _)| pos.1).max().unwrap();
-    let x_range = (x_max - x_min) as u32;
-    let y_range = (y_max - y_min) as u32;
-    let dimensions: Coordinate = (1 + x_range as i32, 1 + y_range as i32);
+    let dimensions: Coordinate = (forest.width(), forest.height());
 
     let slope = (3, 1);
     let scale: i32 = 16;
@@ -298,76 +461,74 @@ fn draw_forest(inputfile: String) {
 
     let mut previous_trees = Vec::<(i32, i32)>::new();
 
-    HillPath::new(start_pos, dimensions, slope)
-        .enumerate()
-        .for_each(|(pframe, (block_offset, repeat_index))| {
-            for iframe in 0..iframes {
-                let frame = pframe as i32 * iframes + iframe as i32;
-                println!("pframe {}, iframe {}, frame {}", pframe, iframe, frame);
+    for (block_offset, repeat_index) in HillPath::new(start_pos, dimensions, slope) {
+        let path_block_pos = (
+            block_offset.0 + (dimensions.0 * repeat_index as i32),
+            block_offset.1,
+        );
+        if forest.get_wrapped(path_block_pos) == Some(&Square::Tree) {
+            previous_trees.push(path_block_pos);
+        }
 
-                let mut pixels = Vec::<(i32, i32, Color)>::new();
+        for iframe in 0..iframes {
+            animation.frame(|| {
+                let mut canvas = Canvas::new(real_size.0, real_size.1, 1, WHITE);
 
                 let interpolated_offset = (
                     scale * (block_offset.0 + dimensions.0 * repeat_index as i32)
                         + (iframe * scale * slope.0) / iframes,
                     scale * block_offset.1 + (iframe * scale * slope.1) / iframes,
                 );
-                let path_block_pos = (
-                    block_offset.0 + (dimensions.0 * repeat_index as i32),
-                    block_offset.1,
+                let view = (
+                    (viewport.0 as i32 / 2 + border as i32) * scale - interpolated_offset.0,
+                    (viewport.1 as i32 / 2 + border as i32) * scale - interpolated_offset.1,
                 );
 
                 for y in viewport_range_y.0..viewport_range_y.1 {
                     for x in viewport_range_x.0..viewport_range_x.1 {
                         let block_pos = (x + path_block_pos.0, y + path_block_pos.1);
-                        let tree_pos = (
-                            block_pos.0.abs() % (dimensions.0) as i32,
-                            block_pos.1.abs() % (dimensions.1) as i32,
-                        );
-
-                        if tree_map.contains_key(&tree_pos) {
-                            if block_pos == path_block_pos {
-                                previous_trees.push(block_pos);
-                            }
-
-                            if previous_trees.contains(&block_pos) {
-                                draw_fallen_tree(&mut pixels, block_pos, scale);
+
+                        if forest.get_wrapped(block_pos) == Some(&Square::Tree) {
+                            let sprite = if previous_trees.contains(&block_pos) {
+                                FALLEN_TREE
                             } else {
-                                draw_tree(&mut pixels, block_pos, scale);
-                            }
+                                TREE
+                            };
+                            let top_left = (
+                                view.0 + block_pos.0 * scale - scale / 2,
+                                view.1 + block_pos.1 * scale - scale / 2,
+                            );
+                            canvas.stamp(top_left, sprite, &SPRITE_PALETTE);
                         }
                     }
                 }
 
-                draw_scaled_sled(&mut pixels, interpolated_offset);
-
-                let mut img = ImageBuffer::from_fn(real_size.0, real_size.1, |_x, _y| {
-                    image::Rgb([255, 255, 255])
-                });
-
-                for pos in pixels {
-                    let x = (pos.0 - interpolated_offset.0)
-                        + ((viewport.0 as i32 / 2 + border as i32) * scale);
-                    let y = (pos.1 - interpolated_offset.1)
-                        + ((viewport.1 as i32 / 2 + border as i32) * scale);
-                    let color = pos.2;
-
-                    let pixel = image::Rgb([color.0, color.1, color.2]);
-                    if x >= 0 && y >= 0 && x < real_size.0 as i32 && y < real_size.1 as i32 {
-                        img.put_pixel(x as u32, y as u32, pixel);
-                    }
-                }
+                let sled_top_left = (
+                    view.0 + interpolated_offset.0 - 8,
+                    view.1 + interpolated_offset.1 - 8,
+                );
+                canvas.stamp(sled_top_left, SLED, &SPRITE_PALETTE);
+                canvas
+            });
+        }
+    }
 
-                img.save(format!("frames/day03.frame{:05}.png", frame))
-                    .unwrap();
-            }
-        });
+    if let Err(error) = animation.finish() {
+        info!("day03: could not write the animation: {}", error);
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
-
-    draw_forest(args[1].to_string());
+impl Generator for Day03 {
+    /// A forest `size` rows deep and 31 columns wide, like the puzzle's.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        (0..size)
+            .map(|_| {
+                let mut row = (0..31)
+                    .map(|_| if rng.gen_bool(0.25) { '#' } else { '.' })
+                    .collect::<String>();
+                row.push('\n');
+                row
+            })
+            .collect()
+    }
 }
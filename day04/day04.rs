@@ -1,154 +1,193 @@
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    fn is_valid(passport: &HashMap<String, String>) -> bool {
-        let required_fields = vec![
-            "byr", // (Birth Year)
-            "iyr", // (Issue Year)
-            "eyr", // (Expiration Year)
-            "hgt", // (Height)
-            "hcl", // (Hair Color)
-            "ecl", // (Eye Color)
-            "pid", // (Passport ID)
-        ];
-        let optional_fields = vec![
-            "cid", // (Country ID)
-        ];
-
-        required_fields
-            .iter()
-            .all(|&field| passport.contains_key(field))
-    }
-    contents.split("\n\n").fold(0, |sum, passport_info| {
-        let mut passport = HashMap::<String, String>::new();
-        passport_info
-            .split(|c| c == '\n' || c == ' ')
-            .for_each(|keyvalue| match keyvalue.split(':').tuples().next() {
-                Some((key, value)) => {
-                    passport.entry(key.to_string()).or_insert(value.to_string());
+use std::ops::RangeInclusive;
+
+pub struct Day04;
+
+pub type Passport = HashMap<String, String>;
+
+fn has_required_fields(passport: &Passport) -> bool {
+    let required_fields = [
+        "byr", // (Birth Year)
+        "iyr", // (Issue Year)
+        "eyr", // (Expiration Year)
+        "hgt", // (Height)
+        "hcl", // (Hair Color)
+        "ecl", // (Eye Color)
+        "pid", // (Passport ID)
+    ];
+    // "cid" (Country ID) is optional
+
+    required_fields
+        .iter()
+        .all(|&field| passport.contains_key(field))
+}
+
+fn is_valid(passport: &Passport) -> bool {
+    if has_required_fields(passport) {
+        let valid_birth_year = match passport.get("byr").unwrap().parse::<i32>() {
+            Ok(year) => (1920..=2002).contains(&year),
+            Err(_) => false,
+        };
+
+        let valid_issue_year = match passport.get("iyr").unwrap().parse::<i32>() {
+            Ok(year) => (2010..=2020).contains(&year),
+            Err(_) => false,
+        };
+
+        let valid_expiration_year = match passport.get("eyr").unwrap().parse::<i32>() {
+            Ok(year) => (2020..=2030).contains(&year),
+            Err(_) => false,
+        };
+
+        let valid_height = match passport.get("hgt") {
+            Some(value) => {
+                let in_range = |height: &str, range: RangeInclusive<i32>| {
+                    height
+                        .parse()
+                        .map_or(false, |height| range.contains(&height))
+                };
+                match (value.strip_suffix("cm"), value.strip_suffix("in")) {
+                    (Some(height), _) => in_range(height, 150..=193),
+                    (_, Some(height)) => in_range(height, 59..=76),
+                    _ => false,
                 }
-                None => (),
-            });
-
-        if is_valid(&passport) {
-            sum + 1
-        } else {
-            sum
-        }
-    })
+            }
+            None => false,
+        };
+        let valid_hair_color = match passport.get("hcl") {
+            Some(value) => value.strip_prefix('#').map_or(false, |color| {
+                color.len() == 6 && color.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+            }),
+            None => false,
+        };
+        let valid_eye_color = match passport.get("ecl") {
+            Some(value) => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+                .iter()
+                .any(|color| color == value),
+            None => false,
+        };
+        let valid_passport_id = match passport.get("pid") {
+            Some(value) => value.len() == 9 && value.chars().all(char::is_numeric),
+            None => false,
+        };
+
+        [
+            valid_birth_year,
+            valid_issue_year,
+            valid_expiration_year,
+            valid_height,
+            valid_hair_color,
+            valid_eye_color,
+            valid_passport_id,
+        ]
+        .iter()
+        .all(|&valid| valid)
+    } else {
+        false
+    }
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    fn is_valid(passport: &HashMap<String, String>) -> bool {
-        let required_fields = vec![
-            "byr", // (Birth Year)
-            "iyr", // (Issue Year)
-            "eyr", // (Expiration Year)
-            "hgt", // (Height)
-            "hcl", // (Hair Color)
-            "ecl", // (Eye Color)
-            "pid", // (Passport ID)
-        ];
-        let optional_fields = vec![
-            "cid", // (Country ID)
-        ];
-
-        if required_fields
-            .iter()
-            .all(|&field| passport.contains_key(field))
-        {
-            let valid_birth_year = match passport.get("byr").unwrap().parse::<i32>() {
-                Ok(year) => year >= 1920 && year <= 2002,
-                Err(_) => false,
-            };
-
-            let valid_issue_year = match passport.get("iyr").unwrap().parse::<i32>() {
-                Ok(year) => year >= 2010 && year <= 2020,
-                Err(_) => false,
-            };
-
-            let valid_expiration_year = match passport.get("eyr").unwrap().parse::<i32>() {
-                Ok(year) => year >= 2020 && year <= 2030,
-                Err(_) => false,
-            };
-
-            let valid_height = match passport.get("hgt") {
-                Some(value) => match value.get(..value.len() - 2).unwrap().parse::<i32>() {
-                    Ok(height) => {
-                        if value.ends_with("cm") {
-                            height >= 150 && height <= 193
-                        } else if value.ends_with("in") {
-                            height >= 59 && height <= 76
-                        } else {
-                            false
-                        }
-                    }
-                    Err(_) => false,
-                },
-                None => false,
-            };
-            let valid_hair_color = match passport.get("hcl") {
-                Some(value) => {
-                    value.len() == 7 && value.get(1..).unwrap().chars().all(char::is_alphanumeric)
+impl Solution for Day04 {
+    const DAY: usize = 4;
+
+    type Input = Vec<Passport>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<Passport>, ParseError> {
+        contents
+            .split("\n\n")
+            .map(|passport_info| {
+                let mut passport = Passport::new();
+                for keyvalue in passport_info.split_whitespace() {
+                    let (key, value) = keyvalue.split_once(':').ok_or_else(|| {
+                        ParseError::at(contents, keyvalue, "expected `key:value`")
+                    })?;
+                    passport
+                        .entry(key.to_string())
+                        .or_insert_with(|| value.to_string());
                 }
-                None => false,
-            };
-            let valid_eye_color = match passport.get("ecl") {
-                Some(value) => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
-                    .iter()
-                    .any(|color| color == value),
-                None => false,
-            };
-            let valid_passport_id = match passport.get("pid") {
-                Some(value) => value.len() == 9 && value.chars().all(char::is_numeric),
-                None => false,
-            };
-
-            return [
-                valid_birth_year,
-                valid_issue_year,
-                valid_expiration_year,
-                valid_height,
-                valid_hair_color,
-                valid_eye_color,
-                valid_passport_id,
-            ]
+                Ok(passport)
+            })
+            .collect()
+    }
+
+    fn part1(passports: &Vec<Passport>) -> usize {
+        passports
             .iter()
-            .all(|&valid| valid);
-        } else {
-            false
-        }
+            .filter(|passport| has_required_fields(passport))
+            .count()
     }
 
-    contents.split("\n\n").fold(0, |sum, passport_info| {
-        let mut passport = HashMap::<String, String>::new();
-        passport_info
-            .split(|c| c == '\n' || c == ' ')
-            .for_each(|keyvalue| match keyvalue.split(':').tuples().next() {
-                Some((key, value)) => {
-                    passport.entry(key.to_string()).or_insert(value.to_string());
-                }
-                None => (),
-            });
-
-        if is_valid(&passport) {
-            sum + 1
-        } else {
-            sum
-        }
-    })
+    fn part2(passports: &Vec<Passport>) -> usize {
+        passports
+            .iter()
+            .filter(|passport| is_valid(passport))
+            .count()
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day04 {
+    /// `size` passports: some valid, some with a field out of range and some
+    /// missing a required field.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const EYE_COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+        (0..size)
+            .map(|_| {
+                let height = if rng.gen() {
+                    format!("{}cm", rng.gen_range(150, 194))
+                } else {
+                    format!("{}in", rng.gen_range(59, 77))
+                };
+                let mut fields = vec![
+                    ("byr", rng.gen_range(1920, 2003).to_string()),
+                    ("iyr", rng.gen_range(2010, 2021).to_string()),
+                    ("eyr", rng.gen_range(2020, 2031).to_string()),
+                    ("hgt", height),
+                    ("hcl", format!("#{:06x}", rng.gen_range(0, 0x100_0000))),
+                    ("ecl", EYE_COLORS.choose(rng).unwrap().to_string()),
+                    ("pid", format!("{:09}", rng.gen_range(0, 1_000_000_000))),
+                ];
+                if rng.gen() {
+                    fields.push(("cid", rng.gen_range(100, 350).to_string()));
+                }
+
+                match rng.gen_range(0, 4) {
+                    0 => {
+                        let missing = rng.gen_range(0, 7);
+                        fields.remove(missing);
+                    }
+                    1 => {
+                        let (_, value) = fields.choose_mut(rng).unwrap();
+                        *value = match rng.gen_range(0, 3) {
+                            0 => rng.gen_range(1900, 2050).to_string(),
+                            1 => format!("{}cm", rng.gen_range(100, 250)),
+                            _ => "zzz".to_string(),
+                        };
+                    }
+                    _ => (),
+                }
+
+                fields.shuffle(rng);
+                let mut passport = String::new();
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    let separator = match index {
+                        0 => "",
+                        _ if rng.gen_bool(0.3) => "\n",
+                        _ => " ",
+                    };
+                    passport.push_str(&format!("{}{}:{}", separator, key, value));
+                }
+                passport
+            })
+            .join("\n\n")
+            + "\n"
+    }
 }
@@ -1,6 +1,10 @@
-use std::env;
+use crate::generate::Generator;
+use crate::{debug, info, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
-fn get_seat_id(boarding_pass: &str) -> i32 {
+pub fn get_seat_id(boarding_pass: &str) -> i32 {
     boarding_pass
         .chars()
         .take(7)
@@ -10,7 +14,7 @@ fn get_seat_id(boarding_pass: &str) -> i32 {
                 'B' => (middlepoint + 1, range.1),
                 'F' => (range.0, middlepoint),
                 _ => {
-                    println!("mismatch");
+                    debug!("mismatch");
                     (0, 0)
                 }
             }
@@ -26,7 +30,7 @@ fn get_seat_id(boarding_pass: &str) -> i32 {
                     'R' => (middlepoint + 1, range.1),
                     'L' => (range.0, middlepoint),
                     _ => {
-                        println!("mismatch");
+                        debug!("mismatch");
                         (0, 0)
                     }
                 }
@@ -34,51 +38,90 @@ fn get_seat_id(boarding_pass: &str) -> i32 {
             .0
 }
 
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    contents
-        .split('\n')
-        .fold(0, |highest_seat_id, boarding_pass| {
-            let seat_id = get_seat_id(boarding_pass);
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: usize = 5;
+
+    type Input = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(contents: &str) -> Result<Vec<i32>, ParseError> {
+        contents
+            .lines()
+            .map(|boarding_pass| {
+                for (position, (index, c)) in boarding_pass.char_indices().enumerate() {
+                    let (expected, message) = match position {
+                        0..=6 => ("FB", "expected `F` or `B`"),
+                        _ => ("LR", "expected `L` or `R`"),
+                    };
+                    if !expected.contains(c) {
+                        let text = &boarding_pass[index..index + c.len_utf8()];
+                        return Err(ParseError::at(contents, text, message));
+                    }
+                }
+                if boarding_pass.len() != 10 {
+                    return Err(ParseError::at(
+                        contents,
+                        boarding_pass,
+                        "expected 10 characters",
+                    ));
+                }
+                Ok(get_seat_id(boarding_pass))
+            })
+            .collect()
+    }
+
+    fn part1(seats: &Vec<i32>) -> i32 {
+        seats.iter().fold(0, |highest_seat_id, &seat_id| {
             if seat_id > highest_seat_id {
                 seat_id
             } else {
                 highest_seat_id
             }
         })
-}
-
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let seats: Vec<i32> = contents
-        .split('\n')
-        .map(|boarding_pass| get_seat_id(boarding_pass))
-        .collect();
+    }
 
-    match (0..seats.len() as i32).find(|&seat| {
-        !seats.contains(&seat) && seats.contains(&(seat - 1)) && seats.contains(&(seat + 1))
-    }) {
-        Some(empty_seat) => empty_seat,
-        None => {
-            println!("Unable to find seat");
-            0
+    fn part2(seats: &Vec<i32>) -> i32 {
+        match (0..seats.len() as i32).find(|&seat| {
+            !seats.contains(&seat) && seats.contains(&(seat - 1)) && seats.contains(&(seat + 1))
+        }) {
+            Some(empty_seat) => empty_seat,
+            None => {
+                info!("Unable to find seat");
+                0
+            }
         }
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!(
-        "{:#?}",
-        (
-            get_seat_id("FBFBBFFRLR"),
-            get_seat_id("BFFFBBFRRR"),
-            get_seat_id("FFFBBBFRRR"),
-            get_seat_id("BBFFBBFRLL")
-        )
-    );
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day05 {
+    /// `size` boarding passes, 3 to 680, for a contiguous block of seats
+    /// with one missing.
+    ///
+    /// Part 2 only looks for the gap among seat IDs below the number of
+    /// passes, so the block starts low enough for the gap to be found.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.clamp(3, 680);
+        let first = rng.gen_range(0, size / 2 + 1);
+        let missing = rng.gen_range(first + 1, size);
+
+        let mut seats = (first..=first + size)
+            .filter(|&seat| seat != missing)
+            .collect::<Vec<usize>>();
+        seats.shuffle(rng);
+        seats
+            .iter()
+            .map(|seat| {
+                let row = (0..7)
+                    .rev()
+                    .map(|bit| if seat >> (3 + bit) & 1 == 1 { 'B' } else { 'F' });
+                let column = (0..3)
+                    .rev()
+                    .map(|bit| if seat >> bit & 1 == 1 { 'R' } else { 'L' });
+                row.chain(column).chain(Some('\n')).collect::<String>()
+            })
+            .collect()
+    }
 }
@@ -1,51 +1,101 @@
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    contents.split("\n\n").fold(0, |sum, questions_group| {
-        let mut questions = Vec::<char>::new();
-        sum + questions_group.split_whitespace().fold(0, |sum, keyvalue| {
-            sum + keyvalue.chars().fold(0, |sum, c| {
-                sum + if !questions.contains(&c) {
-                    questions.push(c);
-                    1
-                } else {
-                    0
-                }
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: usize = 6;
+
+    type Input = Vec<Vec<String>>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(contents: &str) -> Result<Vec<Vec<String>>, ParseError> {
+        contents
+            .split("\n\n")
+            .map(|questions_group| {
+                questions_group
+                    .split_whitespace()
+                    .map(
+                        |answers| match answers.find(|c: char| !c.is_ascii_lowercase()) {
+                            Some(index) => Err(ParseError::at(
+                                contents,
+                                &answers[index..],
+                                "expected questions `a` to `z`",
+                            )),
+                            None => Ok(answers.to_string()),
+                        },
+                    )
+                    .collect()
             })
-        })
-    })
-}
+            .collect()
+    }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+    fn part1(groups: &Vec<Vec<String>>) -> i32 {
+        groups.iter().fold(0, |sum, questions_group| {
+            let mut questions = Vec::<char>::new();
+            sum + questions_group.iter().fold(0, |sum, keyvalue| {
+                sum + keyvalue.chars().fold(0, |sum, c| {
+                    sum + if !questions.contains(&c) {
+                        questions.push(c);
+                        1
+                    } else {
+                        0
+                    }
+                })
+            })
+        })
+    }
 
-    contents.split("\n\n").fold(0, |sum, questions_group| {
-        let mut questions = HashMap::<char, i32>::new();
-        let num_people = questions_group.split_whitespace().fold(0, |sum, answers| {
-            answers.chars().for_each(|c| {
-                questions.entry(c).and_modify(|e| *e += 1).or_insert(1);
+    fn part2(groups: &Vec<Vec<String>>) -> i32 {
+        groups.iter().fold(0, |sum, questions_group| {
+            let mut questions = HashMap::<char, i32>::new();
+            let num_people = questions_group.iter().fold(0, |sum, answers| {
+                answers.chars().for_each(|c| {
+                    questions.entry(c).and_modify(|e| *e += 1).or_insert(1);
+                });
+                sum + 1
             });
-            sum + 1
-        });
 
-        let valid_questions = questions.values().fold(0, |sum, &num_answers| {
-            if num_answers == num_people {
-                sum + 1
-            } else {
-                sum
-            }
-        });
-        sum + valid_questions
-    })
+            let valid_questions = questions.values().fold(0, |sum, &num_answers| {
+                if num_answers == num_people {
+                    sum + 1
+                } else {
+                    sum
+                }
+            });
+            sum + valid_questions
+        })
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day06 {
+    /// `size` groups of one to five people answering from `a` to `z`.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let questions = (b'a'..=b'z').map(char::from).collect::<Vec<char>>();
+
+        (0..size)
+            .map(|_| {
+                // Everyone in a group answers from a shared pool, so some
+                // questions tend to be answered by all of them
+                let pool_size = rng.gen_range(3, 27);
+                let pool = questions
+                    .choose_multiple(rng, pool_size)
+                    .copied()
+                    .collect::<Vec<char>>();
+                (0..rng.gen_range(1, 6))
+                    .map(|_| {
+                        let answers = rng.gen_range(1, pool.len() + 1);
+                        pool.choose_multiple(rng, answers).collect::<String>()
+                    })
+                    .join("\n")
+            })
+            .join("\n\n")
+            + "\n"
+    }
 }
@@ -1,100 +1,192 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use regex::Regex;
 use std::collections::HashMap;
-use std::env;
 
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+pub struct Day07;
 
-    let main_rule = Regex::new(r"(?P<bag_rule>.+)\sbag(s?)\scontain\s(?P<contains>.+).$").unwrap();
-    let content_rule =
-        Regex::new(r"(?P<number_of_bags>(\d|no)+)\s(?P<bag_rule>.+)\sbag(s?)(\.?)$").unwrap();
+pub type BagRules = HashMap<String, Vec<(i32, String)>>;
 
-    let mut bag_rules = HashMap::<String, Vec<String>>::new();
+impl Solution for Day07 {
+    const DAY: usize = 7;
 
-    contents.lines().for_each(|line| {
-        let parsed = main_rule.captures(line).unwrap();
+    type Input = BagRules;
+    type Part1 = i32;
+    type Part2 = i32;
 
-        parsed["contains"].split(", ").for_each(|rule| {
-            let bag_rule = parsed["bag_rule"].to_string();
-            let parsed_content = content_rule.captures(rule).unwrap();
-            let number_of_bags = match parsed_content["number_of_bags"].parse::<i32>() {
-                Ok(value) => value,
-                Err(_) => 0,
-            };
+    fn parse(contents: &str) -> Result<BagRules, ParseError> {
+        let main_rule =
+            Regex::new(r"(?P<bag_rule>.+)\sbag(s?)\scontain\s(?P<contains>.+).$").unwrap();
+        let content_rule =
+            Regex::new(r"(?P<number_of_bags>(\d|no)+)\s(?P<bag_rule>.+)\sbag(s?)(\.?)$").unwrap();
+
+        let mut bag_rules = BagRules::new();
+
+        for line in contents.lines() {
+            let parsed = main_rule.captures(line).ok_or_else(|| {
+                ParseError::at(
+                    contents,
+                    line,
+                    "expected `<color> bags contain <contents>.`",
+                )
+            })?;
+
+            for rule in parsed["contains"].split(", ") {
+                let bag_rule = parsed["bag_rule"].to_string();
+                let parsed_content = content_rule.captures(rule).ok_or_else(|| {
+                    ParseError::at(contents, rule, "expected `<count> <color> bag(s)`")
+                })?;
+                let number_of_bags = match &parsed_content["number_of_bags"] {
+                    "no" => 0,
+                    count => parse_at(contents, count)?,
+                };
 
-            if number_of_bags > 0 {
                 bag_rules
                     .entry(bag_rule)
-                    .and_modify(|entry| {
-                        entry.push(parsed_content["bag_rule"].to_string());
-                    })
-                    .or_insert([parsed_content["bag_rule"].to_string()].to_vec());
+                    .or_default()
+                    .push((number_of_bags, parsed_content["bag_rule"].to_string()));
             }
-        });
-    });
-    fn find_parents(
-        bag_rules: &HashMap<String, Vec<String>>,
-        child: &str,
-        parents: &mut Vec<String>,
-    ) {
-        bag_rules.iter().for_each(|(bag_id, child_bag_ids)| {
-            child_bag_ids.iter().for_each(|child_bag_id| {
-                if child_bag_id == child && !parents.contains(bag_id) {
-                    parents.push(bag_id.to_string());
-                    find_parents(bag_rules, bag_id, parents);
-                }
+        }
+
+        Ok(bag_rules)
+    }
+
+    fn part1(bag_rules: &BagRules) -> i32 {
+        fn find_parents(bag_rules: &BagRules, child: &str, parents: &mut Vec<String>) {
+            bag_rules.iter().for_each(|(bag_id, child_bag_ids)| {
+                child_bag_ids
+                    .iter()
+                    .for_each(|(number_of_bags, child_bag_id)| {
+                        if *number_of_bags > 0 && child_bag_id == child && !parents.contains(bag_id)
+                        {
+                            parents.push(bag_id.to_string());
+                            find_parents(bag_rules, bag_id, parents);
+                        }
+                    });
             });
-        });
+        }
+
+        let mut parents = Vec::<String>::new();
+        find_parents(bag_rules, "shiny gold", &mut parents);
+        parents.len() as i32
     }
 
-    let mut parents = Vec::<String>::new();
-    find_parents(&bag_rules, "shiny gold", &mut parents);
-    parents.len() as i32
+    fn part2(bag_rules: &BagRules) -> i32 {
+        fn find_children(bag_rules: &BagRules, child: &str) -> i32 {
+            match bag_rules.get(child) {
+                Some(entry) => entry.iter().fold(1, |sum, (number_of_bags, child_bag_id)| {
+                    sum + number_of_bags * find_children(bag_rules, child_bag_id)
+                }),
+                None => 0,
+            }
+        }
+
+        find_children(bag_rules, "shiny gold") - 1
+    }
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let main_rule = Regex::new(r"(?P<bag_rule>.+)\sbag(s?)\scontain\s(?P<contains>.+).$").unwrap();
-    let content_rule =
-        Regex::new(r"(?P<number_of_bags>(\d|no)+)\s(?P<bag_rule>.+)\sbag(s?)(\.?)$").unwrap();
-
-    let mut bag_rules = HashMap::<String, Vec<(i32, String)>>::new();
-
-    contents.lines().for_each(|line| {
-        let parsed = main_rule.captures(line).unwrap();
-
-        parsed["contains"].split(", ").for_each(|rule| {
-            let bag_rule = parsed["bag_rule"].to_string();
-            let parsed_content = content_rule.captures(rule).unwrap();
-            let number_of_bags = match parsed_content["number_of_bags"].parse::<i32>() {
-                Ok(value) => value,
-                Err(_) => 0,
-            };
-
-            bag_rules
-                .entry(bag_rule)
-                .and_modify(|entry| {
-                    entry.push((number_of_bags, parsed_content["bag_rule"].to_string()));
-                })
-                .or_insert([(number_of_bags, parsed_content["bag_rule"].to_string())].to_vec());
-        });
-    });
-    fn find_children(bag_rules: &HashMap<String, Vec<(i32, String)>>, child: &str) -> i32 {
-        match bag_rules.get(child) {
-            Some(entry) => entry.iter().fold(1, |sum, (number_of_bags, child_bag_id)| {
-                sum + number_of_bags * find_children(bag_rules, child_bag_id)
-            }),
-            None => 0,
+impl Generator for Day07 {
+    /// Rules for `size` bag colors, at least 2 and at most 1024.
+    ///
+    /// Bags are arranged in six layers and only contain bags from the layer
+    /// below, so the rules have no cycles and the bag counts stay small.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const ADJECTIVES: [&str; 32] = [
+            "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light",
+            "mirrored", "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+            "dusky", "bold", "glossy", "matte", "frosted", "rusty", "smoky", "speckled", "velvet",
+            "wild", "quiet", "sunny", "stormy", "misty",
+        ];
+        const COLORS: [&str; 32] = [
+            "aqua",
+            "beige",
+            "black",
+            "blue",
+            "bronze",
+            "brown",
+            "chartreuse",
+            "coral",
+            "crimson",
+            "cyan",
+            "fuchsia",
+            "gold",
+            "gray",
+            "green",
+            "indigo",
+            "lavender",
+            "lime",
+            "magenta",
+            "maroon",
+            "olive",
+            "orange",
+            "plum",
+            "purple",
+            "red",
+            "salmon",
+            "silver",
+            "tan",
+            "teal",
+            "tomato",
+            "turquoise",
+            "violet",
+            "white",
+        ];
+        const LAYERS: usize = 6;
+
+        let mut colors = ADJECTIVES
+            .iter()
+            .cartesian_product(COLORS.iter())
+            .map(|(adjective, color)| format!("{} {}", adjective, color))
+            .filter(|color| color != "shiny gold")
+            .collect::<Vec<String>>();
+        colors.shuffle(rng);
+        colors.truncate(size.clamp(2, 1024) - 1);
+        colors.push("shiny gold".to_string());
+
+        let mut layers = vec![Vec::<&str>::new(); LAYERS];
+        layers[1].push(&colors[0]);
+        layers[2].push("shiny gold");
+        for color in &colors[1..colors.len() - 1] {
+            layers[rng.gen_range(0, LAYERS)].push(color);
         }
-    }
 
-    find_children(&bag_rules, "shiny gold") - 1
-}
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+        let mut rules = layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer, bags)| bags.iter().map(move |bag| (layer, *bag)))
+            .map(|(layer, bag)| {
+                let count = rng.gen_range(0, 4);
+                let mut contents = match layers.get(layer + 1) {
+                    Some(below) => below
+                        .choose_multiple(rng, count)
+                        .copied()
+                        .collect::<Vec<&str>>(),
+                    None => Vec::new(),
+                };
+                // Something has to contain the shiny gold bag for part 1
+                if layer == 1 && bag == layers[1][0] && !contents.contains(&"shiny gold") {
+                    contents.push("shiny gold");
+                }
+
+                let contents = match contents.len() {
+                    0 => "no other bags".to_string(),
+                    _ => contents
+                        .iter()
+                        .map(|color| match rng.gen_range(1, 6) {
+                            1 => format!("1 {} bag", color),
+                            count => format!("{} {} bags", count, color),
+                        })
+                        .join(", "),
+                };
+                format!("{} bags contain {}.\n", bag, contents)
+            })
+            .collect::<Vec<String>>();
+        rules.shuffle(rng);
+        rules.concat()
+    }
 }
@@ -0,0 +1,132 @@
+//! Boot code written by hand, and read back.
+//!
+//! [`assemble`] reads programs in a friendlier form than the puzzle input:
+//!
+//! ```text
+//! # Count to three, then stop
+//!         acc +1
+//! loop:   acc +1
+//!         jmp done    # labels stand for the instruction after them
+//! done:
+//! ```
+//!
+//! Comments run from `#` to the end of the line and a label is a name
+//! followed by `:`, before an instruction or on its own line. An argument
+//! can be a label instead of a number, which stands for the offset from the
+//! instruction to the label, so `jmp loop` goes to the instruction after
+//! `loop:` and `nop done` would if it were swapped for a `jmp`.
+//!
+//! [`disassemble`] goes the other way, writing each instruction as it
+//! appears in the puzzle input with comments showing where each `jmp` goes
+//! and where it comes from; the result assembles to the same program.
+
+use super::vm::{Instruction, InstructionSet, JMP};
+use crate::error::parse_at;
+use crate::ParseError;
+use std::collections::HashMap;
+
+fn is_label(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn assemble(
+    source: &str,
+    instruction_set: &InstructionSet,
+) -> Result<Vec<Instruction>, ParseError> {
+    // The instructions' text, and where each label points
+    let mut statements = Vec::<&str>::new();
+    let mut labels = HashMap::<&str, usize>::new();
+    for line in source.lines() {
+        let mut statement = line.split('#').next().unwrap().trim();
+        while let Some((label, rest)) = statement.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(ParseError::at(source, label, "expected a label"));
+            }
+            if labels.insert(label, statements.len()).is_some() {
+                return Err(ParseError::at(source, label, "label defined twice"));
+            }
+            statement = rest.trim();
+        }
+        if !statement.is_empty() {
+            statements.push(statement);
+        }
+    }
+
+    statements
+        .iter()
+        .enumerate()
+        .map(|(index, statement)| {
+            let mut words = statement.split_whitespace();
+            let mnemonic = words.next().unwrap();
+            let opcode = instruction_set
+                .opcode(mnemonic)
+                .ok_or_else(|| ParseError::at(source, mnemonic, "unknown operation"))?;
+            let argument = match (words.next(), words.next()) {
+                (Some(argument), None) => argument,
+                (None, _) => return Err(ParseError::at(source, statement, "expected an argument")),
+                (Some(_), Some(extra)) => {
+                    return Err(ParseError::at(source, extra, "expected one argument"))
+                }
+            };
+
+            let argument = if is_label(argument) {
+                match labels.get(argument) {
+                    Some(&target) => target as i32 - index as i32,
+                    None => return Err(ParseError::at(source, argument, "unknown label")),
+                }
+            } else {
+                parse_at(source, argument)?
+            };
+            Ok(Instruction { opcode, argument })
+        })
+        .collect()
+}
+
+/// Writes a program as in the puzzle input, with a comment on each `jmp`
+/// saying which instruction it goes to, and on each instruction jumped to
+/// saying where from. Jumps past the end go to `end`, the instruction the
+/// program terminates on, or out of bounds.
+pub fn disassemble(program: &[Instruction], instruction_set: &InstructionSet) -> String {
+    let target = |index: usize, instruction: &Instruction| {
+        let target = index as i64 + i64::from(instruction.argument);
+        if target == program.len() as i64 {
+            "end".to_string()
+        } else if target < 0 || target > program.len() as i64 {
+            "out of bounds".to_string()
+        } else {
+            target.to_string()
+        }
+    };
+
+    let mut sources = vec![Vec::new(); program.len() + 1];
+    for (index, instruction) in program.iter().enumerate() {
+        let target = index as i64 + i64::from(instruction.argument);
+        if instruction.opcode == JMP && target >= 0 && target <= program.len() as i64 {
+            sources[target as usize].push(index.to_string());
+        }
+    }
+
+    let mut text = String::new();
+    for (index, instruction) in program.iter().enumerate() {
+        let mut comments = Vec::new();
+        if instruction.opcode == JMP {
+            comments.push(format!("-> {}", target(index, instruction)));
+        }
+        if !sources[index].is_empty() {
+            comments.push(format!("<- {}", sources[index].join(", ")));
+        }
+
+        let instruction = instruction_set.format(*instruction);
+        if comments.is_empty() {
+            text.push_str(&format!("{}\n", instruction));
+        } else {
+            text.push_str(&format!("{:<10}# {}\n", instruction, comments.join(", ")));
+        }
+    }
+    if !sources[program.len()].is_empty() {
+        text.push_str(&format!("# end <- {}\n", sources[program.len()].join(", ")));
+    }
+    text
+}
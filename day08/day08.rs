@@ -1,168 +1,92 @@
-use regex::Regex;
-use std::env;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 
-#[derive(Debug)]
-enum Instruction {
-    Nop,
-    Acc,
-    Jmp,
-    Unknown,
-}
-
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let instruction_pattern =
-        Regex::new(r"(?P<instruction>nop|jmp|acc)\s(?P<value>(\+|\-)\d+)$").unwrap();
+pub mod assembler;
+pub mod debugger;
+pub mod repair;
+pub mod vm;
 
-    let instructions = contents
-        .lines()
-        .map(|line| {
-            let parsed = instruction_pattern.captures(line).unwrap();
-            let instruction = match &parsed["instruction"] {
-                "nop" => Instruction::Nop,
-                "acc" => Instruction::Acc,
-                "jmp" => Instruction::Jmp,
-                _ => {
-                    println!("unknown instruction");
-                    Instruction::Unknown
-                }
-            };
+use vm::{Halt, InstructionSet, Program, Vm};
 
-            let value = match parsed["value"].parse::<i32>() {
-                Ok(value) => value,
-                Err(_) => 0,
-            };
+pub struct Day08;
 
-            (instruction, value)
-        })
-        .collect::<Vec<(Instruction, i32)>>();
-
-    fn process(instructions: Vec<(Instruction, i32)>) -> i32 {
-        let mut accumulator = 0;
-        let mut pc = 0;
-
-        let mut visited_instructions = Vec::<i32>::new();
-        loop {
-            if visited_instructions.contains(&pc) {
-                return accumulator;
-            } else {
-                visited_instructions.push(pc);
-            }
+impl Solution for Day08 {
+    const DAY: usize = 8;
 
-            if pc < 0 || pc >= instructions.len() as i32 {
-                println!("Outside program!");
-                return accumulator;
-            }
+    type Input = Program;
+    type Part1 = i32;
+    type Part2 = i32;
 
-            let (instruction, value) = &instructions[pc as usize];
-            match instruction {
-                Instruction::Nop => {
-                    pc += 1;
-                }
-                Instruction::Acc => {
-                    accumulator += value;
-                    pc += 1;
-                }
-                Instruction::Jmp => {
-                    pc += value;
-                }
-                Instruction::Unknown => {
-                    println!("Unknown instruction");
-                }
-            };
-        }
+    fn parse(contents: &str) -> Result<Program, ParseError> {
+        InstructionSet::default().load(contents)
     }
 
-    process(instructions)
-}
-
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let instruction_pattern =
-        Regex::new(r"(?P<instruction>nop|jmp|acc)\s(?P<value>(\+|\-)\d+)$").unwrap();
-
-    let instructions = contents
-        .lines()
-        .map(|line| {
-            let parsed = instruction_pattern.captures(line).unwrap();
-            let instruction = match &parsed["instruction"] {
-                "nop" => Instruction::Nop,
-                "acc" => Instruction::Acc,
-                "jmp" => Instruction::Jmp,
-                _ => {
-                    println!("unknown instruction");
-                    Instruction::Unknown
-                }
-            };
-
-            let value = match parsed["value"].parse::<i32>() {
-                Ok(value) => value,
-                Err(_) => 0,
-            };
-
-            (instruction, value)
-        })
-        .collect::<Vec<(Instruction, i32)>>();
-
-    fn process(instructions: &Vec<(Instruction, i32)>, fix_instruction: i32) -> (i32, i32) {
-        let mut accumulator = 0;
-        let mut pc = 0;
-
-        let mut visited_instructions = Vec::<i32>::new();
-        loop {
-            if visited_instructions.contains(&pc) {
-                return (1, accumulator);
-            } else {
-                visited_instructions.push(pc);
-            }
-
-            if pc < 0 || pc >= instructions.len() as i32 {
-                return (0, accumulator);
-            }
-
-            let (instruction, value) = &instructions[pc as usize];
-            let fixed_instruction = if fix_instruction == pc {
-                match &instruction {
-                    Instruction::Nop => &Instruction::Jmp,
-                    Instruction::Jmp => &Instruction::Nop,
-                    _ => instruction,
-                }
-            } else {
-                instruction
-            };
-            match fixed_instruction {
-                Instruction::Nop => {
-                    pc += 1;
-                }
-                Instruction::Acc => {
-                    accumulator += value;
-                    pc += 1;
-                }
-                Instruction::Jmp => {
-                    pc += value;
-                }
-                Instruction::Unknown => {
-                    println!("Unknown instruction");
-                }
-            };
-        }
+    fn part1(program: &Program) -> i32 {
+        let instruction_set = InstructionSet::default();
+        let mut vm = Vm::new(program, &instruction_set);
+        let halt = vm.run();
+        debug!("halted after {} steps: {:?}", vm.steps(), halt);
+        vm.accumulator()
     }
 
-    for fix_instruction in 0..instructions.len() as i32 {
-        let (return_value, value) = process(&instructions, fix_instruction);
-        if return_value == 0 {
-            return value;
+    /// The accumulator after the one `nop` or `jmp` swap that makes the
+    /// program terminate. Panics if the program already terminates or if no
+    /// swap, or more than one, makes it terminate, as the answer would be
+    /// meaningless.
+    fn part2(program: &Program) -> i32 {
+        let analysis = repair::analyse(program);
+        debug!("halts as it is: {:?}", analysis.halt);
+        match analysis.repairs.as_slice() {
+            [] if analysis.halt == Halt::Terminated => {
+                panic!("the program terminates without a repair")
+            }
+            [] => panic!("no single `nop` or `jmp` swap makes the program terminate"),
+            [repair] => repair.accumulator,
+            repairs => panic!(
+                "{} swaps make the program terminate, at {}",
+                repairs.len(),
+                repairs
+                    .iter()
+                    .map(|repair| repair.index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
-    return -1;
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day08 {
+    /// A program of `size` instructions, at least one, that loops forever
+    /// and has exactly one `jmp` or `nop` whose repair makes it terminate.
+    ///
+    /// Up to the broken `jmp`, every jump and `nop` targets an instruction
+    /// between the start and the broken `jmp`, which loops back, so no other
+    /// single repair escapes. Past it, jumps only go forward, at most to the
+    /// end of the program.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.max(1) as i32;
+        let broken = rng.gen_range(size / 2, size);
+
+        (0..size)
+            .map(|index| {
+                let (instruction, value) = if index == broken {
+                    ("jmp", rng.gen_range(-broken, 1))
+                } else {
+                    // Forward jumps stay short so that most instructions run
+                    let end = if index < broken { broken } else { size };
+                    match rng.gen_range(0, 10) {
+                        0..=4 => ("acc", rng.gen_range(-50, 51)),
+                        5..=6 if index < broken => {
+                            ("nop", rng.gen_range(-index, broken - index + 1))
+                        }
+                        5..=6 => ("nop", rng.gen_range(-size, size)),
+                        _ => ("jmp", rng.gen_range(1, (end - index).min(10) + 1)),
+                    }
+                };
+                format!("{} {:+}\n", instruction, value)
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,476 @@
+//! A step debugger for boot code.
+//!
+//! The [`Debugger`] wraps a [`Vm`] and can step it forwards and backwards,
+//! run it up to a breakpoint on the `pc` or the accumulator, evaluate watch
+//! expressions after every stop and keep a trace of each instruction run.
+//! It is driven by the same commands whether they are typed at a prompt,
+//! see [`Debugger::interact`], or come from a script, see
+//! [`Debugger::run_script`]:
+//!
+//! - `step [N]` runs the next instruction, or the next `N`.
+//! - `back [N]` undoes the last instruction run, or the last `N`.
+//! - `continue` runs until a breakpoint or until the program halts.
+//! - `break pc N`, `break acc N` stop before the instruction at `N`, or once
+//!   the accumulator is `N`; `delete` with the same arguments removes one.
+//! - `watch EXPR` shows the value of `EXPR` after every stop, and
+//!   `print EXPR` shows it once. Expressions combine `pc`, `acc`, `steps`
+//!   and integers with `+`, `-`, `*` and parentheses.
+//! - `trace on` lists every instruction run from then on, until `trace off`.
+//! - `state` shows where the program is.
+
+use super::vm::{Halt, Instruction, Registers, Vm};
+use std::fmt;
+use std::io;
+use std::io::{BufRead, Write};
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before running the instruction at this index.
+    Pc(i32),
+    /// As soon as the accumulator has this value.
+    Accumulator(i32),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Accumulator(accumulator) => write!(f, "acc {}", accumulator),
+        }
+    }
+}
+
+/// Why the debugger stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// It ran as many instructions as it was asked to.
+    Stepped,
+    Breakpoint(Breakpoint),
+    Halted(Halt),
+    /// There was nothing left to undo.
+    AtStart,
+}
+
+/// One instruction run while tracing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// How many instructions ran before this one.
+    pub step: usize,
+    pub instruction: Instruction,
+    pub before: Registers,
+    pub after: Registers,
+}
+
+pub struct Debugger<'a> {
+    vm: Vm<'a>,
+    /// The registers before each step, to undo them.
+    history: Vec<Registers>,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<(String, Expression)>,
+    tracing: bool,
+    trace: Vec<TraceEntry>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(vm: Vm<'a>) -> Debugger<'a> {
+        Debugger {
+            vm,
+            history: Vec::new(),
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            tracing: false,
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn vm(&self) -> &Vm<'a> {
+        &self.vm
+    }
+
+    /// Runs one instruction, stopping after it at a breakpoint.
+    pub fn step(&mut self) -> Stop {
+        let before = self.vm.registers();
+        let instruction = self.vm.next_instruction();
+        if let Err(halt) = self.vm.step() {
+            return Stop::Halted(halt);
+        }
+        self.history.push(before);
+        if self.tracing {
+            self.trace.push(TraceEntry {
+                step: self.history.len() - 1,
+                instruction: instruction.unwrap(),
+                before,
+                after: self.vm.registers(),
+            });
+        }
+
+        let registers = self.vm.registers();
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| match breakpoint {
+                Breakpoint::Pc(pc) => *pc == registers.pc,
+                Breakpoint::Accumulator(value) => *value == registers.accumulator,
+            })
+            .map_or(Stop::Stepped, |&breakpoint| Stop::Breakpoint(breakpoint))
+    }
+
+    /// Undoes the last instruction run.
+    pub fn back(&mut self) -> Stop {
+        match self.history.pop() {
+            Some(before) => {
+                self.vm.restore(before, before.pc as usize);
+                Stop::Stepped
+            }
+            None => Stop::AtStart,
+        }
+    }
+
+    /// Runs until a breakpoint or until the program halts; always runs at
+    /// least one instruction, to get past a breakpoint it stopped at.
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            match self.step() {
+                Stop::Stepped => (),
+                stop => return stop,
+            }
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Removes a breakpoint, returning whether there was one.
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|&known| known != breakpoint);
+        self.breakpoints.len() != count
+    }
+
+    /// Adds a watch expression, returning its current value.
+    pub fn watch(&mut self, text: &str) -> Result<i64, String> {
+        let expression = Expression::parse(text)?;
+        let value = expression.evaluate(&self.vm);
+        self.watches.push((text.trim().to_string(), expression));
+        Ok(value)
+    }
+
+    /// Every watch expression with its current value.
+    pub fn watches(&self) -> Vec<(&str, i64)> {
+        self.watches
+            .iter()
+            .map(|(text, expression)| (text.as_str(), expression.evaluate(&self.vm)))
+            .collect()
+    }
+
+    pub fn set_tracing(&mut self, tracing: bool) {
+        self.tracing = tracing;
+    }
+
+    /// The instructions run while tracing, including ones undone since.
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    /// Runs one command, returning what it has to say.
+    pub fn execute(&mut self, command: &str) -> Result<String, String> {
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        let count = || match words.get(1) {
+            Some(count) => count
+                .parse::<usize>()
+                .map_err(|_| format!("expected a count, not `{}`", count)),
+            None => Ok(1),
+        };
+        let breakpoint = || match (words.get(1), words.get(2).map(|value| value.parse())) {
+            (Some(&"pc"), Some(Ok(pc))) => Ok(Breakpoint::Pc(pc)),
+            (Some(&"acc"), Some(Ok(value))) => Ok(Breakpoint::Accumulator(value)),
+            _ => Err(format!("usage: {} pc|acc N", words[0])),
+        };
+        let expression = || command.trim().split_once(' ').map_or("", |(_, rest)| rest);
+
+        let trace_start = self.trace.len();
+        let stop = match words.first().copied().unwrap_or("") {
+            "step" | "s" => {
+                let count = count()?;
+                let mut stop = Stop::Stepped;
+                for _ in 0..count {
+                    stop = self.step();
+                    if stop != Stop::Stepped {
+                        break;
+                    }
+                }
+                stop
+            }
+            "back" | "b" => {
+                let count = count()?;
+                let mut stop = Stop::Stepped;
+                for _ in 0..count {
+                    stop = self.back();
+                    if stop != Stop::Stepped {
+                        break;
+                    }
+                }
+                stop
+            }
+            "continue" | "c" => self.resume(),
+            "break" => {
+                let breakpoint = breakpoint()?;
+                self.add_breakpoint(breakpoint);
+                return Ok(format!("breakpoint at {}", breakpoint));
+            }
+            "delete" => {
+                let breakpoint = breakpoint()?;
+                return if self.remove_breakpoint(breakpoint) {
+                    Ok(format!("deleted breakpoint at {}", breakpoint))
+                } else {
+                    Err(format!("no breakpoint at {}", breakpoint))
+                };
+            }
+            "watch" => {
+                let value = self.watch(expression())?;
+                return Ok(format!("watch {} = {}", expression(), value));
+            }
+            "print" | "p" => {
+                let value = Expression::parse(expression())?.evaluate(&self.vm);
+                return Ok(format!("{} = {}", expression(), value));
+            }
+            "trace" => {
+                match words.get(1) {
+                    Some(&"on") => self.set_tracing(true),
+                    Some(&"off") => self.set_tracing(false),
+                    _ => return Err("usage: trace on|off".to_string()),
+                }
+                return Ok(format!("trace {}", words[1]));
+            }
+            "state" => Stop::Stepped,
+            "" => return Err("expected a command".to_string()),
+            unknown => return Err(format!("unknown command `{}`", unknown)),
+        };
+
+        let mut lines = self.trace[trace_start..]
+            .iter()
+            .map(|entry| self.format_trace(entry))
+            .collect::<Vec<_>>();
+        match stop {
+            Stop::Stepped => (),
+            Stop::Breakpoint(breakpoint) => lines.push(format!("breakpoint at {}", breakpoint)),
+            Stop::Halted(halt) => lines.push(format!("halted: {}", describe(halt))),
+            Stop::AtStart => lines.push("at the start".to_string()),
+        }
+        lines.push(self.state());
+        lines.extend(
+            self.watches()
+                .into_iter()
+                .map(|(text, value)| format!("watch {} = {}", text, value)),
+        );
+        Ok(lines.join("\n"))
+    }
+
+    /// Runs every line of `script` as a command, skipping blank lines and
+    /// `#` comments, and returns a transcript of the commands and their
+    /// output, with `error: ` before failures.
+    pub fn run_script(&mut self, script: &str) -> String {
+        let mut transcript = String::new();
+        for command in script.lines().map(str::trim) {
+            if command.is_empty() || command.starts_with('#') {
+                continue;
+            }
+            transcript.push_str(&format!("> {}\n", command));
+            match self.execute(command) {
+                Ok(output) => transcript.push_str(&output),
+                Err(message) => transcript.push_str(&format!("error: {}", message)),
+            }
+            transcript.push('\n');
+        }
+        transcript
+    }
+
+    /// Reads commands from `input` after a prompt on `output` until the
+    /// input ends or says `quit`.
+    pub fn interact(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "{}", self.state())?;
+        write!(output, "(handheld) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let command = line?;
+            match command.trim() {
+                "quit" | "q" => break,
+                "" => (),
+                command => match self.execute(command) {
+                    Ok(text) => writeln!(output, "{}", text)?,
+                    Err(message) => writeln!(output, "error: {}", message)?,
+                },
+            }
+            write!(output, "(handheld) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Where the program is: the number of instructions run, the `pc`, the
+    /// next instruction and the accumulator.
+    pub fn state(&self) -> String {
+        let next = match self.vm.next_instruction() {
+            Some(instruction) => self.vm.instruction_set().format(instruction),
+            None => "-".to_string(),
+        };
+        format!(
+            "step {}  pc {}: {}  acc {}",
+            self.vm.steps(),
+            self.vm.pc(),
+            next,
+            self.vm.accumulator()
+        )
+    }
+
+    fn format_trace(&self, entry: &TraceEntry) -> String {
+        format!(
+            "  {:>5}  pc {:>4}: {:<10}  acc {} -> {}",
+            entry.step,
+            entry.before.pc,
+            self.vm.instruction_set().format(entry.instruction),
+            entry.before.accumulator,
+            entry.after.accumulator
+        )
+    }
+}
+
+fn describe(halt: Halt) -> &'static str {
+    match halt {
+        Halt::Terminated => "terminated",
+        Halt::InfiniteLoop => "the next instruction already ran, so it loops forever",
+        Halt::OutOfBounds => "the pc is out of bounds",
+        Halt::IllegalInstruction => "illegal instruction",
+    }
+}
+
+/// A watch or print expression over the machine's state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expression {
+    Number(i64),
+    Pc,
+    Accumulator,
+    Steps,
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    fn parse(text: &str) -> Result<Expression, String> {
+        let mut chars = text.chars().peekable();
+        let expression = Expression::sum(&mut chars)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Ok(expression),
+            Some(c) => Err(format!("unexpected `{}` in `{}`", c, text.trim())),
+        }
+    }
+
+    fn sum(chars: &mut Peekable<Chars>) -> Result<Expression, String> {
+        let mut expression = Expression::product(chars)?;
+        loop {
+            skip_whitespace(chars);
+            match chars.peek() {
+                Some(&operator) if operator == '+' || operator == '-' => {
+                    chars.next();
+                    let right = Expression::product(chars)?;
+                    expression =
+                        Expression::Binary(operator, Box::new(expression), Box::new(right));
+                }
+                _ => return Ok(expression),
+            }
+        }
+    }
+
+    fn product(chars: &mut Peekable<Chars>) -> Result<Expression, String> {
+        let mut expression = Expression::atom(chars)?;
+        loop {
+            skip_whitespace(chars);
+            if chars.peek() != Some(&'*') {
+                return Ok(expression);
+            }
+            chars.next();
+            let right = Expression::atom(chars)?;
+            expression = Expression::Binary('*', Box::new(expression), Box::new(right));
+        }
+    }
+
+    fn atom(chars: &mut Peekable<Chars>) -> Result<Expression, String> {
+        skip_whitespace(chars);
+        match chars.peek().copied() {
+            Some('(') => {
+                chars.next();
+                let expression = Expression::sum(chars)?;
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(')') => Ok(expression),
+                    _ => Err("expected `)`".to_string()),
+                }
+            }
+            Some('-') => {
+                chars.next();
+                Ok(Expression::Negate(Box::new(Expression::atom(chars)?)))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let digits = take_while(chars, |c| c.is_ascii_digit());
+                digits
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|error| format!("`{}`: {}", digits, error))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                match take_while(chars, |c| c.is_ascii_alphabetic()).as_str() {
+                    "pc" => Ok(Expression::Pc),
+                    "acc" => Ok(Expression::Accumulator),
+                    "steps" => Ok(Expression::Steps),
+                    name => Err(format!(
+                        "unknown name `{}`, expected pc, acc or steps",
+                        name
+                    )),
+                }
+            }
+            Some(c) => Err(format!("unexpected `{}`", c)),
+            None => Err("expected an expression".to_string()),
+        }
+    }
+
+    fn evaluate(&self, vm: &Vm) -> i64 {
+        match self {
+            Expression::Number(number) => *number,
+            Expression::Pc => i64::from(vm.pc()),
+            Expression::Accumulator => i64::from(vm.accumulator()),
+            Expression::Steps => vm.steps() as i64,
+            Expression::Negate(expression) => expression.evaluate(vm).wrapping_neg(),
+            Expression::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(vm), right.evaluate(vm));
+                match operator {
+                    '+' => left.wrapping_add(right),
+                    '-' => left.wrapping_sub(right),
+                    _ => left.wrapping_mul(right),
+                }
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn take_while(chars: &mut Peekable<Chars>, keep: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+    while let Some(&c) = chars.peek() {
+        if !keep(c) {
+            break;
+        }
+        taken.push(c);
+        chars.next();
+    }
+    taken
+}
@@ -0,0 +1,111 @@
+//! Finds the `nop` or `jmp` to swap to make boot code terminate, in time
+//! linear in the length of the program.
+//!
+//! Every instruction has exactly one successor, so the instructions from
+//! which the program runs off its end form a tree rooted at the end, found
+//! by a search backwards along the jumps. Swapping an instruction a broken
+//! program runs repairs it exactly when its new successor is in that tree:
+//! the instruction itself can't be, since the program doesn't terminate
+//! with it unswapped, so the rest of the run doesn't come back to it.
+//!
+//! Only the boot code's `nop`, `acc` and `jmp` are understood; any other
+//! operation is treated as a dead end.
+
+use super::vm::{Halt, Instruction, InstructionSet, Vm, ACC, JMP, NOP};
+
+/// Swapping the instruction at `index` makes the program terminate with
+/// `accumulator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repair {
+    pub index: usize,
+    pub accumulator: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    /// How the program halts as it is.
+    pub halt: Halt,
+    /// Every repair, by index, or none if the program terminates as it is.
+    /// Swapping instructions the program never runs changes nothing, so
+    /// they aren't listed.
+    pub repairs: Vec<Repair>,
+}
+
+/// Where `instruction` at `index` goes next, if that is in the program or
+/// right after its end.
+fn successor(program: &[Instruction], index: usize, instruction: Instruction) -> Option<usize> {
+    let offset = match instruction.opcode {
+        NOP | ACC => 1,
+        JMP => instruction.argument as i64,
+        _ => return None,
+    };
+    let next = index as i64 + offset;
+    if next >= 0 && next <= program.len() as i64 {
+        Some(next as usize)
+    } else {
+        None
+    }
+}
+
+fn swapped(instruction: Instruction) -> Option<Instruction> {
+    let opcode = match instruction.opcode {
+        NOP => JMP,
+        JMP => NOP,
+        _ => return None,
+    };
+    Some(Instruction {
+        opcode,
+        ..instruction
+    })
+}
+
+pub fn analyse(program: &[Instruction]) -> Analysis {
+    let mut predecessors = vec![Vec::new(); program.len() + 1];
+    for (index, &instruction) in program.iter().enumerate() {
+        if let Some(next) = successor(program, index, instruction) {
+            predecessors[next].push(index);
+        }
+    }
+
+    // What the accumulator gains from each instruction to the end, for
+    // those that reach it
+    let mut to_end = vec![None; program.len() + 1];
+    to_end[program.len()] = Some(0);
+    let mut queue = vec![program.len()];
+    while let Some(next) = queue.pop() {
+        for &index in &predecessors[next] {
+            let gain = match program[index].opcode {
+                ACC => program[index].argument,
+                _ => 0,
+            };
+            to_end[index] = Some(to_end[next].unwrap() + gain);
+            queue.push(index);
+        }
+    }
+
+    let instruction_set = InstructionSet::default();
+    let mut vm = Vm::new(program, &instruction_set);
+    let mut repairs = Vec::new();
+    let halt = loop {
+        if let Some(halt) = vm.halt() {
+            break halt;
+        }
+        let index = vm.pc() as usize;
+        let target = swapped(program[index])
+            .and_then(|instruction| successor(program, index, instruction))
+            .and_then(|next| to_end[next]);
+        if let Some(gain) = target {
+            repairs.push(Repair {
+                index,
+                accumulator: vm.accumulator() + gain,
+            });
+        }
+        vm.step().unwrap();
+    };
+
+    if halt == Halt::Terminated {
+        repairs.clear();
+    }
+    repairs.sort_unstable_by_key(|repair| repair.index);
+    Analysis { halt, repairs }
+}
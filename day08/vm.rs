@@ -0,0 +1,265 @@
+//! The handheld game console's boot code interpreter.
+//!
+//! A [`Program`] is a list of [`Instruction`]s, each an operation from an
+//! [`InstructionSet`] and a signed argument. The [`Vm`] runs it one step at
+//! a time until it halts, and reports why with a [`Halt`].
+//!
+//! The boot code only knows `nop`, `acc` and `jmp`, but an instruction set
+//! is just a table of named functions on the [`Registers`], so new
+//! operations can be added with [`InstructionSet::with`].
+
+use crate::error::parse_at;
+use crate::ParseError;
+use std::fmt;
+
+/// An operation's index in its instruction set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Opcode(pub usize);
+
+/// Does nothing and moves on to the next instruction.
+pub const NOP: Opcode = Opcode(0);
+/// Adds its argument to the accumulator.
+pub const ACC: Opcode = Opcode(1);
+/// Jumps by its argument, relative to itself.
+pub const JMP: Opcode = Opcode(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Registers {
+    pub pc: i32,
+    pub accumulator: i32,
+}
+
+/// Carries out an operation with its argument; it has to move the `pc`
+/// itself.
+pub type Execute = fn(&mut Registers, i32);
+
+#[derive(Clone)]
+pub struct InstructionSet {
+    operations: Vec<(&'static str, Execute)>,
+}
+
+impl InstructionSet {
+    /// A set without any operations.
+    pub fn empty() -> InstructionSet {
+        InstructionSet {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Adds an operation, whose opcode is the number of operations before
+    /// it.
+    pub fn with(mut self, mnemonic: &'static str, execute: Execute) -> InstructionSet {
+        self.operations.push((mnemonic, execute));
+        self
+    }
+
+    pub fn opcode(&self, mnemonic: &str) -> Option<Opcode> {
+        self.operations
+            .iter()
+            .position(|(known, _)| *known == mnemonic)
+            .map(Opcode)
+    }
+
+    pub fn mnemonic(&self, opcode: Opcode) -> Option<&'static str> {
+        self.operations.get(opcode.0).map(|(mnemonic, _)| *mnemonic)
+    }
+
+    /// An instruction as it would be written in a program, as in `jmp -3`;
+    /// opcodes not in the set show as their number.
+    pub fn format(&self, instruction: Instruction) -> String {
+        match self.mnemonic(instruction.opcode) {
+            Some(mnemonic) => format!("{} {:+}", mnemonic, instruction.argument),
+            None => format!("<{}> {:+}", instruction.opcode.0, instruction.argument),
+        }
+    }
+
+    fn execute(&self, opcode: Opcode) -> Option<Execute> {
+        self.operations.get(opcode.0).map(|(_, execute)| *execute)
+    }
+
+    /// Reads a program with one instruction per line: a mnemonic from this
+    /// set and a signed argument, as in `jmp -3`.
+    pub fn load(&self, contents: &str) -> Result<Program, ParseError> {
+        contents
+            .lines()
+            .map(|line| {
+                let (mnemonic, argument) = line.split_once(' ').ok_or_else(|| {
+                    ParseError::at(contents, line, "expected an operation and a signed value")
+                })?;
+                let opcode = self.opcode(mnemonic).ok_or_else(|| {
+                    let expected = self
+                        .operations
+                        .iter()
+                        .map(|(mnemonic, _)| format!("`{}`", mnemonic))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    ParseError::at(
+                        contents,
+                        mnemonic,
+                        format!("unknown operation, expected one of {}", expected),
+                    )
+                })?;
+                if !argument.starts_with(['+', '-']) {
+                    return Err(ParseError::at(contents, argument, "expected a sign"));
+                }
+                let argument = parse_at(contents, argument)?;
+                Ok(Instruction { opcode, argument })
+            })
+            .collect()
+    }
+}
+
+/// The boot code's `nop`, `acc` and `jmp`, as [`NOP`], [`ACC`] and [`JMP`].
+impl Default for InstructionSet {
+    fn default() -> InstructionSet {
+        InstructionSet::empty()
+            .with("nop", |registers, _| registers.pc += 1)
+            .with("acc", |registers, argument| {
+                registers.accumulator += argument;
+                registers.pc += 1;
+            })
+            .with("jmp", |registers, argument| registers.pc += argument)
+    }
+}
+
+impl fmt::Debug for InstructionSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.operations.iter().map(|(mnemonic, _)| mnemonic))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub argument: i32,
+}
+
+pub type Program = Vec<Instruction>;
+
+/// Why a program stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    /// The `pc` reached the instruction right after the last one.
+    Terminated,
+    /// The next instruction has already run once, so the program would
+    /// repeat itself forever.
+    InfiniteLoop,
+    /// The `pc` went before the first instruction or past the one after the
+    /// last.
+    OutOfBounds,
+    /// The next instruction's opcode isn't in the instruction set.
+    IllegalInstruction,
+}
+
+pub struct Vm<'a> {
+    program: &'a [Instruction],
+    instruction_set: &'a InstructionSet,
+    registers: Registers,
+    executed: Vec<bool>,
+    steps: usize,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a [Instruction], instruction_set: &'a InstructionSet) -> Vm<'a> {
+        Vm {
+            program,
+            instruction_set,
+            registers: Registers::default(),
+            executed: vec![false; program.len()],
+            steps: 0,
+        }
+    }
+
+    pub fn program(&self) -> &'a [Instruction] {
+        self.program
+    }
+
+    pub fn instruction_set(&self) -> &'a InstructionSet {
+        self.instruction_set
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    pub fn accumulator(&self) -> i32 {
+        self.registers.accumulator
+    }
+
+    pub fn pc(&self) -> i32 {
+        self.registers.pc
+    }
+
+    /// Instructions executed so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Whether the instruction at `index` has run.
+    pub fn has_executed(&self, index: usize) -> bool {
+        self.executed.get(index).copied().unwrap_or(false)
+    }
+
+    /// The instruction at the `pc`, if there is one.
+    pub fn next_instruction(&self) -> Option<Instruction> {
+        if self.registers.pc < 0 {
+            return None;
+        }
+        self.program.get(self.registers.pc as usize).copied()
+    }
+
+    /// Why the program would stop before running the next instruction, if
+    /// it would.
+    pub fn halt(&self) -> Option<Halt> {
+        let pc = self.registers.pc;
+        if pc == self.program.len() as i32 {
+            return Some(Halt::Terminated);
+        }
+        let instruction = match self.next_instruction() {
+            Some(instruction) => instruction,
+            None => return Some(Halt::OutOfBounds),
+        };
+        if self.executed[pc as usize] {
+            Some(Halt::InfiniteLoop)
+        } else if self.instruction_set.execute(instruction.opcode).is_none() {
+            Some(Halt::IllegalInstruction)
+        } else {
+            None
+        }
+    }
+
+    /// Runs the next instruction, or returns why it can't without changing
+    /// anything.
+    pub fn step(&mut self) -> Result<(), Halt> {
+        if let Some(halt) = self.halt() {
+            return Err(halt);
+        }
+
+        let index = self.registers.pc as usize;
+        let instruction = self.program[index];
+        let execute = self.instruction_set.execute(instruction.opcode).unwrap();
+        execute(&mut self.registers, instruction.argument);
+        self.executed[index] = true;
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Runs until the program halts.
+    pub fn run(&mut self) -> Halt {
+        loop {
+            if let Err(halt) = self.step() {
+                return halt;
+            }
+        }
+    }
+
+    /// Undoes the last step, given the registers from before it and the
+    /// index of the instruction it ran.
+    pub fn restore(&mut self, registers: Registers, index: usize) {
+        self.registers = registers;
+        self.executed[index] = false;
+        self.steps -= 1;
+    }
+}
@@ -1,17 +1,13 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
 use itertools::Itertools;
-use std::env;
+use rand::rngs::StdRng;
+use rand::Rng;
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let preamble = 25;
-
-    let sequence = contents
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
+pub struct Day09;
 
+fn find_invalid_entry(sequence: &[usize], preamble: usize) -> usize {
     let mut invalid_entry = 0;
     sequence
         .iter()
@@ -22,7 +18,7 @@ fn solve_part1(inputfile: String) -> usize {
                 .combinations(2)
                 .any(|combination| combination[0] + combination[1] == *entry)
             {
-                println!(
+                debug!(
                     "invalid entry: {} not in {:?}",
                     *entry,
                     &sequence[index - preamble..index]
@@ -34,62 +30,141 @@ fn solve_part1(inputfile: String) -> usize {
     invalid_entry
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+impl Solution for Day09 {
+    const DAY: usize = 9;
 
-    let preamble = 25;
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let sequence = contents
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        contents
+            .lines()
+            .map(|line| parse_at(contents, line))
+            .collect()
+    }
 
-    let mut invalid_entry = 0;
-    sequence
-        .iter()
-        .skip(preamble)
-        .fold(preamble, |index, entry| {
-            if !&sequence[index - preamble..index]
-                .iter()
-                .combinations(2)
-                .any(|combination| combination[0] + combination[1] == *entry)
-            {
-                invalid_entry = *entry;
-            }
-            index + 1
-        });
+    fn part1(sequence: &Vec<usize>) -> usize {
+        find_invalid_entry(sequence, 25)
+    }
+
+    fn part2(sequence: &Vec<usize>) -> usize {
+        let invalid_entry = find_invalid_entry(sequence, 25);
 
-    let mut weakness = 0;
-    for start_index in 0..sequence.len() {
-        sequence
-            .iter()
-            .enumerate()
-            .skip(start_index)
-            .any(|(index, entry)| {
-                if index - start_index > 1 {
-                    let subsequence = &sequence[start_index..index];
+        let mut weakness = 0;
+        for start_index in 0..sequence.len() {
+            sequence
+                .iter()
+                .enumerate()
+                .skip(start_index)
+                .any(|(index, _)| {
+                    if index - start_index > 1 {
+                        let subsequence = &sequence[start_index..index];
 
-                    let sum = subsequence.iter().sum::<usize>();
+                        let sum = subsequence.iter().sum::<usize>();
 
-                    if sum == invalid_entry {
-                        let min = subsequence.iter().min().unwrap();
-                        let max = subsequence.iter().max().unwrap();
+                        if sum == invalid_entry {
+                            let min = subsequence.iter().min().unwrap();
+                            let max = subsequence.iter().max().unwrap();
 
-                        weakness = min + max;
-                        true
+                            weakness = min + max;
+                            true
+                        } else {
+                            false
+                        }
                     } else {
                         false
                     }
-                } else {
-                    false
-                }
-            });
+                });
+        }
+        weakness
+    }
+}
+
+/// The length of the window that generated numbers are checked against.
+const PREAMBLE: usize = 25;
+
+/// How many candidates the generator tries, for the invalid number and for
+/// the whole input, before giving up. Each is accepted far more often than
+/// not, so running out means something is wrong rather than unlucky.
+const ATTEMPTS: usize = 1000;
+
+impl Generator for Day09 {
+    /// `size` numbers, 28 to 1000, of which exactly one is not the sum of
+    /// two of the 25 before it, but is the sum of exactly one contiguous
+    /// run of at least two numbers.
+    ///
+    /// Every valid number is at least twice the smallest of the 25 before
+    /// it, so the numbers double every 25 lines or so; sums of the smallest
+    /// numbers keep 1000 lines below 2^50.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.clamp(PREAMBLE + 3, 1000);
+        for _ in 0..ATTEMPTS {
+            let sequence = generate_sequence(rng, size);
+            if contiguous_runs(&sequence, find_invalid_entry(&sequence, PREAMBLE)) == 1 {
+                return sequence
+                    .iter()
+                    .map(|number| format!("{}\n", number))
+                    .collect();
+            }
+        }
+        panic!("no input with a single run found in {} attempts", ATTEMPTS);
     }
-    weakness
 }
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+
+/// Counts the runs of at least two consecutive numbers that sum to `target`.
+///
+/// The numbers are all positive, so each run ending at a number starts at
+/// the first number keeping the sum within `target`, found by sliding the
+/// start along behind the end.
+fn contiguous_runs(sequence: &[usize], target: usize) -> usize {
+    let (mut start, mut sum, mut runs) = (0, 0, 0);
+    for end in 0..sequence.len() {
+        sum += sequence[end];
+        while sum > target {
+            sum -= sequence[start];
+            start += 1;
+        }
+        if sum == target && end > start {
+            runs += 1;
+        }
+    }
+    runs
+}
+
+/// One candidate input, whose invalid number may have several runs.
+fn generate_sequence(rng: &mut StdRng, size: usize) -> Vec<usize> {
+    let invalid_at = rng.gen_range(PREAMBLE + 2, size);
+
+    let mut sequence = (0..PREAMBLE)
+        .map(|_| rng.gen_range(1, 51))
+        .collect::<Vec<usize>>();
+    while sequence.len() < size {
+        let window = &sequence[sequence.len() - PREAMBLE..];
+        let next = if sequence.len() == invalid_at {
+            (0..ATTEMPTS)
+                .map(|_| {
+                    let start = rng.gen_range(0, invalid_at - 1);
+                    let end = rng.gen_range(start + 2, (start + 17).min(invalid_at) + 1);
+                    sequence[start..end].iter().sum::<usize>()
+                })
+                .find(|&sum| {
+                    !window
+                        .iter()
+                        .tuple_combinations()
+                        .any(|(a, b)| a + b == sum)
+                })
+                .unwrap_or_else(|| panic!("no invalid number found in {} attempts", ATTEMPTS))
+        } else {
+            let smallest = window.iter().copied().sorted().take(6).collect::<Vec<_>>();
+            let (a, b) = smallest
+                .iter()
+                .tuple_combinations()
+                .nth(rng.gen_range(0, 15))
+                .unwrap();
+            a + b
+        };
+        sequence.push(next);
+    }
+    sequence
 }
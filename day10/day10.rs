@@ -1,107 +1,154 @@
-use itertools::Itertools;
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut adapters = contents
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-    adapters.sort();
-
-    let used_adapters = Vec::<usize>::new();
-    let initial_outlet = 0;
-
-    fn find_adapter_chain(
-        adapters: &Vec<usize>,
-        previous_outlet: usize,
-        differences: &mut HashMap<usize, usize>,
-    ) -> usize {
-        let jolt_difference = 3;
-        match adapters
-            .iter()
-            .filter_map(|&jolt| {
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        let mut adapters = contents
+            .lines()
+            .map(|line| parse_at(contents, line))
+            .collect::<Result<Vec<usize>, ParseError>>()?;
+        if adapters.is_empty() {
+            return Err(ParseError::at(
+                contents,
+                &contents[contents.len()..],
+                "expected at least one adapter",
+            ));
+        }
+        adapters.sort_unstable();
+        Ok(adapters)
+    }
+
+    fn part1(adapters: &Vec<usize>) -> usize {
+        let initial_outlet = 0;
+
+        fn find_adapter_chain(
+            adapters: &[usize],
+            previous_outlet: usize,
+            differences: &mut HashMap<usize, usize>,
+        ) -> usize {
+            let jolt_difference = 3;
+            match adapters
+                .iter()
+                .filter_map(|&jolt| {
+                    if jolt > previous_outlet && jolt <= (previous_outlet + jolt_difference) {
+                        differences
+                            .entry(jolt - previous_outlet)
+                            .and_modify(|e| *e += 1)
+                            .or_insert(1);
+                        Some(jolt)
+                    } else {
+                        None
+                    }
+                })
+                .next()
+            {
+                Some(next_adapter) => find_adapter_chain(adapters, next_adapter, differences),
+                None => previous_outlet + 3,
+            }
+        }
+        let mut differences = HashMap::<usize, usize>::new();
+        let final_adapter = find_adapter_chain(adapters, initial_outlet, &mut differences);
+
+        // The device's own adapter is always three jolts above the last
+        let one_diffs = differences.get(&1).copied().unwrap_or(0);
+        let three_diffs = differences.get(&3).copied().unwrap_or(0) + 1;
+        debug!(
+            "All differences: {:?}, final: {}",
+            differences, final_adapter
+        );
+
+        one_diffs * three_diffs
+    }
+
+    fn part2(adapters: &Vec<usize>) -> usize {
+        let initial_outlet = 0;
+
+        fn find_adapter_chain(
+            adapters: &[usize],
+            previous_outlet: usize,
+            cache: &mut HashMap<usize, usize>,
+        ) -> usize {
+            let jolt_difference = 3;
+            let valid_adapters = adapters.iter().filter_map(|&jolt| {
                 if jolt > previous_outlet && jolt <= (previous_outlet + jolt_difference) {
-                    differences
-                        .entry(jolt - previous_outlet)
-                        .and_modify(|e| *e += 1)
-                        .or_insert(1);
                     Some(jolt)
                 } else {
                     None
                 }
+            });
+
+            let num_available_adapters = valid_adapters.clone().count();
+            if num_available_adapters == 0 {
+                return 1;
+            }
+
+            valid_adapters.fold(0, |sum, next_adapter| {
+                sum + if cache.contains_key(&next_adapter) {
+                    cache[&next_adapter]
+                } else {
+                    let next_value = find_adapter_chain(adapters, next_adapter, cache);
+                    cache.insert(next_adapter, next_value);
+                    next_value
+                }
             })
-            .next()
-        {
-            Some(next_adapter) => find_adapter_chain(&adapters, next_adapter, differences),
-            None => previous_outlet + 3,
         }
-    }
-    let mut differences = HashMap::<usize, usize>::new();
-    let final_adapter = find_adapter_chain(&adapters, initial_outlet, &mut differences);
 
-    let one_diffs = differences[&1];
-    let three_diffs = differences[&3] + 1;
-    println!(
-        "All differences: {:?}, final: {}",
-        differences, final_adapter
-    );
-
-    one_diffs * three_diffs
+        let mut cache = HashMap::<usize, usize>::new();
+        find_adapter_chain(adapters, initial_outlet, &mut cache)
+    }
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut adapters = contents
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-    adapters.sort();
-
-    let used_adapters = Vec::<usize>::new();
-    let initial_outlet = 0;
-
-    fn find_adapter_chain(
-        adapters: &Vec<usize>,
-        previous_outlet: usize,
-        cache: &mut HashMap<usize, usize>,
-    ) -> usize {
-        let jolt_difference = 3;
-        let valid_adapters = adapters.iter().filter_map(|&jolt| {
-            if jolt > previous_outlet && jolt <= (previous_outlet + jolt_difference) {
-                Some(jolt)
+impl Generator for Day10 {
+    /// `size` adapters, at least 2, joined by differences of 1 and 3.
+    ///
+    /// Each run of differences of 1 multiplies the number of arrangements,
+    /// so once there would be more than 2^60 of them the runs stay at a
+    /// single difference of 1.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        // Arrangements contributed by a run of 1 to 4 differences of 1
+        const ARRANGEMENTS: [usize; 5] = [1, 1, 2, 4, 7];
+
+        let size = size.max(2);
+        let mut adapters = Vec::<usize>::new();
+        let mut jolts = 0;
+        let mut arrangements = 1_usize;
+        while adapters.len() < size {
+            let mut run = if adapters.is_empty() {
+                1
             } else {
-                None
+                rng.gen_range(1, 5)
+            };
+            if arrangements * ARRANGEMENTS[run] > 1 << 60 {
+                run = 1;
             }
-        });
+            arrangements *= ARRANGEMENTS[run];
 
-        let num_available_adapters = valid_adapters.clone().fold(0, |count, _| count + 1);
-        if num_available_adapters == 0 {
-            return 1;
+            for _ in 0..run {
+                jolts += 1;
+                adapters.push(jolts);
+            }
+            jolts += 3;
+            adapters.push(jolts);
         }
+        adapters.truncate(size);
 
-        valid_adapters.fold(0, |sum, next_adapter| {
-            sum + if cache.contains_key(&next_adapter) {
-                cache[&next_adapter]
-            } else {
-                let next_value = find_adapter_chain(&adapters, next_adapter, cache);
-                cache.insert(next_adapter, next_value);
-                next_value
-            }
-        })
+        adapters.shuffle(rng);
+        adapters
+            .iter()
+            .map(|jolts| format!("{}\n", jolts))
+            .collect()
     }
-
-    let mut cache = HashMap::<usize, usize>::new();
-    find_adapter_chain(&adapters, initial_outlet, &mut cache)
-}
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
 }
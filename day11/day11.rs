@@ -1,262 +1,248 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::env;
-
-type Coordinate = (i32, i32);
+use crate::generate::Generator;
+use crate::grid::{Coordinate, Grid, NEIGHBOURS_8};
+use crate::render::{Animation, Canvas, WHITE};
+use crate::{debug, info, trace, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum SeatingStatus {
+pub enum SeatingStatus {
     Floor,
     EmptySeat,
     OccupiedSeat,
     Unknown,
 }
 
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut seating_map = HashMap::<Coordinate, SeatingStatus>::new();
+pub type SeatingMap = Grid<SeatingStatus>;
 
-    let mut dimensions: Coordinate = (0, 0);
-    contents.lines().enumerate().for_each(|(y, row)| {
-        dimensions.1 = y as i32 + 1;
-        row.chars().enumerate().for_each(|(x, value)| {
-            if (x as i32 + 1) > dimensions.0 {
-                dimensions.0 = x as i32 + 1;
-            }
-            let coord = (x as i32, y as i32);
-            seating_map.entry(coord).or_insert(match value {
-                '.' => SeatingStatus::Floor,
-                '#' => SeatingStatus::OccupiedSeat,
-                'L' => SeatingStatus::EmptySeat,
-                _ => SeatingStatus::Unknown,
-            });
-        });
-    });
+pub struct Day11;
 
-    fn occupy_seat(
-        seating_map: &HashMap<Coordinate, SeatingStatus>,
-        coord: &Coordinate,
-    ) -> SeatingStatus {
-        let coords_to_check = [
-            (-1, -1),
-            (-1, 1),
-            (-1, 0),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 1),
-            (1, 0),
-        ];
+fn occupy_seat_adjacent(seating_map: &SeatingMap, coord: &Coordinate) -> SeatingStatus {
+    let occupied_adjacent_seats = seating_map
+        .neighbours8(*coord)
+        .filter(|(_, seating)| **seating == SeatingStatus::OccupiedSeat)
+        .count();
 
-        let occupied_adjacent_seats = coords_to_check.iter().fold(0, |sum, relative_coord| {
-            let check = (coord.0 + relative_coord.0, coord.1 + relative_coord.1);
-            match seating_map.get(&check) {
-                Some(valid_seating) => {
-                    sum + match valid_seating {
-                        SeatingStatus::OccupiedSeat => 1,
-                        _ => 0,
-                    }
+    match seating_map.get(*coord) {
+        Some(valid_seating) => match valid_seating {
+            SeatingStatus::EmptySeat => {
+                if occupied_adjacent_seats == 0 {
+                    SeatingStatus::OccupiedSeat
+                } else {
+                    *valid_seating
+                }
+            }
+            SeatingStatus::OccupiedSeat => {
+                if occupied_adjacent_seats >= 4 {
+                    SeatingStatus::EmptySeat
+                } else {
+                    *valid_seating
                 }
-                None => sum,
             }
-        });
+            _ => *valid_seating,
+        },
+        None => {
+            debug!("Unknown seating combination at: {:?}", coord);
+            SeatingStatus::Unknown
+        }
+    }
+}
 
-        match seating_map.get(&coord) {
-            Some(valid_seating) => match valid_seating {
-                SeatingStatus::EmptySeat => {
-                    if occupied_adjacent_seats == 0 {
-                        SeatingStatus::OccupiedSeat
-                    } else {
-                        *valid_seating
-                    }
+fn occupy_seat_visible(seating_map: &SeatingMap, coord: &Coordinate) -> SeatingStatus {
+    let occupied_adjacent_seats = NEIGHBOURS_8
+        .iter()
+        .filter(|&&direction| {
+            let first_seat = seating_map
+                .ray(*coord, direction)
+                .map(|(_, seating)| *seating)
+                .find(|seating| {
+                    *seating == SeatingStatus::OccupiedSeat || *seating == SeatingStatus::EmptySeat
+                });
+            first_seat == Some(SeatingStatus::OccupiedSeat)
+        })
+        .count();
+
+    match seating_map.get(*coord) {
+        Some(valid_seating) => match valid_seating {
+            SeatingStatus::EmptySeat => {
+                if occupied_adjacent_seats == 0 {
+                    SeatingStatus::OccupiedSeat
+                } else {
+                    *valid_seating
                 }
-                SeatingStatus::OccupiedSeat => {
-                    if occupied_adjacent_seats >= 4 {
-                        SeatingStatus::EmptySeat
-                    } else {
-                        *valid_seating
-                    }
+            }
+            SeatingStatus::OccupiedSeat => {
+                if occupied_adjacent_seats >= 5 {
+                    SeatingStatus::EmptySeat
+                } else {
+                    *valid_seating
                 }
-                _ => *valid_seating,
-            },
-            None => {
-                println!("Unknown seating combination at: {:?}", coord);
-                SeatingStatus::Unknown
             }
+            _ => *valid_seating,
+        },
+        None => {
+            debug!("Unknown seating combination at: {:?}", coord);
+            SeatingStatus::Unknown
         }
     }
+}
 
-    fn run_generation(seating_map: &mut HashMap<Coordinate, SeatingStatus>) -> usize {
-        let seating_changes = seating_map
-            .iter()
-            .filter_map(|(coord, seat_status)| {
-                let next_status = occupy_seat(&seating_map, coord);
+fn run_generation(
+    seating_map: &mut SeatingMap,
+    occupy_seat: fn(&SeatingMap, &Coordinate) -> SeatingStatus,
+) -> usize {
+    let seating_changes = seating_map
+        .iter()
+        .filter_map(|(coord, seat_status)| {
+            let next_status = occupy_seat(seating_map, &coord);
+
+            if *seat_status == next_status {
+                None
+            } else {
+                Some((coord, next_status))
+            }
+        })
+        .collect::<Vec<(Coordinate, SeatingStatus)>>();
 
-                if *seat_status == next_status {
-                    None
-                } else {
-                    Some((*coord, next_status))
-                }
-            })
-            .collect::<Vec<(Coordinate, SeatingStatus)>>();
+    seating_changes.iter().for_each(|(coord, status)| {
+        seating_map.insert(*coord, *status);
+    });
 
-        seating_changes.iter().for_each(|(coord, status)| {
-            seating_map.entry(*coord).and_modify(|e| *e = *status);
-        });
+    seating_changes.len()
+}
 
-        seating_changes.len()
+/// Draws the waiting area with a square per place: empty seats green and
+/// occupied ones red on a white floor.
+fn draw(seating_map: &SeatingMap) -> Canvas {
+    let bounds = seating_map.bounds();
+    let mut canvas = Canvas::new(bounds.width() as u32, bounds.height() as u32, 4, WHITE);
+    for (coord, seating) in seating_map.iter() {
+        let color = match seating {
+            SeatingStatus::EmptySeat => (0x33, 0xCC, 0x66),
+            SeatingStatus::OccupiedSeat => (0xCC, 0x33, 0x33),
+            _ => continue,
+        };
+        canvas.put((coord.0 - bounds.min.0, coord.1 - bounds.min.1), color);
     }
+    canvas
+}
+
+/// Runs generations until no seat changes, animating them as `name`.
+fn count_stable_occupied_seats(
+    seating_map: &SeatingMap,
+    occupy_seat: fn(&SeatingMap, &Coordinate) -> SeatingStatus,
+    name: &str,
+) -> usize {
+    let mut seating_map = seating_map.clone();
+    let mut animation = Animation::new(name);
 
     loop {
-        let seat_changes = run_generation(&mut seating_map);
-        println!("seat_changes: {}", seat_changes);
+        animation.frame(|| draw(&seating_map));
+        let seat_changes = run_generation(&mut seating_map, occupy_seat);
+        trace!("seat_changes: {}", seat_changes);
         if seat_changes == 0 {
             break;
         }
     }
+    if let Err(error) = animation.finish() {
+        info!("{}: could not write the animation: {}", name, error);
+    }
 
-    seating_map.iter().fold(0, |sum, (_, seat_status)| {
-        if *seat_status == SeatingStatus::OccupiedSeat {
-            sum + 1
-        } else {
-            sum
-        }
-    })
+    seating_map
+        .values()
+        .filter(|&&seat_status| seat_status == SeatingStatus::OccupiedSeat)
+        .count()
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut seating_map = HashMap::<Coordinate, SeatingStatus>::new();
-
-    let mut dimensions: Coordinate = (0, 0);
-    contents.lines().enumerate().for_each(|(y, row)| {
-        dimensions.1 = y as i32 + 1;
-        row.chars().enumerate().for_each(|(x, value)| {
-            if (x as i32 + 1) > dimensions.0 {
-                dimensions.0 = x as i32 + 1;
-            }
-            let coord = (x as i32, y as i32);
-            seating_map.entry(coord).or_insert(match value {
-                '.' => SeatingStatus::Floor,
-                '#' => SeatingStatus::OccupiedSeat,
-                'L' => SeatingStatus::EmptySeat,
-                _ => SeatingStatus::Unknown,
-            });
-        });
-    });
-
-    fn occupy_seat(
-        seating_map: &HashMap<Coordinate, SeatingStatus>,
-        coord: &Coordinate,
-    ) -> SeatingStatus {
-        let directions_to_check = [
-            (-1, -1),
-            (-1, 1),
-            (-1, 0),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 1),
-            (1, 0),
-        ];
-
-        let occupied_adjacent_seats = directions_to_check.iter().fold(0, |sum, direction| {
-            let mut check = *coord;
-            let mut is_direction_occupied = false;
-            loop {
-                check.0 += direction.0;
-                check.1 += direction.1;
-
-                match seating_map.get(&check) {
-                    Some(valid_seating) => match valid_seating {
-                        SeatingStatus::OccupiedSeat => {
-                            is_direction_occupied = true;
-                            break;
-                        }
-                        SeatingStatus::EmptySeat => {
-                            break;
-                        }
-                        _ => (),
-                    },
-                    None => break,
-                };
-            }
-            if is_direction_occupied {
-                sum + 1
-            } else {
-                sum
-            }
-        });
-
-        match seating_map.get(&coord) {
-            Some(valid_seating) => match valid_seating {
-                SeatingStatus::EmptySeat => {
-                    if occupied_adjacent_seats == 0 {
-                        SeatingStatus::OccupiedSeat
-                    } else {
-                        *valid_seating
-                    }
-                }
-                SeatingStatus::OccupiedSeat => {
-                    if occupied_adjacent_seats >= 5 {
-                        SeatingStatus::EmptySeat
-                    } else {
-                        *valid_seating
-                    }
-                }
-                _ => *valid_seating,
+impl Solution for Day11 {
+    const DAY: usize = 11;
+
+    type Input = SeatingMap;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<SeatingMap, ParseError> {
+        Grid::parse(
+            contents,
+            contents,
+            "expected `.`, `#` or `L`",
+            |value| match value {
+                '.' => Some(SeatingStatus::Floor),
+                '#' => Some(SeatingStatus::OccupiedSeat),
+                'L' => Some(SeatingStatus::EmptySeat),
+                _ => None,
             },
-            None => {
-                println!("Unknown seating combination at: {:?}", coord);
-                SeatingStatus::Unknown
-            }
-        }
+        )
     }
 
-    fn run_generation(seating_map: &mut HashMap<Coordinate, SeatingStatus>) -> usize {
-        let seating_changes = seating_map
-            .iter()
-            .filter_map(|(coord, seat_status)| {
-                let next_status = occupy_seat(&seating_map, coord);
-
-                if *seat_status == next_status {
-                    None
-                } else {
-                    Some((*coord, next_status))
-                }
-            })
-            .collect::<Vec<(Coordinate, SeatingStatus)>>();
-
-        seating_changes.iter().for_each(|(coord, status)| {
-            seating_map.entry(*coord).and_modify(|e| *e = *status);
-        });
-
-        seating_changes.len()
+    fn part1(seating_map: &SeatingMap) -> usize {
+        count_stable_occupied_seats(seating_map, occupy_seat_adjacent, "day11-part1")
     }
 
-    loop {
-        let seat_changes = run_generation(&mut seating_map);
-        println!("seat_changes: {}", seat_changes);
-        if seat_changes == 0 {
-            break;
-        }
+    fn part2(seating_map: &SeatingMap) -> usize {
+        count_stable_occupied_seats(seating_map, occupy_seat_visible, "day11-part2")
     }
+}
 
-    seating_map.iter().fold(0, |sum, (_, seat_status)| {
-        if *seat_status == SeatingStatus::OccupiedSeat {
-            sum + 1
-        } else {
-            sum
+/// Whether the seats stop changing under `occupy_seat` within `limit`
+/// generations, rather than flipping between two layouts forever.
+fn settles(
+    seating_map: &SeatingMap,
+    occupy_seat: fn(&SeatingMap, &Coordinate) -> SeatingStatus,
+    limit: usize,
+) -> bool {
+    let mut seating_map = seating_map.clone();
+    let mut two_back = None;
+    for _ in 0..limit {
+        let previous = seating_map.clone();
+        if run_generation(&mut seating_map, occupy_seat) == 0 {
+            return true;
         }
-    })
+        if two_back.as_ref() == Some(&seating_map) {
+            return false;
+        }
+        two_back = Some(previous);
+    }
+    false
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+/// How many random layouts the generator tries before giving up. Two in
+/// three settle even at the largest size, so running out means something is
+/// wrong rather than unlucky.
+const ATTEMPTS: usize = 100;
+
+impl Generator for Day11 {
+    /// A waiting area of `size` by `size` places, at most 100 by 100 like
+    /// the puzzle's 90 by 91, mostly empty seats, whose seats settle under
+    /// the rules of both parts.
+    ///
+    /// Random layouts keep changing forever more often the larger they are,
+    /// about one in three at the cap, so layouts are simulated until one
+    /// settles; each try takes time growing with the cube of the side.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.min(100);
+        let layout = |seat: &mut dyn FnMut(usize) -> bool| {
+            (0..size)
+                .map(|y| {
+                    let mut row = (0..size)
+                        .map(|_| if seat(y) { 'L' } else { '.' })
+                        .collect::<String>();
+                    row.push('\n');
+                    row
+                })
+                .collect::<String>()
+        };
+
+        for _ in 0..ATTEMPTS {
+            let contents = layout(&mut |_| rng.gen_bool(0.8));
+            let seating_map = Self::parse(&contents).unwrap();
+            let limit = 10 * size + 100;
+            if settles(&seating_map, occupy_seat_adjacent, limit)
+                && settles(&seating_map, occupy_seat_visible, limit)
+            {
+                return contents;
+            }
+        }
+        panic!("no layout that settles found in {} attempts", ATTEMPTS);
+    }
 }
@@ -1,11 +1,11 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::env;
-
-type Coordinate = (i32, i32);
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum Command {
+pub enum Command {
     North,
     South,
     East,
@@ -13,18 +13,23 @@ enum Command {
     TurnLeft,
     TurnRight,
     Forward,
-    Unknown,
 }
 
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: usize = 12;
 
-    let sequence = contents
-        .lines()
-        .map(|line| {
-            (
-                match &line[0..1] {
+    type Input = Vec<(Command, i32)>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(contents: &str) -> Result<Vec<(Command, i32)>, ParseError> {
+        contents
+            .lines()
+            .map(|line| {
+                let (action, text) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+                let command = match action {
                     "N" => Command::North,
                     "S" => Command::South,
                     "E" => Command::East,
@@ -32,119 +37,122 @@ fn solve_part1(inputfile: String) -> i32 {
                     "L" => Command::TurnLeft,
                     "R" => Command::TurnRight,
                     "F" => Command::Forward,
-                    _ => Command::North,
-                },
-                line[1..].parse::<i32>().unwrap(),
-            )
-        })
-        .collect::<Vec<(Command, i32)>>();
-
-    let (position, direction) =
-        sequence
-            .iter()
-            .fold(((0, 0), 0), |(position, direction), (command, value)| {
-                let mut next_position = position;
-                let mut next_direction = direction;
-                match command {
-                    Command::Forward => match next_direction {
-                        0 => next_position.0 += value,
-                        90 => next_position.1 += value,
-                        180 => next_position.0 -= value,
-                        270 => next_position.1 -= value,
-                        _ => {
-                            println!("Unknown direction: {}", next_direction);
-                            ()
-                        }
-                    },
-                    Command::TurnLeft => next_direction = (360 + next_direction - value) % 360,
-                    Command::TurnRight => next_direction = (next_direction + value) % 360,
-                    Command::North => next_position.1 -= value,
-                    Command::South => next_position.1 += value,
-                    Command::East => next_position.0 += value,
-                    Command::West => next_position.0 -= value,
-                    Command::Unknown => (),
+                    _ => {
+                        return Err(ParseError::at(
+                            contents,
+                            action,
+                            "expected one of `N`, `S`, `E`, `W`, `L`, `R` or `F`",
+                        ))
+                    }
                 };
+                let value: i32 = parse_at(contents, text)?;
+                if matches!(command, Command::TurnLeft | Command::TurnRight) && value % 90 != 0 {
+                    return Err(ParseError::at(
+                        contents,
+                        text,
+                        "expected a turn by a multiple of 90 degrees",
+                    ));
+                }
+                Ok((command, value))
+            })
+            .collect()
+    }
 
-                (next_position, next_direction)
-            });
-
-    println!("position: {:?}, direction: {:?}", position, direction);
-    position.0 + position.1
-}
+    fn part1(commands: &Vec<(Command, i32)>) -> i32 {
+        let (position, direction) =
+            commands
+                .iter()
+                .fold(((0, 0), 0), |(position, direction), (command, value)| {
+                    let mut next_position = position;
+                    let mut next_direction = direction;
+                    match command {
+                        Command::Forward => match next_direction {
+                            0 => next_position.0 += value,
+                            90 => next_position.1 += value,
+                            180 => next_position.0 -= value,
+                            270 => next_position.1 -= value,
+                            _ => unreachable!("turns are by multiples of 90 degrees"),
+                        },
+                        Command::TurnLeft => {
+                            next_direction = (next_direction - value).rem_euclid(360)
+                        }
+                        Command::TurnRight => {
+                            next_direction = (next_direction + value).rem_euclid(360)
+                        }
+                        Command::North => next_position.1 -= value,
+                        Command::South => next_position.1 += value,
+                        Command::East => next_position.0 += value,
+                        Command::West => next_position.0 -= value,
+                    };
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+                    (next_position, next_direction)
+                });
 
-    let sequence = contents
-        .lines()
-        .map(|line| {
-            (
-                match &line[0..1] {
-                    "N" => Command::North,
-                    "S" => Command::South,
-                    "E" => Command::East,
-                    "W" => Command::West,
-                    "L" => Command::TurnLeft,
-                    "R" => Command::TurnRight,
-                    "F" => Command::Forward,
-                    _ => Command::North,
-                },
-                line[1..].parse::<i32>().unwrap(),
-            )
-        })
-        .collect::<Vec<(Command, i32)>>();
+        debug!("position: {:?}, direction: {:?}", position, direction);
+        position.0.abs() + position.1.abs()
+    }
 
-    let (ship_position, waypoint_position) = sequence.iter().fold(
-        ((0, 0), (10, -1)),
-        |(ship_position, waypoint_position), (command, value)| {
-            let mut next_ship_position = ship_position;
-            let mut next_waypoint_position = waypoint_position;
-            match command {
-                Command::Forward => {
-                    next_ship_position.0 += value * next_waypoint_position.0;
-                    next_ship_position.1 += value * next_waypoint_position.1;
-                }
-                Command::TurnLeft => {
-                    next_waypoint_position = match (360 - value) % 360 {
-                        0 => (waypoint_position.0, waypoint_position.1),
-                        90 => (-waypoint_position.1, waypoint_position.0),
-                        180 => (-waypoint_position.0, -waypoint_position.1),
-                        270 => (waypoint_position.1, -waypoint_position.0),
-                        _ => (0, 0),
-                    };
-                }
-                Command::TurnRight => {
-                    next_waypoint_position = match value % 360 {
-                        0 => (waypoint_position.0, waypoint_position.1),
-                        90 => (-waypoint_position.1, waypoint_position.0),
-                        180 => (-waypoint_position.0, -waypoint_position.1),
-                        270 => (waypoint_position.1, -waypoint_position.0),
-                        _ => (0, 0),
-                    };
-                }
-                Command::North => next_waypoint_position.1 -= value,
-                Command::South => next_waypoint_position.1 += value,
-                Command::East => next_waypoint_position.0 += value,
-                Command::West => next_waypoint_position.0 -= value,
-                Command::Unknown => {
-                    println!("Unknown command: {:?}", (command, value));
-                    ()
-                }
-            };
+    fn part2(commands: &Vec<(Command, i32)>) -> i32 {
+        let (ship_position, waypoint_position) = commands.iter().fold(
+            ((0, 0), (10, -1)),
+            |(ship_position, waypoint_position), (command, value)| {
+                let mut next_ship_position = ship_position;
+                let mut next_waypoint_position = waypoint_position;
+                match command {
+                    Command::Forward => {
+                        next_ship_position.0 += value * next_waypoint_position.0;
+                        next_ship_position.1 += value * next_waypoint_position.1;
+                    }
+                    Command::TurnLeft => {
+                        next_waypoint_position = match (-value).rem_euclid(360) {
+                            0 => (waypoint_position.0, waypoint_position.1),
+                            90 => (-waypoint_position.1, waypoint_position.0),
+                            180 => (-waypoint_position.0, -waypoint_position.1),
+                            270 => (waypoint_position.1, -waypoint_position.0),
+                            _ => unreachable!("turns are by multiples of 90 degrees"),
+                        };
+                    }
+                    Command::TurnRight => {
+                        next_waypoint_position = match value.rem_euclid(360) {
+                            0 => (waypoint_position.0, waypoint_position.1),
+                            90 => (-waypoint_position.1, waypoint_position.0),
+                            180 => (-waypoint_position.0, -waypoint_position.1),
+                            270 => (waypoint_position.1, -waypoint_position.0),
+                            _ => unreachable!("turns are by multiples of 90 degrees"),
+                        };
+                    }
+                    Command::North => next_waypoint_position.1 -= value,
+                    Command::South => next_waypoint_position.1 += value,
+                    Command::East => next_waypoint_position.0 += value,
+                    Command::West => next_waypoint_position.0 -= value,
+                };
 
-            (next_ship_position, next_waypoint_position)
-        },
-    );
+                (next_ship_position, next_waypoint_position)
+            },
+        );
 
-    println!(
-        "ship_position: {:?}, waypoint {:?}",
-        ship_position, waypoint_position
-    );
-    ship_position.0.abs() + ship_position.1.abs()
+        debug!(
+            "ship_position: {:?}, waypoint {:?}",
+            ship_position, waypoint_position
+        );
+        ship_position.0.abs() + ship_position.1.abs()
+    }
 }
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+
+impl Generator for Day12 {
+    /// `size` navigation instructions, turning by multiples of 90 degrees.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        (0..size)
+            .map(|_| match rng.gen_range(0, 7) {
+                0 => format!("L{}\n", 90 * rng.gen_range(1, 4)),
+                1 => format!("R{}\n", 90 * rng.gen_range(1, 4)),
+                2 => format!("F{}\n", rng.gen_range(1, 101)),
+                action => format!(
+                    "{}{}\n",
+                    ["N", "S", "E", "W"][action - 3],
+                    rng.gen_range(1, 6)
+                ),
+            })
+            .collect()
+    }
 }
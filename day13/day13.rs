@@ -1,77 +1,130 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let timestamp = contents.lines().nth(0).unwrap().parse::<usize>().unwrap();
-
-    let (lowest_waiting_time, bus_id) = contents
-        .lines()
-        .nth(1)
-        .unwrap()
-        .split(',')
-        .filter_map(|bus_id| match bus_id {
-            "x" => None,
-            _ => Some(bus_id.parse::<usize>().unwrap()),
-        })
-        .fold((timestamp, 0), |(lowest_waiting_time, id), bus_id| {
-            let current_waiting_time = bus_id - (timestamp % bus_id);
-            if current_waiting_time < lowest_waiting_time {
-                (current_waiting_time, bus_id)
-            } else {
-                (lowest_waiting_time, id)
-            }
-        });
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, trace, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub struct Day13;
 
-    lowest_waiting_time * bus_id
+#[derive(Debug, Clone)]
+pub struct Notes {
+    pub timestamp: usize,
+    pub bus_ids: Vec<(usize, usize)>,
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let bus_ids = contents
-        .lines()
-        .nth(1)
-        .unwrap()
-        .split(',')
-        .enumerate()
-        .filter_map(|(offset, bus_id)| match bus_id {
-            "x" => None,
-            _ => Some((offset, bus_id.parse::<usize>().unwrap())),
-        })
-        .collect::<Vec<(usize, usize)>>();
-
-    println!("bus_ids: {:?}", bus_ids);
-    let mut timestamp = 0;
-
-    for num_buses in 0..bus_ids.len() {
-        let increment = bus_ids
-            .iter()
-            .take(num_buses)
-            .fold(1, |product, (_, bus_id)| product * bus_id);
-
-        loop {
-            if bus_ids
+impl Solution for Day13 {
+    const DAY: usize = 13;
+
+    type Input = Notes;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Notes, ParseError> {
+        let mut lines = contents.lines();
+        let missing = |message| ParseError::at(contents, &contents[contents.len()..], message);
+
+        let timestamp = lines
+            .next()
+            .ok_or_else(|| missing("expected the earliest timestamp"))?;
+        let timestamp = parse_at(contents, timestamp)?;
+
+        let bus_ids = lines
+            .next()
+            .ok_or_else(|| missing("expected the bus IDs"))?
+            .split(',')
+            .enumerate()
+            .filter_map(|(offset, bus_id)| match bus_id {
+                "x" => None,
+                _ => Some(parse_at(contents, bus_id).map(|bus_id| (offset, bus_id))),
+            })
+            .collect::<Result<Vec<(usize, usize)>, ParseError>>()?;
+
+        Ok(Notes { timestamp, bus_ids })
+    }
+
+    fn part1(notes: &Notes) -> usize {
+        let timestamp = notes.timestamp;
+
+        let (lowest_waiting_time, bus_id) =
+            notes
+                .bus_ids
+                .iter()
+                .fold((timestamp, 0), |(lowest_waiting_time, id), &(_, bus_id)| {
+                    let current_waiting_time = (bus_id - timestamp % bus_id) % bus_id;
+                    if current_waiting_time < lowest_waiting_time {
+                        (current_waiting_time, bus_id)
+                    } else {
+                        (lowest_waiting_time, id)
+                    }
+                });
+
+        lowest_waiting_time * bus_id
+    }
+
+    fn part2(notes: &Notes) -> usize {
+        let bus_ids = &notes.bus_ids;
+
+        debug!("bus_ids: {:?}", bus_ids);
+        let mut timestamp = 0;
+
+        for num_buses in 0..bus_ids.len() {
+            let increment = bus_ids
                 .iter()
-                .take(num_buses + 1)
-                .all(|(offset, bus_id)| (timestamp + *offset) % bus_id == 0)
-            {
-                println!("Increment is now: {}, timestamp: {}", increment, timestamp);
-                break;
+                .take(num_buses)
+                .fold(1, |product, (_, bus_id)| product * bus_id);
+
+            loop {
+                if bus_ids
+                    .iter()
+                    .take(num_buses + 1)
+                    .all(|(offset, bus_id)| (timestamp + *offset) % bus_id == 0)
+                {
+                    debug!("Increment is now: {}, timestamp: {}", increment, timestamp);
+                    break;
+                }
+                timestamp += increment;
+                trace!("multiple: {}, timestamp: {}", increment, timestamp);
             }
-            timestamp += increment;
-            println!("multiple: {}, timestamp: {}", increment, timestamp);
         }
+        timestamp
     }
-    timestamp
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day13 {
+    /// A schedule of `size` slots, at least one, with buses in as many of
+    /// them as fit.
+    ///
+    /// Bus IDs are distinct primes so that part 2 has a solution, and stop
+    /// before their product, which bounds the answer, reaches 2^50.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.max(1);
+        let mut primes = (7..1000)
+            .filter(|&n: &usize| (2..).take_while(|d| d * d <= n).all(|d| n % d != 0))
+            .collect::<Vec<usize>>();
+        primes.shuffle(rng);
+
+        let mut product = 1_usize;
+        let bus_ids = primes
+            .into_iter()
+            .take_while(|bus_id| {
+                product *= bus_id;
+                product < 1 << 50
+            })
+            .take(size)
+            .collect::<Vec<usize>>();
+
+        let mut slots = vec!["x".to_string(); size];
+        let mut offsets = (1..size).collect::<Vec<usize>>();
+        offsets.shuffle(rng);
+        for (offset, bus_id) in Some(0).into_iter().chain(offsets).zip(bus_ids) {
+            slots[offset] = bus_id.to_string();
+        }
+
+        format!(
+            "{}\n{}\n",
+            rng.gen_range(100_000, 10_000_000),
+            slots.join(",")
+        )
+    }
 }
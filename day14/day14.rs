@@ -1,45 +1,79 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use regex::Regex;
 use std::collections::HashMap;
-use std::env;
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let pattern =
-        Regex::new(r"(mask\s=\s(?P<mask>[X01]+)$)|(mem\[(?P<address>\d+)]\s=\s(?P<value>\d+)$)")
-            .unwrap();
-
-    let mut memory = HashMap::<usize, usize>::new();
-
-    let mut bitmask_and = 0xFFFFFFFFFF as usize;
-    let mut bitmask_or = 0x0 as usize;
-
-    let instructions = contents.lines().for_each(|line| {
-        let parsed = pattern.captures(line).unwrap();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Mask(String),
+    Write { address: usize, value: usize },
+}
 
-        match parsed.name("address") {
-            Some(_) => {
-                let address = parsed["address"].parse::<usize>().unwrap();
-                let mut value = parsed["value"].parse::<usize>().unwrap();
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: usize = 14;
+
+    type Input = Vec<Instruction>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<Instruction>, ParseError> {
+        let pattern = Regex::new(
+            r"(mask\s=\s(?P<mask>[X01]+)$)|(mem\[(?P<address>\d+)]\s=\s(?P<value>\d+)$)",
+        )
+        .unwrap();
+
+        contents
+            .lines()
+            .map(|line| {
+                let parsed = pattern.captures(line).ok_or_else(|| {
+                    ParseError::at(
+                        contents,
+                        line,
+                        "expected `mask = <mask>` or `mem[<address>] = <value>`",
+                    )
+                })?;
+
+                Ok(match parsed.name("address") {
+                    Some(_) => Instruction::Write {
+                        address: parse_at(contents, &parsed["address"])?,
+                        value: parse_at(contents, &parsed["value"])?,
+                    },
+                    None => Instruction::Mask(parsed["mask"].to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn part1(instructions: &Vec<Instruction>) -> usize {
+        let mut memory = HashMap::<usize, usize>::new();
+
+        let mut bitmask_and = 0xFF_FFFF_FFFF_usize;
+        let mut bitmask_or = 0x0_usize;
+
+        instructions.iter().for_each(|instruction| {
+            match instruction {
+                Instruction::Write { address, value } => {
+                    let mut value = *value;
+
+                    // apply bitmasks
+                    value &= bitmask_and;
+                    value |= bitmask_or;
 
-                // apply bitmasks
-                value &= bitmask_and;
-                value |= bitmask_or;
+                    memory
+                        .entry(*address)
+                        .and_modify(|e| *e = value)
+                        .or_insert(value);
+                }
+                Instruction::Mask(mask) => {
+                    bitmask_and = 0xFF_FFFF_FFFF_usize;
+                    bitmask_or = 0x0_usize;
 
-                memory
-                    .entry(address)
-                    .and_modify(|e| *e = value)
-                    .or_insert(value);
-            }
-            _ => {
-                bitmask_and = 0xFFFFFFFFFF as usize;
-                bitmask_or = 0x0 as usize;
-
-                parsed["mask"]
-                    .chars()
-                    .enumerate()
-                    .for_each(|(index, bits)| {
+                    mask.chars().enumerate().for_each(|(index, bits)| {
                         match bits {
                             'X' => {}
                             '1' => {
@@ -49,53 +83,38 @@ fn solve_part1(inputfile: String) -> usize {
                                 bitmask_and ^= 1 << (35 - index);
                             }
                             _ => {
-                                println!("Unknown bitpattern");
+                                debug!("Unknown bitpattern");
                             }
                         };
                     });
-            }
-        };
-    });
-
-    memory.values().fold(0, |sum, value| sum + value)
-}
-
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let pattern =
-        Regex::new(r"(mask\s=\s(?P<mask>[X01]+)$)|(mem\[(?P<address>\d+)]\s=\s(?P<value>\d+)$)")
-            .unwrap();
-
-    let mut memory = HashMap::<usize, usize>::new();
-    let mut bitmask_and = 0x0 as usize;
-    let mut bitmasks = Vec::<usize>::new();
-
-    let instructions = contents.lines().for_each(|line| {
-        let parsed = pattern.captures(line).unwrap();
-
-        match parsed.name("address") {
-            Some(_) => {
-                let address = parsed["address"].parse::<usize>().unwrap();
-                let value = parsed["value"].parse::<usize>().unwrap();
+                }
+            };
+        });
+
+        memory.values().sum()
+    }
+
+    fn part2(instructions: &Vec<Instruction>) -> usize {
+        let mut memory = HashMap::<usize, usize>::new();
+        let mut bitmask_and = 0x0_usize;
+        let mut bitmasks = Vec::<usize>::new();
+
+        instructions.iter().for_each(|instruction| {
+            match instruction {
+                Instruction::Write { address, value } => {
+                    bitmasks.iter().for_each(|bitmask| {
+                        memory
+                            .entry((address & bitmask_and) | bitmask)
+                            .and_modify(|e| *e = *value)
+                            .or_insert(*value);
+                    });
+                }
+                Instruction::Mask(mask) => {
+                    bitmasks.clear();
+                    bitmasks.push(0x0);
+                    bitmask_and = 0x0;
 
-                bitmasks.iter().for_each(|bitmask| {
-                    memory
-                        .entry((address & bitmask_and) | bitmask)
-                        .and_modify(|e| *e = value)
-                        .or_insert(value);
-                });
-            }
-            _ => {
-                bitmasks.clear();
-                bitmasks.push(0x0);
-                bitmask_and = 0x0;
-
-                parsed["mask"]
-                    .chars()
-                    .enumerate()
-                    .for_each(|(index, bits)| {
+                    mask.chars().enumerate().for_each(|(index, bits)| {
                         match bits {
                             'X' => {
                                 let mut bitmasks_upper = bitmasks
@@ -108,30 +127,54 @@ fn solve_part2(inputfile: String) -> usize {
                                 bitmasks = bitmasks_upper;
                             }
                             '1' => {
-                                bitmask_and |= (1 << (35 - index));
+                                bitmask_and |= 1 << (35 - index);
                                 bitmasks = bitmasks
                                     .iter()
                                     .map(|bitmask| *bitmask | 1 << (35 - index))
                                     .collect::<Vec<usize>>();
                             }
                             '0' => {
-                                bitmask_and |= (1 << (35 - index));
+                                bitmask_and |= 1 << (35 - index);
                             }
                             _ => {
-                                println!("Unknown bitpattern");
+                                debug!("Unknown bitpattern");
                             }
                         };
                     });
-            }
-        };
-    });
-    //println!("memory: {:#?}", memory);
+                }
+            };
+        });
 
-    memory.values().fold(0, |sum, value| sum + value)
+        memory.values().sum()
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day14 {
+    /// `size` lines of masks, each followed by a few writes.
+    ///
+    /// Masks have at most nine floating bits, as in the puzzle, since part 2
+    /// writes to every combination of them.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let mut lines = Vec::<String>::new();
+        while lines.len() < size {
+            let mut mask = (0..36)
+                .map(|_| if rng.gen() { '1' } else { '0' })
+                .collect::<Vec<char>>();
+            let floating_bits = rng.gen_range(0, 10);
+            for floating in rand::seq::index::sample(rng, 36, floating_bits).iter() {
+                mask[floating] = 'X';
+            }
+            lines.push(format!("mask = {}", mask.iter().collect::<String>()));
+
+            for _ in 0..rng.gen_range(1, 7) {
+                lines.push(format!(
+                    "mem[{}] = {}",
+                    rng.gen_range(0, 65536),
+                    rng.gen_range(0, 1_000_000_000)
+                ));
+            }
+        }
+        lines.truncate(size);
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
 }
@@ -1,7 +1,11 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{trace, ParseError, Solution};
+use itertools::Itertools;
+use rand::rngs::StdRng;
 use std::collections::HashMap;
-use std::env;
 
-fn solve_parts(starting_numbers: Vec<usize>, count: usize) -> usize {
+pub fn solve_parts(starting_numbers: &[usize], count: usize) -> usize {
     let mut memory = HashMap::<usize, (usize, usize)>::new();
 
     let mut last_spoken = 0;
@@ -14,16 +18,16 @@ fn solve_parts(starting_numbers: Vec<usize>, count: usize) -> usize {
         });
 
     for turn in starting_numbers.len()..count {
-        let mut update_entry = false;
-        match memory.get(&last_spoken) {
+        let update_entry = match memory.get(&last_spoken) {
             Some((previously_been_spoken, last_been_spoken)) => {
                 last_spoken = last_been_spoken - previously_been_spoken;
-                update_entry = true;
+                true
             }
             None => {
-                println!("Has not spoken {} before", last_spoken);
+                trace!("Has not spoken {} before", last_spoken);
+                false
             }
-        }
+        };
 
         if update_entry {
             memory
@@ -38,14 +42,43 @@ fn solve_parts(starting_numbers: Vec<usize>, count: usize) -> usize {
     last_spoken
 }
 
-fn main() {
-    println!("Example1: {}", solve_parts(vec![0, 3, 6], 2020));
-    println!("Example2: {}", solve_parts(vec![2, 1, 3], 2020));
-    println!("Example3: {}", solve_parts(vec![1, 2, 3], 2020));
-    println!("Example4: {}", solve_parts(vec![2, 3, 1], 2020));
-    println!("Example5: {}", solve_parts(vec![3, 2, 1], 2020));
-    println!("Example6: {}", solve_parts(vec![3, 1, 2], 2020));
-    println!("Part1: {}", solve_parts(vec![2, 1, 10, 11, 0, 6], 2020));
-    println!("Example1, part2: {}", solve_parts(vec![0, 3, 6], 30000000));
-    println!("Part2: {}", solve_parts(vec![2, 1, 10, 11, 0, 6], 30000000));
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: usize = 15;
+
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        let line = contents.trim();
+        if line.is_empty() {
+            return Err(ParseError::at(
+                contents,
+                line,
+                "expected comma-separated starting numbers",
+            ));
+        }
+        line.split(',')
+            .map(|number| parse_at(contents, number))
+            .collect()
+    }
+
+    fn part1(starting_numbers: &Vec<usize>) -> usize {
+        solve_parts(starting_numbers, 2020)
+    }
+
+    fn part2(starting_numbers: &Vec<usize>) -> usize {
+        solve_parts(starting_numbers, 30000000)
+    }
+}
+
+impl Generator for Day15 {
+    /// `size` distinct starting numbers, at least one, below `2 * size`.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.max(1);
+        let numbers = rand::seq::index::sample(rng, 2 * size, size);
+        numbers.iter().join(",") + "\n"
+    }
 }
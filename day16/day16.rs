@@ -1,181 +1,295 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, trace, ParseError, Solution};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use regex::Regex;
 use std::collections::HashMap;
-use std::env;
 
 type Range = (usize, usize);
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub ranges: (Range, Range),
+}
 
-    let rule_pattern =
-        Regex::new(r"(?P<rule_name>.+):\s(?P<range1_start>\d+)-(?P<range1_end>\d+)\sor\s(?P<range2_start>\d+)-(?P<range2_end>\d+)$").unwrap();
+impl Rule {
+    fn is_valid(&self, value: usize) -> bool {
+        let ((start1, end1), (start2, end2)) = self.ranges;
+        (value >= start1 && value <= end1) || (value >= start2 && value <= end2)
+    }
+}
 
-    let mut rules = HashMap::<Range, String>::new();
+#[derive(Debug, Clone)]
+pub struct Notes {
+    pub rules: Vec<Rule>,
+    pub my_ticket: Vec<usize>,
+    pub nearby_tickets: Vec<Vec<usize>>,
+}
 
-    let first_block = contents.split("\n\n").nth(0).unwrap();
-    let third_block = contents.split("\n\n").nth(2).unwrap();
+pub struct Day16;
 
-    first_block.lines().for_each(|line| {
-        let parsed = rule_pattern.captures(line).unwrap();
-        let rule_name = &parsed["rule_name"];
-        let range1 = (
-            parsed["range1_start"].parse::<usize>().unwrap(),
-            parsed["range1_end"].parse::<usize>().unwrap(),
-        );
-        let range2 = (
-            parsed["range2_start"].parse::<usize>().unwrap(),
-            parsed["range2_end"].parse::<usize>().unwrap(),
-        );
-        rules.entry(range1).or_insert(rule_name.to_string());
-        rules.entry(range2).or_insert(rule_name.to_string());
-    });
-
-    let mut ticket_scanning_error_rate = 0;
-    third_block
-        .lines()
-        .skip(1)
-        .enumerate()
-        .for_each(|(ticket_index, line)| {
-            println!("ticket: {}", ticket_index);
-            line.split(',').for_each(|field| {
-                let value = field.parse::<usize>().unwrap();
-                let is_valid = rules
-                    .keys()
-                    .any(|&(start, end)| value >= start && value <= end);
-                if !is_valid {
-                    println!("    {}", value);
-                    ticket_scanning_error_rate += value;
-                }
-            });
-        });
+impl Solution for Day16 {
+    const DAY: usize = 16;
 
-    ticket_scanning_error_rate
-}
+    type Input = Notes;
+    type Part1 = usize;
+    type Part2 = usize;
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+    fn parse(contents: &str) -> Result<Notes, ParseError> {
+        let rule_pattern =
+            Regex::new(r"(?P<rule_name>.+):\s(?P<range1_start>\d+)-(?P<range1_end>\d+)\sor\s(?P<range2_start>\d+)-(?P<range2_end>\d+)$").unwrap();
 
-    let rule_pattern =
-        Regex::new(r"(?P<rule_name>.+):\s(?P<range1_start>\d+)-(?P<range1_end>\d+)\sor\s(?P<range2_start>\d+)-(?P<range2_end>\d+)$").unwrap();
+        let missing = |message| ParseError::at(contents, &contents[contents.len()..], message);
+        let mut blocks = contents.split("\n\n");
+        let first_block = blocks.next().unwrap();
+        let second_block = blocks
+            .next()
+            .ok_or_else(|| missing("expected `your ticket:`"))?;
+        let third_block = blocks
+            .next()
+            .ok_or_else(|| missing("expected `nearby tickets:`"))?;
 
-    let mut rules = HashMap::<(Range, Range), String>::new();
+        let rules = first_block
+            .lines()
+            .map(|line| {
+                let parsed = rule_pattern.captures(line).ok_or_else(|| {
+                    ParseError::at(contents, line, "expected `<field>: <a>-<b> or <c>-<d>`")
+                })?;
+                let range1 = (
+                    parse_at(contents, &parsed["range1_start"])?,
+                    parse_at(contents, &parsed["range1_end"])?,
+                );
+                let range2 = (
+                    parse_at(contents, &parsed["range2_start"])?,
+                    parse_at(contents, &parsed["range2_end"])?,
+                );
+                Ok(Rule {
+                    name: parsed["rule_name"].to_string(),
+                    ranges: (range1, range2),
+                })
+            })
+            .collect::<Result<Vec<Rule>, ParseError>>()?;
 
-    let first_block = contents.split("\n\n").nth(0).unwrap();
-    let second_block = contents.split("\n\n").nth(1).unwrap();
-    let third_block = contents.split("\n\n").nth(2).unwrap();
+        let parse_ticket = |line: &str| {
+            line.split(',')
+                .map(|field| parse_at(contents, field))
+                .collect::<Result<Vec<usize>, ParseError>>()
+        };
 
-    first_block.lines().for_each(|line| {
-        let parsed = rule_pattern.captures(line).unwrap();
-        let rule_name = &parsed["rule_name"];
-        let range1 = (
-            parsed["range1_start"].parse::<usize>().unwrap(),
-            parsed["range1_end"].parse::<usize>().unwrap(),
-        );
-        let range2 = (
-            parsed["range2_start"].parse::<usize>().unwrap(),
-            parsed["range2_end"].parse::<usize>().unwrap(),
-        );
-        rules
-            .entry((range1, range2))
-            .or_insert(rule_name.to_string());
-    });
-
-    let my_ticket = second_block
-        .lines()
-        .skip(1)
-        .map(|line| {
-            line.split(',').map(|field| {
-                println!("field: {:?}", field);
-                field.parse::<usize>().unwrap()
-            })
+        let my_ticket = second_block
+            .lines()
+            .skip(1)
+            .map(parse_ticket)
+            .next()
+            .ok_or_else(|| ParseError::at(contents, second_block, "expected your ticket"))??;
+        let nearby_tickets = third_block
+            .lines()
+            .skip(1)
+            .map(parse_ticket)
+            .collect::<Result<Vec<Vec<usize>>, ParseError>>()?;
+
+        Ok(Notes {
+            rules,
+            my_ticket,
+            nearby_tickets,
         })
-        .next()
-        .unwrap()
-        .collect::<Vec<usize>>();
-
-    let mut ticket_fields = Vec::<Vec<usize>>::new();
-    third_block
-        .lines()
-        .skip(1)
-        .enumerate()
-        .for_each(|(ticket_index, line)| {
-            let num_fields = line.split(',').count();
-            let fields = line
-                .split(',')
-                .filter_map(|field| {
-                    let value = field.parse::<usize>().unwrap();
-                    let is_valid = rules.keys().any(|&((start1, end1), (start2, end2))| {
-                        (value >= start1 && value <= end1) || (value >= start2 && value <= end2)
-                    });
-                    if is_valid {
-                        Some(value)
-                    } else {
-                        None
+    }
+
+    fn part1(notes: &Notes) -> usize {
+        let mut ticket_scanning_error_rate = 0;
+        notes
+            .nearby_tickets
+            .iter()
+            .enumerate()
+            .for_each(|(ticket_index, ticket)| {
+                trace!("ticket: {}", ticket_index);
+                ticket.iter().for_each(|&value| {
+                    let is_valid = notes.rules.iter().any(|rule| rule.is_valid(value));
+                    if !is_valid {
+                        trace!("    {}", value);
+                        ticket_scanning_error_rate += value;
                     }
-                })
-                .collect::<Vec<usize>>();
+                });
+            });
+
+        ticket_scanning_error_rate
+    }
+
+    fn part2(notes: &Notes) -> usize {
+        let rules = &notes.rules;
 
-            if num_fields == fields.len() {
-                fields.iter().enumerate().for_each(|(index, field)| {
+        let mut ticket_fields = Vec::<Vec<usize>>::new();
+        notes
+            .nearby_tickets
+            .iter()
+            .filter(|ticket| {
+                ticket
+                    .iter()
+                    .all(|&value| rules.iter().any(|rule| rule.is_valid(value)))
+            })
+            .for_each(|ticket| {
+                ticket.iter().enumerate().for_each(|(index, field)| {
                     match ticket_fields.get_mut(index) {
                         Some(tickets) => tickets.push(*field),
                         None => ticket_fields.push(vec![*field]),
                     };
                 });
-            } else {
-            }
-        });
+            });
 
-    let mut rules_with_fieldindex = HashMap::<String, usize>::new();
-    loop {
-        ticket_fields
-            .iter()
-            .enumerate()
-            .for_each(|(ticket_field_index, values)| {
-                let matching_rules = rules
-                    .iter()
-                    .filter_map(|(((start1, end1), (start2, end2)), rule_name)| {
-                        if rules_with_fieldindex.contains_key(rule_name) {
-                            None
-                        } else {
-                            if values.iter().all(|value| {
-                                (value >= start1 && value <= end1)
-                                    || (value >= start2 && value <= end2)
-                            }) {
-                                Some(rule_name.clone())
+        let mut rules_with_fieldindex = HashMap::<String, usize>::new();
+        loop {
+            ticket_fields
+                .iter()
+                .enumerate()
+                .for_each(|(ticket_field_index, values)| {
+                    let matching_rules = rules
+                        .iter()
+                        .filter_map(|rule| {
+                            if !rules_with_fieldindex.contains_key(&rule.name)
+                                && values.iter().all(|&value| rule.is_valid(value))
+                            {
+                                Some(rule.name.clone())
                             } else {
                                 None
                             }
-                        }
-                    })
-                    .collect::<Vec<String>>();
-
-                if matching_rules.len() == 1 {
-                    rules_with_fieldindex
-                        .entry(matching_rules[0].to_string())
-                        .or_insert(ticket_field_index);
-                }
-            });
-        if rules_with_fieldindex.len() == ticket_fields.len() {
-            println!("determined rules: {:?}", rules_with_fieldindex);
-            break;
+                        })
+                        .collect::<Vec<String>>();
+
+                    if matching_rules.len() == 1 {
+                        rules_with_fieldindex
+                            .entry(matching_rules[0].to_string())
+                            .or_insert(ticket_field_index);
+                    }
+                });
+            if rules_with_fieldindex.len() == ticket_fields.len() {
+                debug!("determined rules: {:?}", rules_with_fieldindex);
+                break;
+            }
         }
+
+        rules_with_fieldindex
+            .iter()
+            .fold(1, |product, (rule_name, index)| {
+                if rule_name.starts_with("departure") {
+                    product * notes.my_ticket[*index]
+                } else {
+                    product
+                }
+            })
     }
+}
 
-    rules_with_fieldindex
-        .iter()
-        .fold(1, |product, (rule_name, index)| {
-            if rule_name.starts_with("departure") {
-                product * my_ticket[*index]
+impl Generator for Day16 {
+    /// Twenty fields and `size` nearby tickets, at least one, a quarter of
+    /// them with an invalid value.
+    ///
+    /// Each rule leaves a gap between its ranges, nested so that the values
+    /// at the gaps' lower edges rank the rules: the value for a field's rank
+    /// is invalid for every rule ranked below it. One ticket carries those
+    /// values, which is enough for part 2 to resolve every field one at a
+    /// time.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const FIELDS: [&str; 20] = [
+            "departure location",
+            "departure station",
+            "departure platform",
+            "departure track",
+            "departure date",
+            "departure time",
+            "arrival location",
+            "arrival station",
+            "arrival platform",
+            "arrival track",
+            "class",
+            "duration",
+            "price",
+            "route",
+            "row",
+            "seat",
+            "train",
+            "type",
+            "wagon",
+            "zone",
+        ];
+
+        let mut ranks = (0..FIELDS.len()).collect::<Vec<usize>>();
+        ranks.shuffle(rng);
+        let gaps = ranks
+            .iter()
+            .map(|rank| {
+                (
+                    100 + 20 * rank + rng.gen_range(0, 10),
+                    900 - 20 * rank - rng.gen_range(0, 10),
+                )
+            })
+            .collect::<Vec<(usize, usize)>>();
+        let rules = FIELDS
+            .iter()
+            .zip(&gaps)
+            .map(|(field, (below, above))| {
+                format!(
+                    "{}: {}-{} or {}-{}\n",
+                    field,
+                    rng.gen_range(25, 51),
+                    below,
+                    above,
+                    rng.gen_range(950, 976)
+                )
+            })
+            .collect::<String>();
+
+        // Column `index` holds the field `columns[index]`
+        let mut columns = (0..FIELDS.len()).collect::<Vec<usize>>();
+        columns.shuffle(rng);
+        let valid_everywhere = |rng: &mut StdRng| {
+            if rng.gen() {
+                rng.gen_range(50, 100)
             } else {
-                product
+                rng.gen_range(900, 950)
             }
-        })
-}
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+        };
+        let ticket = |rng: &mut StdRng| {
+            (0..FIELDS.len())
+                .map(|_| valid_everywhere(rng))
+                .collect::<Vec<usize>>()
+        };
+
+        let mut nearby_tickets = (1..size.max(1))
+            .map(|_| {
+                let mut values = ticket(rng);
+                if rng.gen_bool(0.25) {
+                    values[rng.gen_range(0, FIELDS.len())] = if rng.gen() {
+                        rng.gen_range(0, 25)
+                    } else {
+                        rng.gen_range(976, 1000)
+                    };
+                }
+                values
+            })
+            .collect::<Vec<Vec<usize>>>();
+        nearby_tickets.push(
+            columns
+                .iter()
+                .map(|&field| match ranks[field] {
+                    0 => valid_everywhere(rng),
+                    _ => gaps[field].0,
+                })
+                .collect(),
+        );
+        nearby_tickets.shuffle(rng);
+
+        format!(
+            "{}\nyour ticket:\n{}\n\nnearby tickets:\n{}",
+            rules,
+            ticket(rng).iter().join(","),
+            nearby_tickets
+                .iter()
+                .map(|values| values.iter().join(",") + "\n")
+                .collect::<String>()
+        )
+    }
 }
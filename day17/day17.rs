@@ -1,140 +1,281 @@
-use itertools::Itertools;
+use crate::generate::Generator;
+use crate::render::{Animation, Canvas, WHITE};
+use crate::{debug, info, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    type Coordinate = (i32, i32, i32);
-
-    fn get_relevant_cubes(cubes: &HashMap<Coordinate, bool>) -> Vec<(Coordinate, bool, usize)> {
-        let directions_to_check = [
-            (0, 0, 0),
-            (0, 0, 1),
-            (0, 0, -1),
-            (0, 1, 0),
-            (0, 1, 1),
-            (0, 1, -1),
-            (0, -1, 0),
-            (0, -1, 1),
-            (0, -1, -1),
-            (1, 0, 0),
-            (1, 0, 1),
-            (1, 0, -1),
-            (1, 1, 0),
-            (1, 1, 1),
-            (1, 1, -1),
-            (1, -1, 0),
-            (1, -1, 1),
-            (1, -1, -1),
-            (-1, 0, 0),
-            (-1, 0, 1),
-            (-1, 0, -1),
-            (-1, 1, 0),
-            (-1, 1, 1),
-            (-1, 1, -1),
-            (-1, -1, 0),
-            (-1, -1, 1),
-            (-1, -1, -1),
-        ];
-        let mut visited = Vec::<Coordinate>::new();
-        let mut relevant_cubes = Vec::<(Coordinate, bool, usize)>::new();
-
-        cubes.iter().for_each(|(coord, active_cube)| {
-            directions_to_check.iter().for_each(|direction| {
-                let check = (
-                    coord.0 + direction.0,
-                    coord.1 + direction.1,
-                    coord.2 + direction.2,
-                );
-
-                let active_adjacent =
-                    directions_to_check
-                        .iter()
-                        .fold(0, |sum, secondary_direction| {
-                            if secondary_direction != &(0, 0, 0) {
-                                let secondary_check = (
-                                    check.0 + secondary_direction.0,
-                                    check.1 + secondary_direction.1,
-                                    check.2 + secondary_direction.2,
-                                );
-
-                                sum + match cubes.get(&secondary_check) {
-                                    Some(active) => {
-                                        if *active {
-                                            1
-                                        } else {
-                                            0
-                                        }
-                                    }
-                                    None => 0,
-                                }
-                            } else {
-                                sum
-                            }
-                        });
-
-                match cubes.get(&check) {
-                    Some(active) => {
-                        let cube = (check, *active, active_adjacent);
-                        if !relevant_cubes.contains(&cube) {
-                            relevant_cubes.push(cube);
+
+type Coordinate3 = (i32, i32, i32);
+type Coordinate4 = (i32, i32, i32, i32);
+
+pub struct Day17;
+
+fn get_relevant_cubes_3d(cubes: &HashMap<Coordinate3, bool>) -> Vec<(Coordinate3, bool, usize)> {
+    let directions_to_check = [
+        (0, 0, 0),
+        (0, 0, 1),
+        (0, 0, -1),
+        (0, 1, 0),
+        (0, 1, 1),
+        (0, 1, -1),
+        (0, -1, 0),
+        (0, -1, 1),
+        (0, -1, -1),
+        (1, 0, 0),
+        (1, 0, 1),
+        (1, 0, -1),
+        (1, 1, 0),
+        (1, 1, 1),
+        (1, 1, -1),
+        (1, -1, 0),
+        (1, -1, 1),
+        (1, -1, -1),
+        (-1, 0, 0),
+        (-1, 0, 1),
+        (-1, 0, -1),
+        (-1, 1, 0),
+        (-1, 1, 1),
+        (-1, 1, -1),
+        (-1, -1, 0),
+        (-1, -1, 1),
+        (-1, -1, -1),
+    ];
+    let mut relevant_cubes = Vec::<(Coordinate3, bool, usize)>::new();
+
+    cubes.keys().for_each(|coord| {
+        directions_to_check.iter().for_each(|direction| {
+            let check = (
+                coord.0 + direction.0,
+                coord.1 + direction.1,
+                coord.2 + direction.2,
+            );
+
+            let active_adjacent = directions_to_check
+                .iter()
+                .fold(0, |sum, secondary_direction| {
+                    if secondary_direction != &(0, 0, 0) {
+                        let secondary_check = (
+                            check.0 + secondary_direction.0,
+                            check.1 + secondary_direction.1,
+                            check.2 + secondary_direction.2,
+                        );
+
+                        sum + match cubes.get(&secondary_check) {
+                            Some(true) => 1,
+                            _ => 0,
                         }
+                    } else {
+                        sum
                     }
-                    None => {
-                        let cube = (check, false, active_adjacent);
-                        visited.push(check);
-                        if !relevant_cubes.contains(&cube) {
-                            relevant_cubes.push(cube);
-                        }
+                });
+
+            match cubes.get(&check) {
+                Some(active) => {
+                    let cube = (check, *active, active_adjacent);
+                    if !relevant_cubes.contains(&cube) {
+                        relevant_cubes.push(cube);
                     }
-                };
-            });
+                }
+                None => {
+                    let cube = (check, false, active_adjacent);
+                    if !relevant_cubes.contains(&cube) {
+                        relevant_cubes.push(cube);
+                    }
+                }
+            };
         });
+    });
 
-        relevant_cubes
-    }
+    relevant_cubes
+}
 
-    fn print_cubes(cubes: &HashMap<Coordinate, bool>) {
-        for z in -2..3 {
-            println!("z={}", z);
-            for y in -5..10 {
-                for x in -5..10 {
-                    let check = (x, y, z);
-                    match cubes.get(&check) {
-                        Some(active) => {
-                            if *active {
-                                print!("#");
-                            } else {
-                                print!(".");
-                            }
-                        }
-                        None => print!("."),
-                    };
-                }
-                println!();
+/// Draws each layer of the pocket dimension next to the one below it, over
+/// the bounds of the active cubes, which are shown as dark squares.
+fn draw_cubes(cubes: &HashMap<Coordinate3, bool>) -> Canvas {
+    let active = cubes
+        .iter()
+        .filter(|(_, &active)| active)
+        .map(|(&coord, _)| coord)
+        .collect::<Vec<_>>();
+    let min = |axis: fn(&Coordinate3) -> i32| active.iter().map(axis).min().unwrap_or(0);
+    let max = |axis: fn(&Coordinate3) -> i32| active.iter().map(axis).max().unwrap_or(0);
+    let (min_x, min_y, min_z) = (min(|c| c.0), min(|c| c.1), min(|c| c.2));
+    let (width, height) = (max(|c| c.0) - min_x + 1, max(|c| c.1) - min_y + 1);
+    let layers = max(|c| c.2) - min_z + 1;
+
+    // A column of background between the layers
+    let mut canvas = Canvas::new(
+        ((width + 1) * layers + 1) as u32,
+        (height + 2) as u32,
+        8,
+        WHITE,
+    );
+    for z in 0..layers {
+        for y in 0..height {
+            for x in 0..width {
+                let color = if active.contains(&(x + min_x, y + min_y, z + min_z)) {
+                    (0x32, 0x17, 0x04)
+                } else {
+                    (0xE0, 0xE0, 0xE0)
+                };
+                canvas.put((1 + z * (width + 1) + x, 1 + y), color);
             }
         }
     }
+    canvas
+}
 
-    let mut cubes = HashMap::<Coordinate, bool>::new();
+fn get_relevant_cubes_4d(cubes: &HashMap<Coordinate4, bool>) -> Vec<(Coordinate4, bool, usize)> {
+    let directions_to_check = [
+        (0, 0, 0, 0),
+        (0, 0, 1, 0),
+        (0, 0, -1, 0),
+        (0, 1, 0, 0),
+        (0, 1, 1, 0),
+        (0, 1, -1, 0),
+        (0, -1, 0, 0),
+        (0, -1, 1, 0),
+        (0, -1, -1, 0),
+        (1, 0, 0, 0),
+        (1, 0, 1, 0),
+        (1, 0, -1, 0),
+        (1, 1, 0, 0),
+        (1, 1, 1, 0),
+        (1, 1, -1, 0),
+        (1, -1, 0, 0),
+        (1, -1, 1, 0),
+        (1, -1, -1, 0),
+        (-1, 0, 0, 0),
+        (-1, 0, 1, 0),
+        (-1, 0, -1, 0),
+        (-1, 1, 0, 0),
+        (-1, 1, 1, 0),
+        (-1, 1, -1, 0),
+        (-1, -1, 0, 0),
+        (-1, -1, 1, 0),
+        (-1, -1, -1, 0),
+        (0, 0, 0, 1),
+        (0, 0, 1, 1),
+        (0, 0, -1, 1),
+        (0, 1, 0, 1),
+        (0, 1, 1, 1),
+        (0, 1, -1, 1),
+        (0, -1, 0, 1),
+        (0, -1, 1, 1),
+        (0, -1, -1, 1),
+        (1, 0, 0, 1),
+        (1, 0, 1, 1),
+        (1, 0, -1, 1),
+        (1, 1, 0, 1),
+        (1, 1, 1, 1),
+        (1, 1, -1, 1),
+        (1, -1, 0, 1),
+        (1, -1, 1, 1),
+        (1, -1, -1, 1),
+        (-1, 0, 0, 1),
+        (-1, 0, 1, 1),
+        (-1, 0, -1, 1),
+        (-1, 1, 0, 1),
+        (-1, 1, 1, 1),
+        (-1, 1, -1, 1),
+        (-1, -1, 0, 1),
+        (-1, -1, 1, 1),
+        (-1, -1, -1, 1),
+        (0, 0, 0, -1),
+        (0, 0, 1, -1),
+        (0, 0, -1, -1),
+        (0, 1, 0, -1),
+        (0, 1, 1, -1),
+        (0, 1, -1, -1),
+        (0, -1, 0, -1),
+        (0, -1, 1, -1),
+        (0, -1, -1, -1),
+        (1, 0, 0, -1),
+        (1, 0, 1, -1),
+        (1, 0, -1, -1),
+        (1, 1, 0, -1),
+        (1, 1, 1, -1),
+        (1, 1, -1, -1),
+        (1, -1, 0, -1),
+        (1, -1, 1, -1),
+        (1, -1, -1, -1),
+        (-1, 0, 0, -1),
+        (-1, 0, 1, -1),
+        (-1, 0, -1, -1),
+        (-1, 1, 0, -1),
+        (-1, 1, 1, -1),
+        (-1, 1, -1, -1),
+        (-1, -1, 0, -1),
+        (-1, -1, 1, -1),
+        (-1, -1, -1, -1),
+    ];
 
-    contents.lines().enumerate().for_each(|(y, row)| {
-        row.chars().enumerate().for_each(|(x, value)| {
-            let coord = (x as i32, y as i32, 0 as i32);
-            if value == '#' {
-                cubes.entry(coord).or_insert(true);
-            }
+    let mut relevant_cubes = Vec::<(Coordinate4, bool, usize)>::new();
+
+    cubes.keys().for_each(|coord| {
+        directions_to_check.iter().for_each(|direction| {
+            let check = (
+                coord.0 + direction.0,
+                coord.1 + direction.1,
+                coord.2 + direction.2,
+                coord.3 + direction.3,
+            );
+
+            let active_adjacent = directions_to_check
+                .iter()
+                .fold(0, |sum, secondary_direction| {
+                    if secondary_direction != &(0, 0, 0, 0) {
+                        let secondary_check = (
+                            check.0 + secondary_direction.0,
+                            check.1 + secondary_direction.1,
+                            check.2 + secondary_direction.2,
+                            check.3 + secondary_direction.3,
+                        );
+
+                        sum + match cubes.get(&secondary_check) {
+                            Some(true) => 1,
+                            _ => 0,
+                        }
+                    } else {
+                        sum
+                    }
+                });
+
+            match cubes.get(&check) {
+                Some(active) => {
+                    let cube = (check, *active, active_adjacent);
+                    if !relevant_cubes.contains(&cube) {
+                        relevant_cubes.push(cube);
+                    }
+                }
+                None => {
+                    let cube = (check, false, active_adjacent);
+                    if !relevant_cubes.contains(&cube) {
+                        relevant_cubes.push(cube);
+                    }
+                }
+            };
         });
     });
 
-    println!("initial cubes: {:#?}", cubes);
-    //print_cubes(&cubes);
-    for generation in 0..6 {
-        let mut changes = Vec::<(Coordinate, bool)>::new();
+    relevant_cubes
+}
+
+type RelevantCubes<C> = fn(&HashMap<C, bool>) -> Vec<(C, bool, usize)>;
+
+/// Runs the six cycles, calling `observe` before each and after the last.
+fn run_cycles<C>(
+    cubes: &mut HashMap<C, bool>,
+    get_relevant_cubes: RelevantCubes<C>,
+    mut observe: impl FnMut(&HashMap<C, bool>),
+) where
+    C: std::hash::Hash + Eq + Copy,
+{
+    for _ in 0..6 {
+        observe(cubes);
+        let mut changes = Vec::<(C, bool)>::new();
 
-        get_relevant_cubes(&cubes)
+        get_relevant_cubes(cubes)
             .iter()
             .for_each(|(coord, active, active_adjacent)| {
                 if *active && !(*active_adjacent == 2 || *active_adjacent == 3) {
@@ -144,211 +285,86 @@ fn solve_part1(inputfile: String) -> i32 {
                 }
             });
 
-        //println!("changes: {:?}", changes);
         changes.iter().for_each(|(coord, status)| {
             cubes
                 .entry(*coord)
                 .and_modify(|e| *e = *status)
                 .or_insert(*status);
         });
-        //print_cubes(&cubes);
     }
-
-    cubes
-        .iter()
-        .fold(0, |sum, (_, active)| if *active { sum + 1 } else { sum })
+    observe(cubes);
 }
 
-fn solve_part2(inputfile: String) -> i32 {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    type Coordinate = (i32, i32, i32, i32);
-
-    fn get_relevant_cubes(cubes: &HashMap<Coordinate, bool>) -> Vec<(Coordinate, bool, usize)> {
-        let directions_to_check = [
-            (0, 0, 0, 0),
-            (0, 0, 1, 0),
-            (0, 0, -1, 0),
-            (0, 1, 0, 0),
-            (0, 1, 1, 0),
-            (0, 1, -1, 0),
-            (0, -1, 0, 0),
-            (0, -1, 1, 0),
-            (0, -1, -1, 0),
-            (1, 0, 0, 0),
-            (1, 0, 1, 0),
-            (1, 0, -1, 0),
-            (1, 1, 0, 0),
-            (1, 1, 1, 0),
-            (1, 1, -1, 0),
-            (1, -1, 0, 0),
-            (1, -1, 1, 0),
-            (1, -1, -1, 0),
-            (-1, 0, 0, 0),
-            (-1, 0, 1, 0),
-            (-1, 0, -1, 0),
-            (-1, 1, 0, 0),
-            (-1, 1, 1, 0),
-            (-1, 1, -1, 0),
-            (-1, -1, 0, 0),
-            (-1, -1, 1, 0),
-            (-1, -1, -1, 0),
-            (0, 0, 0, 1),
-            (0, 0, 1, 1),
-            (0, 0, -1, 1),
-            (0, 1, 0, 1),
-            (0, 1, 1, 1),
-            (0, 1, -1, 1),
-            (0, -1, 0, 1),
-            (0, -1, 1, 1),
-            (0, -1, -1, 1),
-            (1, 0, 0, 1),
-            (1, 0, 1, 1),
-            (1, 0, -1, 1),
-            (1, 1, 0, 1),
-            (1, 1, 1, 1),
-            (1, 1, -1, 1),
-            (1, -1, 0, 1),
-            (1, -1, 1, 1),
-            (1, -1, -1, 1),
-            (-1, 0, 0, 1),
-            (-1, 0, 1, 1),
-            (-1, 0, -1, 1),
-            (-1, 1, 0, 1),
-            (-1, 1, 1, 1),
-            (-1, 1, -1, 1),
-            (-1, -1, 0, 1),
-            (-1, -1, 1, 1),
-            (-1, -1, -1, 1),
-            (0, 0, 0, -1),
-            (0, 0, 1, -1),
-            (0, 0, -1, -1),
-            (0, 1, 0, -1),
-            (0, 1, 1, -1),
-            (0, 1, -1, -1),
-            (0, -1, 0, -1),
-            (0, -1, 1, -1),
-            (0, -1, -1, -1),
-            (1, 0, 0, -1),
-            (1, 0, 1, -1),
-            (1, 0, -1, -1),
-            (1, 1, 0, -1),
-            (1, 1, 1, -1),
-            (1, 1, -1, -1),
-            (1, -1, 0, -1),
-            (1, -1, 1, -1),
-            (1, -1, -1, -1),
-            (-1, 0, 0, -1),
-            (-1, 0, 1, -1),
-            (-1, 0, -1, -1),
-            (-1, 1, 0, -1),
-            (-1, 1, 1, -1),
-            (-1, 1, -1, -1),
-            (-1, -1, 0, -1),
-            (-1, -1, 1, -1),
-            (-1, -1, -1, -1),
-        ];
-
-        let mut visited = Vec::<Coordinate>::new();
-        let mut relevant_cubes = Vec::<(Coordinate, bool, usize)>::new();
-
-        cubes.iter().for_each(|(coord, active_cube)| {
-            directions_to_check.iter().for_each(|direction| {
-                let check = (
-                    coord.0 + direction.0,
-                    coord.1 + direction.1,
-                    coord.2 + direction.2,
-                    coord.3 + direction.3,
-                );
-
-                let active_adjacent =
-                    directions_to_check
-                        .iter()
-                        .fold(0, |sum, secondary_direction| {
-                            if secondary_direction != &(0, 0, 0, 0) {
-                                let secondary_check = (
-                                    check.0 + secondary_direction.0,
-                                    check.1 + secondary_direction.1,
-                                    check.2 + secondary_direction.2,
-                                    check.3 + secondary_direction.3,
-                                );
-
-                                sum + match cubes.get(&secondary_check) {
-                                    Some(active) => {
-                                        if *active {
-                                            1
-                                        } else {
-                                            0
-                                        }
-                                    }
-                                    None => 0,
-                                }
-                            } else {
-                                sum
-                            }
-                        });
-
-                match cubes.get(&check) {
-                    Some(active) => {
-                        let cube = (check, *active, active_adjacent);
-                        if !relevant_cubes.contains(&cube) {
-                            relevant_cubes.push(cube);
-                        }
-                    }
-                    None => {
-                        let cube = (check, false, active_adjacent);
-                        visited.push(check);
-                        if !relevant_cubes.contains(&cube) {
-                            relevant_cubes.push(cube);
-                        }
-                    }
-                };
-            });
-        });
+impl Solution for Day17 {
+    const DAY: usize = 17;
 
-        relevant_cubes
-    }
+    type Input = Vec<(i32, i32)>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    let mut cubes = HashMap::<Coordinate, bool>::new();
+    fn parse(contents: &str) -> Result<Vec<(i32, i32)>, ParseError> {
+        let mut active_cubes = Vec::<(i32, i32)>::new();
 
-    contents.lines().enumerate().for_each(|(y, row)| {
-        row.chars().enumerate().for_each(|(x, value)| {
-            let coord = (x as i32, y as i32, 0 as i32, 0 as i32);
-            if value == '#' {
-                cubes.entry(coord).or_insert(true);
+        for (y, row) in contents.lines().enumerate() {
+            for (x, (index, value)) in row.char_indices().enumerate() {
+                match value {
+                    '#' => active_cubes.push((x as i32, y as i32)),
+                    '.' => (),
+                    _ => {
+                        let cube = &row[index..index + value.len_utf8()];
+                        return Err(ParseError::at(contents, cube, "expected `.` or `#`"));
+                    }
+                }
             }
-        });
-    });
+        }
 
-    for generation in 0..6 {
-        let mut changes = Vec::<(Coordinate, bool)>::new();
+        Ok(active_cubes)
+    }
 
-        get_relevant_cubes(&cubes)
+    fn part1(active_cubes: &Vec<(i32, i32)>) -> usize {
+        let mut cubes = active_cubes
             .iter()
-            .for_each(|(coord, active, active_adjacent)| {
-                if *active && !(*active_adjacent == 2 || *active_adjacent == 3) {
-                    changes.push((*coord, false));
-                } else if !*active && *active_adjacent == 3 {
-                    changes.push((*coord, true));
-                }
-            });
+            .map(|&(x, y)| ((x, y, 0), true))
+            .collect::<HashMap<Coordinate3, bool>>();
 
-        changes.iter().for_each(|(coord, status)| {
-            cubes
-                .entry(*coord)
-                .and_modify(|e| *e = *status)
-                .or_insert(*status);
+        debug!("initial cubes: {:#?}", cubes);
+        let mut animation = Animation::new("day17");
+        run_cycles(&mut cubes, get_relevant_cubes_3d, |cubes| {
+            animation.frame(|| draw_cubes(cubes))
         });
+        if let Err(error) = animation.finish() {
+            info!("day17: could not write the animation: {}", error);
+        }
+
+        cubes.values().filter(|&&active| active).count()
     }
 
-    cubes
-        .iter()
-        .fold(0, |sum, (_, active)| if *active { sum + 1 } else { sum })
+    fn part2(active_cubes: &Vec<(i32, i32)>) -> usize {
+        let mut cubes = active_cubes
+            .iter()
+            .map(|&(x, y)| ((x, y, 0, 0), true))
+            .collect::<HashMap<Coordinate4, bool>>();
+
+        run_cycles(&mut cubes, get_relevant_cubes_4d, |_| ());
+
+        cubes.values().filter(|&&active| active).count()
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day17 {
+    /// An initial slice of `size` by `size` cubes, at most 8 by 8 like the
+    /// puzzle's: the four-dimensional part takes seconds at that size and
+    /// grows with the fourth power of the side.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.min(8);
+        (0..size)
+            .map(|_| {
+                let mut row = (0..size)
+                    .map(|_| if rng.gen_bool(0.4) { '#' } else { '.' })
+                    .collect::<String>();
+                row.push('\n');
+                row
+            })
+            .collect()
+    }
 }
@@ -1,6 +1,7 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::env;
+use crate::generate::Generator;
+use crate::{debug, trace, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 
 #[derive(Debug)]
 enum Operation {
@@ -8,160 +9,223 @@ enum Operation {
     Multiplication,
 }
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+pub struct Day18;
 
-    fn calculate(expression: &mut Vec<String>) -> usize {
-        let mut sum = 0;
-        let mut operation = Operation::Addition;
+fn calculate(expression: &mut Vec<String>) -> usize {
+    let mut sum = 0;
+    let mut operation = Operation::Addition;
 
-        while expression.len() != 0 {
-            let token = &expression[0];
+    while !expression.is_empty() {
+        let token = &expression[0];
 
-            match token.parse::<usize>() {
-                Ok(number) => {
-                    expression.remove(0);
-                    match operation {
-                        Operation::Addition => sum += number,
-                        Operation::Multiplication => sum *= number,
-                    }
+        match token.parse::<usize>() {
+            Ok(number) => {
+                expression.remove(0);
+                match operation {
+                    Operation::Addition => sum += number,
+                    Operation::Multiplication => sum *= number,
                 }
-                Err(error_message) => {
-                    match token.as_ref() {
-                        "+" => {
-                            expression.remove(0);
-                            operation = Operation::Addition;
-                        }
-                        "*" => {
-                            expression.remove(0);
-                            operation = Operation::Multiplication;
-                        }
-                        _ => {
-                            if token.starts_with('(') {
-                                println!("Token: {}, should recurse", token);
-                                expression[0] = token[1..].to_string();
-                                let number = calculate(expression);
-                                match operation {
-                                    Operation::Addition => sum += number,
-                                    Operation::Multiplication => sum *= number,
-                                }
-                            } else {
-                                match token.find(')') {
-                                    Some(pos) => {
-                                        if pos != 0 {
-                                            let number = token[..pos].parse::<usize>().unwrap();
-                                            match operation {
-                                                Operation::Addition => sum += number,
-                                                Operation::Multiplication => sum *= number,
-                                            }
+            }
+            Err(_) => {
+                match token.as_ref() {
+                    "+" => {
+                        expression.remove(0);
+                        operation = Operation::Addition;
+                    }
+                    "*" => {
+                        expression.remove(0);
+                        operation = Operation::Multiplication;
+                    }
+                    _ => {
+                        if let Some(inner_token) = token.strip_prefix('(') {
+                            trace!("Token: {}, should recurse", token);
+                            expression[0] = inner_token.to_string();
+                            let number = calculate(expression);
+                            match operation {
+                                Operation::Addition => sum += number,
+                                Operation::Multiplication => sum *= number,
+                            }
+                        } else {
+                            match token.find(')') {
+                                Some(pos) => {
+                                    if pos != 0 {
+                                        let number = token[..pos].parse::<usize>().unwrap();
+                                        match operation {
+                                            Operation::Addition => sum += number,
+                                            Operation::Multiplication => sum *= number,
                                         }
-                                        expression[0] = token[pos + 1..].to_string();
-
-                                        return sum;
-                                    }
-                                    None => {
-                                        expression.remove(0);
                                     }
+                                    expression[0] = token[pos + 1..].to_string();
+
+                                    return sum;
+                                }
+                                None => {
+                                    expression.remove(0);
                                 }
                             }
                         }
-                    };
-                }
-            };
-        }
-
-        sum
+                    }
+                };
+            }
+        };
     }
 
-    contents.lines().fold(0, |sum, line| {
-        let mut expression = line
-            .split(' ')
-            .map(|token| token.to_string())
-            .collect::<Vec<String>>();
-        let result = calculate(&mut expression);
-        println!("expression: {:#?} = {}", line, result);
-        sum + result
-    })
+    sum
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    fn parse_tokens(tokens: &mut Vec<char>) -> usize {
-        let mut token_index = 0;
-        let mut numbers = Vec::<usize>::new();
-        let mut last_operation = Operation::Addition;
-
-        while tokens.len() != 0 {
-            let num_tokens = tokens.iter().take_while(|&&c| char::is_numeric(c)).count();
-
-            match tokens
-                .iter()
-                .take_while(|&&c| char::is_numeric(c))
-                .collect::<String>()
-                .parse::<usize>()
-            {
-                Ok(number) => {
-                    for _ in 0..num_tokens {
-                        tokens.remove(0);
+fn parse_tokens(tokens: &mut Vec<char>) -> usize {
+    let mut numbers = Vec::<usize>::new();
+    let mut last_operation = Operation::Addition;
+
+    while !tokens.is_empty() {
+        let num_tokens = tokens.iter().take_while(|&&c| char::is_numeric(c)).count();
+
+        match tokens
+            .iter()
+            .take_while(|&&c| char::is_numeric(c))
+            .collect::<String>()
+            .parse::<usize>()
+        {
+            Ok(number) => {
+                for _ in 0..num_tokens {
+                    tokens.remove(0);
+                }
+                match last_operation {
+                    Operation::Addition => match numbers.last_mut() {
+                        Some(last_number) => *last_number += number,
+                        None => numbers.push(number),
+                    },
+                    Operation::Multiplication => {
+                        numbers.push(number);
                     }
+                }
+                trace!("number: {:?}, numbers: {:?}", number, numbers);
+            }
+            Err(_) => match tokens[0] {
+                '*' => {
+                    last_operation = Operation::Multiplication;
+                    tokens.remove(0);
+                }
+                '+' => {
+                    last_operation = Operation::Addition;
+                    tokens.remove(0);
+                }
+                '(' => {
+                    tokens.remove(0);
+                    trace!("paren open, recurse into: {:?}", tokens);
+                    let inner_number = parse_tokens(tokens);
                     match last_operation {
                         Operation::Addition => match numbers.last_mut() {
-                            Some(last_number) => *last_number += number,
-                            None => numbers.push(number),
+                            Some(last_number) => *last_number += inner_number,
+                            None => numbers.push(inner_number),
                         },
                         Operation::Multiplication => {
-                            numbers.push(number);
+                            numbers.push(inner_number);
                         }
                     }
-                    println!("number: {:?}, numbers: {:?}", number, numbers);
                 }
-                Err(_) => match tokens[0] {
-                    '*' => {
-                        last_operation = Operation::Multiplication;
-                        tokens.remove(0);
-                    }
-                    '+' => {
-                        last_operation = Operation::Addition;
-                        tokens.remove(0);
-                    }
-                    '(' => {
-                        tokens.remove(0);
-                        println!("paren open, recurse into: {:?}", tokens);
-                        let inner_number = parse_tokens(tokens);
-                        match last_operation {
-                            Operation::Addition => match numbers.last_mut() {
-                                Some(last_number) => *last_number += inner_number,
-                                None => numbers.push(inner_number),
-                            },
-                            Operation::Multiplication => {
-                                numbers.push(inner_number);
+                ')' => {
+                    tokens.remove(0);
+                    trace!("paren close");
+                    break;
+                }
+                _ => {}
+            },
+        }
+    }
+    trace!("returning product of numbers: {:?}", numbers);
+    numbers.iter().product()
+}
+
+impl Solution for Day18 {
+    const DAY: usize = 18;
+
+    type Input = Vec<String>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<String>, ParseError> {
+        contents
+            .lines()
+            .map(|line| {
+                let mut open_parens = Vec::new();
+                for (index, c) in line.char_indices() {
+                    let token = &line[index..index + c.len_utf8()];
+                    match c {
+                        '0'..='9' | '+' | '*' | ' ' => (),
+                        '(' => open_parens.push(token),
+                        ')' => {
+                            if open_parens.pop().is_none() {
+                                return Err(ParseError::at(contents, token, "unmatched `)`"));
                             }
                         }
+                        _ => {
+                            return Err(ParseError::at(
+                                contents,
+                                token,
+                                "expected a digit, `+`, `*`, `(` or `)`",
+                            ))
+                        }
                     }
-                    ')' => {
-                        tokens.remove(0);
-                        println!("paren close");
-                        break;
-                    }
-                    _ => {}
-                },
-            }
-        }
-        println!("returning product of numbers: {:?}", numbers);
-        return numbers.iter().fold(1, |product, number| product * number);
+                }
+                match open_parens.pop() {
+                    Some(token) => Err(ParseError::at(contents, token, "unmatched `(`")),
+                    None => Ok(line.to_string()),
+                }
+            })
+            .collect()
     }
 
-    contents.lines().fold(0, |sum, line| {
-        let mut tokens = line.chars().filter(|c| *c != ' ').collect::<Vec<char>>();
-        sum + parse_tokens(&mut tokens)
-    })
+    fn part1(expressions: &Vec<String>) -> usize {
+        expressions.iter().fold(0, |sum, line| {
+            let mut expression = line
+                .split(' ')
+                .map(|token| token.to_string())
+                .collect::<Vec<String>>();
+            let result = calculate(&mut expression);
+            debug!("expression: {:#?} = {}", line, result);
+            sum + result
+        })
+    }
+
+    fn part2(expressions: &Vec<String>) -> usize {
+        expressions.iter().fold(0, |sum, line| {
+            let mut tokens = line.chars().filter(|c| *c != ' ').collect::<Vec<char>>();
+            sum + parse_tokens(&mut tokens)
+        })
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    //println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day18 {
+    /// `size` expressions of single digits, nested up to three deep.
+    ///
+    /// Each has at most twelve numbers, which keeps every result, and the
+    /// sum of a million of them, within 64 bits.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        // An expression of at most `numbers` numbers, at least 2, and how
+        // many it used
+        fn expression(rng: &mut StdRng, depth: usize, numbers: usize) -> (String, usize) {
+            let operands = rng.gen_range(2, numbers.min(5) + 1);
+            let mut text = String::new();
+            let mut left = numbers;
+            for operand in 0..operands {
+                if operand > 0 {
+                    text.push_str(if rng.gen() { " + " } else { " * " });
+                }
+                // Keep a number for each operand still to come
+                let available = left - (operands - 1 - operand);
+                if depth < 3 && available >= 2 && rng.gen_bool(0.25) {
+                    let (inner, used) = expression(rng, depth + 1, available);
+                    text.push_str(&format!("({})", inner));
+                    left -= used;
+                } else {
+                    text.push_str(&rng.gen_range(1, 10).to_string());
+                    left -= 1;
+                }
+            }
+            (text, numbers - left)
+        }
+
+        (0..size).map(|_| expression(rng, 0, 12).0 + "\n").collect()
+    }
 }
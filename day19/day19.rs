@@ -1,9 +1,15 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{ParseError, Solution};
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use regex::Regex;
-use std::collections::HashMap;
-use std::env;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug)]
-struct Rule {
+#[derive(Debug, Clone)]
+pub struct Rule {
     rule_id: i32,
     primary_subrules: Option<Vec<i32>>,
     secondary_subrules: Option<Vec<i32>>,
@@ -11,48 +17,48 @@ struct Rule {
 }
 
 impl Rule {
-    fn new(rule_line: &str) -> Rule {
+    fn parse(contents: &str, rule_line: &str) -> Result<Rule, ParseError> {
         let rule_pattern = Regex::new(r"(?P<rule_id>\d+):\s(((?P<primary_subrules>[\d\s]+)(\s\|\s(?P<secondary_subrules>[\d\s]+))?)|(.(?P<rule>[a|b]).))$").unwrap();
 
-        let parsed = rule_pattern.captures(rule_line).unwrap();
-        let rule_id = parsed["rule_id"].parse::<i32>().unwrap();
-
-        Rule {
-            rule_id: rule_id,
-            primary_subrules: match parsed.name("primary_subrules") {
-                Some(subrules) => Some(
-                    subrules
-                        .as_str()
-                        .split(' ')
-                        .map(|token| token.parse::<i32>().unwrap())
-                        .collect::<Vec<i32>>(),
-                ),
-                None => None,
-            },
-            secondary_subrules: match parsed.name("secondary_subrules") {
-                Some(subrules) => Some(
-                    subrules
-                        .as_str()
-                        .split(' ')
-                        .map(|token| token.parse::<i32>().unwrap())
-                        .collect::<Vec<i32>>(),
-                ),
-                None => None,
-            },
-            rule: match parsed.name("rule") {
-                Some(rule) => Some(rule.as_str().chars().nth(0).unwrap()),
-                None => None,
-            },
-        }
+        let parsed = rule_pattern.captures(rule_line).ok_or_else(|| {
+            ParseError::at(
+                contents,
+                rule_line,
+                "expected `<id>: <subrules> | <subrules>` or `<id>: \"<a|b>\"`",
+            )
+        })?;
+        let rule_id = parse_at(contents, &parsed["rule_id"])?;
+
+        let parse_subrules = |subrules: regex::Match| {
+            subrules
+                .as_str()
+                .split(' ')
+                .map(|token| parse_at(contents, token))
+                .collect::<Result<Vec<i32>, ParseError>>()
+        };
+
+        Ok(Rule {
+            rule_id,
+            primary_subrules: parsed
+                .name("primary_subrules")
+                .map(parse_subrules)
+                .transpose()?,
+            secondary_subrules: parsed
+                .name("secondary_subrules")
+                .map(parse_subrules)
+                .transpose()?,
+            rule: parsed
+                .name("rule")
+                .map(|rule| rule.as_str().chars().next().unwrap()),
+        })
     }
 }
 
 fn matches_rules(
     rules: &HashMap<i32, Rule>,
     rule_index: i32,
-    payload: &Vec<char>,
+    payload: &[char],
     payload_indexes: &mut Vec<usize>,
-    indentation: usize,
 ) -> bool {
     let mut next_payload_indexes = vec![];
     let found_match = payload_indexes.iter_mut().any(|payload_index| {
@@ -64,62 +70,32 @@ fn matches_rules(
         let mut primary_subpayload_index = vec![*payload_index];
         let mut secondary_subpayload_index = vec![*payload_index];
         let matches_primary = match &rule.primary_subrules {
-            Some(subrules) => {
-                let matches_subrule = subrules.iter().all(|subrule_index| {
-                    if matches_rules(
-                        rules,
-                        *subrule_index,
-                        payload,
-                        &mut primary_subpayload_index,
-                        indentation + 4,
-                    ) {
-                        true
-                    } else {
-                        false
-                    }
-                });
-
-                if matches_subrule {
-                    true
-                } else {
-                    false
-                }
-            }
+            Some(subrules) => subrules.iter().all(|subrule_index| {
+                matches_rules(
+                    rules,
+                    *subrule_index,
+                    payload,
+                    &mut primary_subpayload_index,
+                )
+            }),
             None => false,
         };
 
         let matches_secondary = match &rule.secondary_subrules {
-            Some(subrules) => {
-                let matches_subrule = subrules.iter().all(|subrule_index| {
-                    if matches_rules(
-                        rules,
-                        *subrule_index,
-                        payload,
-                        &mut secondary_subpayload_index,
-                        indentation + 4,
-                    ) {
-                        true
-                    } else {
-                        false
-                    }
-                });
-
-                if matches_subrule {
-                    true
-                } else {
-                    false
-                }
-            }
+            Some(subrules) => subrules.iter().all(|subrule_index| {
+                matches_rules(
+                    rules,
+                    *subrule_index,
+                    payload,
+                    &mut secondary_subpayload_index,
+                )
+            }),
             None => false,
         };
 
         match rule.rule {
             Some(rule_char) => {
-                let found_match = if payload.get(*payload_index) == Some(&rule_char) {
-                    true
-                } else {
-                    false
-                };
+                let found_match = payload.get(*payload_index) == Some(&rule_char);
 
                 next_payload_indexes.push(*payload_index + 1);
                 found_match
@@ -151,74 +127,179 @@ fn matches_rules(
     found_match
 }
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut rules = HashMap::<i32, Rule>::new();
-
-    let rule_block = contents.split("\n\n").nth(0).unwrap();
-    let payload_block = contents.split("\n\n").nth(1).unwrap();
-
-    rule_block.lines().for_each(|line| {
-        let rule = Rule::new(line);
-        rules.entry(rule.rule_id).or_insert(rule);
-    });
-
-    payload_block.lines().fold(0, |sum, line| {
+fn count_matching_messages(rules: &HashMap<i32, Rule>, messages: &[String]) -> usize {
+    messages.iter().fold(0, |sum, line| {
         let payload = line.chars().collect::<Vec<char>>();
         let mut payload_indexes = vec![0];
 
-        if matches_rules(&rules, 0, &payload, &mut payload_indexes, 0) {
-            if payload_indexes.iter().any(|index| payload.len() == *index) {
-                sum + 1
-            } else {
-                sum
-            }
+        if matches_rules(rules, 0, &payload, &mut payload_indexes)
+            && payload_indexes.contains(&payload.len())
+        {
+            sum + 1
         } else {
             sum
         }
     })
 }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+#[derive(Debug, Clone)]
+pub struct Messages {
+    pub rules: HashMap<i32, Rule>,
+    pub messages: Vec<String>,
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: usize = 19;
+
+    type Input = Messages;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Messages, ParseError> {
+        let mut rules = HashMap::<i32, Rule>::new();
+
+        let mut blocks = contents.split("\n\n");
+        let rule_block = blocks.next().unwrap();
+        let payload_block = blocks.next().ok_or_else(|| {
+            ParseError::at(
+                contents,
+                &contents[contents.len()..],
+                "expected messages after the rules",
+            )
+        })?;
+
+        for line in rule_block.lines() {
+            let rule = Rule::parse(contents, line)?;
+            rules.entry(rule.rule_id).or_insert(rule);
+        }
+
+        Ok(Messages {
+            rules,
+            messages: payload_block.lines().map(|line| line.to_string()).collect(),
+        })
+    }
 
-    let mut rules = HashMap::<i32, Rule>::new();
+    fn part1(input: &Messages) -> usize {
+        count_matching_messages(&input.rules, &input.messages)
+    }
 
-    let rule_block = contents.split("\n\n").nth(0).unwrap();
-    let payload_block = contents.split("\n\n").nth(1).unwrap();
+    fn part2(input: &Messages) -> usize {
+        let mut rules = input.rules.clone();
 
-    rule_block.lines().for_each(|line| {
-        let mut line_copy = line.clone();
-        if line == "8: 42" {
-            line_copy = "8: 42 | 42 8";
+        if rules.contains_key(&8) {
+            let rule = "8: 42 | 42 8";
+            rules.insert(8, Rule::parse(rule, rule).unwrap());
         }
-        if line == "11: 42 31" {
-            line_copy = "11: 42 31 | 42 11 31";
+        if rules.contains_key(&11) {
+            let rule = "11: 42 31 | 42 11 31";
+            rules.insert(11, Rule::parse(rule, rule).unwrap());
         }
-        let rule = Rule::new(line_copy);
-        rules.entry(rule.rule_id).or_insert(rule);
-    });
 
-    payload_block.lines().fold(0, |sum, line| {
-        let payload = line.chars().collect::<Vec<char>>();
-        let mut payload_indexes = vec![0];
+        count_matching_messages(&rules, &input.messages)
+    }
+}
 
-        if matches_rules(&rules, 0, &payload, &mut payload_indexes, 0) {
-            if payload_indexes.iter().any(|index| payload.len() == *index) {
-                sum + 1
-            } else {
-                sum
+impl Generator for Day19 {
+    /// A grammar shaped like the puzzle's and `size` messages: a third match
+    /// rule 0 as given, a third only once rules 8 and 11 loop, and the rest
+    /// are random.
+    ///
+    /// Rules 42 and 31 match eight letters each, built from layers of rules
+    /// that pair up rules of the layer below.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        enum Production {
+            Letter(char),
+            Alternatives(Vec<Vec<usize>>),
+        }
+
+        // Ordered by ID so that a seed always gives the same rules
+        let mut rules = BTreeMap::<usize, Production>::new();
+
+        let mut ids = (1..140)
+            .filter(|id| ![8, 11, 31, 42].contains(id))
+            .collect::<Vec<usize>>();
+        ids.shuffle(rng);
+        let mut ids = ids.into_iter();
+
+        let mut layer = vec![ids.next().unwrap(), ids.next().unwrap()];
+        rules.insert(layer[0], Production::Letter('a'));
+        rules.insert(layer[1], Production::Letter('b'));
+        for depth in 1..=3 {
+            let next_ids = match depth {
+                3 => vec![42, 31],
+                _ => ids.by_ref().take(4).collect(),
+            };
+            for &id in &next_ids {
+                let pair = |rng: &mut StdRng| {
+                    vec![*layer.choose(rng).unwrap(), *layer.choose(rng).unwrap()]
+                };
+                let alternatives = match rng.gen_range(0, 3) {
+                    0 => vec![pair(rng)],
+                    _ => vec![pair(rng), pair(rng)],
+                };
+                rules.insert(id, Production::Alternatives(alternatives));
             }
-        } else {
-            sum
+            layer = next_ids;
         }
-    })
-}
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+        rules.insert(0, Production::Alternatives(vec![vec![8, 11]]));
+        rules.insert(8, Production::Alternatives(vec![vec![42]]));
+        rules.insert(11, Production::Alternatives(vec![vec![42, 31]]));
+
+        fn sample(rules: &BTreeMap<usize, Production>, id: usize, rng: &mut StdRng) -> String {
+            match &rules[&id] {
+                Production::Alternatives(alternatives) => alternatives
+                    .choose(rng)
+                    .unwrap()
+                    .iter()
+                    .map(|&id| sample(rules, id, rng))
+                    .collect(),
+                Production::Letter(letter) => letter.to_string(),
+            }
+        }
+
+        let messages = (0..size)
+            .map(|_| {
+                let rule_ids = match rng.gen_range(0, 3) {
+                    0 => vec![42, 42, 31],
+                    1 => {
+                        let loops = rng.gen_range(1, 6);
+                        let mut ids = vec![42; loops + rng.gen_range(1, 4)];
+                        ids.extend(vec![31; loops]);
+                        ids
+                    }
+                    _ => {
+                        return (0..8 * rng.gen_range(2, 12))
+                            .map(|_| if rng.gen() { 'a' } else { 'b' })
+                            .chain(Some('\n'))
+                            .collect::<String>()
+                    }
+                };
+                rule_ids
+                    .iter()
+                    .map(|&id| sample(&rules, id, rng))
+                    .chain(Some("\n".to_string()))
+                    .collect::<String>()
+            })
+            .collect::<String>();
+
+        let mut lines = rules
+            .iter()
+            .map(|(id, rule)| match rule {
+                Production::Alternatives(alternatives) => format!(
+                    "{}: {}\n",
+                    id,
+                    alternatives
+                        .iter()
+                        .map(|subrules| subrules.iter().join(" "))
+                        .join(" | ")
+                ),
+                Production::Letter(letter) => format!("{}: \"{}\"\n", id, letter),
+            })
+            .collect::<Vec<String>>();
+        lines.shuffle(rng);
+
+        format!("{}\n{}", lines.concat(), messages)
+    }
 }
@@ -1,10 +1,13 @@
-use image::ImageBuffer;
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::grid::{Bounds, Coordinate, Grid};
+use crate::render::{Animation, Canvas, WHITE};
+use crate::{debug, info, trace, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use regex::Regex;
-use std::collections::HashMap;
-use std::env;
-
-type Color = (u8, u8, u8);
-type Coordinate = (i32, i32);
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Location {
@@ -14,54 +17,60 @@ enum Location {
     Right,
 }
 
-#[derive(Debug)]
-struct Tile {
+#[derive(Debug, Clone)]
+pub struct Tile {
     tile_id: i32,
     location: Coordinate,
-    pixels: Vec<Coordinate>,
+    pixels: Grid<bool>,
     borders: Vec<((i32, i32), Location)>,
-    block_size: usize,
     dimension: usize,
 }
 
 impl Tile {
-    fn new(block_index: usize, block_size: usize, block: &str) -> Tile {
+    fn parse(
+        contents: &str,
+        block_index: usize,
+        block_size: usize,
+        block: &str,
+    ) -> Result<Tile, ParseError> {
         let tile_id_pattern = Regex::new(r"Tile (?P<tile_id>\d+):$").unwrap();
 
+        let header = block.lines().next().unwrap_or(block);
         let parsed = tile_id_pattern
-            .captures(block.lines().nth(0).unwrap())
-            .unwrap();
-        let tile_id = parsed["tile_id"].parse::<i32>().unwrap();
-
-        let mut pixels = Vec::<Coordinate>::new();
-
-        block.lines().skip(1).enumerate().for_each(|(y, row)| {
-            row.chars().enumerate().for_each(|(x, value)| {
-                let coord = (x as i32, y as i32);
-                if value != '.' {
-                    pixels.push(coord);
-                }
-            });
-        });
-
-        let dimension = 10;
-
-        Tile {
-            tile_id: tile_id,
+            .captures(header)
+            .ok_or_else(|| ParseError::at(contents, header, "expected `Tile <id>:`"))?;
+        let tile_id = parse_at(contents, &parsed["tile_id"])?;
+
+        let image = block.split_once('\n').map_or("", |(_, image)| image);
+        let pixels = Grid::parse(
+            contents,
+            image,
+            "expected `.` or `#`",
+            |value| match value {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None,
+            },
+        )?
+        .into_sparse(|&lit| lit);
+
+        let dimension = pixels.width() as usize;
+
+        Ok(Tile {
+            tile_id,
             location: (
                 (block_index % block_size) as i32,
                 (block_index / block_size) as i32,
             ),
             borders: Tile::get_borders(&pixels, dimension),
-            pixels: pixels,
-            block_size: block_size,
-            dimension: dimension,
-        }
+            pixels,
+            dimension,
+        })
     }
 
     fn get_border_bitpattern(
-        pixels: &Vec<Coordinate>,
-        border: &Vec<Coordinate>,
+        pixels: &Grid<bool>,
+        border: &[Coordinate],
         dimension: usize,
     ) -> (i32, i32) {
         let max_index = dimension as i32 - 1;
@@ -69,7 +78,7 @@ impl Tile {
         let mut bitpattern = 0;
         let mut bitpattern_reversed = 0;
         border.iter().enumerate().for_each(|(bit, coord)| {
-            if pixels.contains(&coord) {
+            if pixels.contains(*coord) {
                 bitpattern_reversed |= 1 << bit;
                 bitpattern |= 1 << (max_index - bit as i32);
             }
@@ -78,7 +87,7 @@ impl Tile {
         (bitpattern, bitpattern_reversed)
     }
 
-    fn get_borders(pixels: &Vec<Coordinate>, dimension: usize) -> Vec<((i32, i32), Location)> {
+    fn get_borders(pixels: &Grid<bool>, dimension: usize) -> Vec<((i32, i32), Location)> {
         let max_index = dimension as i32 - 1;
 
         let top_row = (0..dimension as i32)
@@ -117,8 +126,8 @@ impl Tile {
     fn draw(
         &self,
         tiles: &HashMap<i32, Tile>,
-        pixels: &mut Vec<(Coordinate, Color)>,
-        top_left: (i32, i32),
+        canvas: &mut Canvas,
+        top_left: Coordinate,
         tile_border: i32,
     ) {
         let color = (0x56, 0x67, 0x44);
@@ -128,7 +137,7 @@ impl Tile {
 
         for y in 0..self.dimension {
             for x in 0..self.dimension {
-                if self.pixels.contains(&(x as i32, y as i32)) {
+                if self.pixels.contains((x as i32, y as i32)) {
                     let pixel_color = if x == 0
                         || y == 0
                         || x as i32 == (self.dimension as i32 - 1)
@@ -136,19 +145,19 @@ impl Tile {
                     {
                         border_color
                     } else {
-                        match self.count_valid_borders(&tiles) {
+                        match self.count_valid_borders(tiles) {
                             2 => corner_color,
                             3 => edge_color,
                             _ => color,
                         }
                     };
-                    pixels.push((
+                    canvas.put(
                         (
                             (top_left.0 + tile_border + x as i32),
                             (top_left.1 + tile_border + y as i32),
                         ),
                         pixel_color,
-                    ));
+                    );
                 }
             }
         }
@@ -200,12 +209,7 @@ impl Tile {
     }
 
     fn count_valid_borders(&self, tiles: &HashMap<i32, Tile>) -> usize {
-        self.valid_borders(&tiles).len()
-    }
-
-    fn is_edgepiece(&self, tiles: &HashMap<i32, Tile>) -> bool {
-        // Edge pieces has three valid borders
-        self.count_valid_borders(tiles) == 3
+        self.valid_borders(tiles).len()
     }
 
     fn is_cornerpiece(&self, tiles: &HashMap<i32, Tile>) -> bool {
@@ -214,52 +218,27 @@ impl Tile {
     }
 
     fn find_bordering_tiles(&self, tiles: &HashMap<i32, Tile>) -> Vec<i32> {
-        self.valid_borders(&tiles)
+        self.valid_borders(tiles)
     }
 
     fn rotate(&mut self) {
-        self.pixels = self
-            .pixels
-            .iter()
-            .map(|(x, y)| (9 - 1 * *y as i32, *x as i32))
-            .collect::<Vec<Coordinate>>();
+        self.pixels.rotate();
         self.borders = Tile::get_borders(&self.pixels, self.dimension);
     }
 
     fn flip_horizontal(&mut self) {
-        self.pixels = self
-            .pixels
-            .iter()
-            .map(|(x, y)| (9 - *x as i32, *y as i32))
-            .collect::<Vec<Coordinate>>();
+        self.pixels.flip_horizontal();
         self.borders = Tile::get_borders(&self.pixels, self.dimension);
     }
 
     fn flip_vertical(&mut self) {
-        self.pixels = self
-            .pixels
-            .iter()
-            .map(|(x, y)| (*x as i32, 9 - *y as i32))
-            .collect::<Vec<Coordinate>>();
+        self.pixels.flip_vertical();
         self.borders = Tile::get_borders(&self.pixels, self.dimension);
     }
 
     fn remove_borders(&mut self) {
-        self.pixels = self
-            .pixels
-            .iter()
-            .filter_map(|&(x, y)| {
-                if x == 0
-                    || y == 0
-                    || x == self.dimension as i32 - 1
-                    || y == self.dimension as i32 - 1
-                {
-                    None
-                } else {
-                    Some((x as i32 - 1, y as i32 - 1))
-                }
-            })
-            .collect::<Vec<Coordinate>>();
+        let inner = self.dimension as i32 - 1;
+        self.pixels.crop(Bounds::new((1, 1), (inner, inner)));
         self.dimension -= 2;
         self.borders = vec![];
     }
@@ -303,7 +282,7 @@ fn needs_to_flip(
     let (other_direction, other_reversed) = other_tile.matching_border(current_tile).unwrap();
 
     if other_reversed {
-        println!("Flip!: {:?}", other_direction);
+        trace!("Flip!: {:?}", other_direction);
         Some(other_direction)
     } else {
         None
@@ -319,7 +298,7 @@ fn is_placed_correctly(
     let other_tile = &tiles.get(&other_tile_id).unwrap();
 
     let (other_direction, other_reversed) = other_tile.matching_border(current_tile).unwrap();
-    let (direction, reversed) = current_tile.matching_border(other_tile).unwrap();
+    let (direction, _) = current_tile.matching_border(other_tile).unwrap();
 
     !other_reversed
         && match direction {
@@ -354,343 +333,365 @@ fn switch_tiles(tiles: &mut HashMap<i32, Tile>, from: Coordinate, to: Coordinate
 }
 
 fn remove_borders(tiles: &mut HashMap<i32, Tile>) {
-    tiles
-        .iter_mut()
-        .for_each(|(tile_id, tile)| tile.remove_borders());
+    tiles.values_mut().for_each(|tile| tile.remove_borders());
 }
 
-fn visualize(tiles: &HashMap<i32, Tile>, block_size: usize, tile_border: i32, frame: i32) {
-    let max_dimension: u32 = 10;
-    let scale: u32 = 8;
-    let border: u32 = 2;
-    let tile_size = (max_dimension as i32 + tile_border * 2) as u32;
-    let mut pixels = Vec::<(Coordinate, Color)>::new();
-
-    println!("frame: {}, ", frame);
+fn draw_tiles(tiles: &HashMap<i32, Tile>, block_size: usize, tile_border: i32) -> Canvas {
+    let max_dimension = 10;
+    let scale = 8;
+    let border = 2;
+    let tile_size = max_dimension + tile_border * 2;
+    let size = (block_size as i32 * tile_size + border * 2) as u32;
+    let mut canvas = Canvas::new(size, size, scale, WHITE);
 
     tiles.iter().for_each(|(_, tile)| {
         tile.draw(
-            &tiles,
-            &mut pixels,
+            tiles,
+            &mut canvas,
             (
-                (tile.location.0 as usize * tile_size as usize) as i32,
-                (tile.location.1 as usize * tile_size as usize) as i32,
+                border + tile.location.0 * tile_size,
+                border + tile.location.1 * tile_size,
             ),
             tile_border,
         );
     });
 
-    let real_size = (
-        (block_size as u32 * tile_size + border * 2) * scale as u32,
-        (block_size as u32 * tile_size + border * 2) * scale as u32,
-    );
+    canvas
+}
 
-    let mut img = ImageBuffer::from_fn(real_size.0, real_size.1, |_x, _y| {
-        image::Rgb([255, 255, 255])
-    });
+fn draw_full_map(pixels: &Grid<bool>, full_size: usize) -> Canvas {
+    let scale = 8;
+    let border = 2;
+    let size = (full_size as i32 + border * 2) as u32;
+    let mut canvas = Canvas::new(size, size, scale, WHITE);
 
-    for ((x, y), color) in pixels {
-        let pixel = image::Rgb([color.0, color.1, color.2]);
-        if x >= 0 && y >= 0 && x < real_size.0 as i32 && y < real_size.1 as i32 {
-            for scaled_y in 0..scale {
-                for scaled_x in 0..scale {
-                    img.put_pixel(
-                        ((x as u32 + border) * scale + scaled_x) as u32,
-                        ((y as u32 + border) * scale + scaled_y) as u32,
-                        pixel,
-                    );
-                }
-            }
-        }
+    let color = (0x56, 0x67, 0x44);
+    for ((x, y), _) in pixels.iter() {
+        canvas.put((x + border, y + border), color);
     }
 
-    img.save(format!("frames/day20.frame{:05}.png", frame))
-        .unwrap();
+    canvas
 }
 
-fn visualize_full_map(pixels: &Vec<Coordinate>, full_size: usize, frame: i32) {
-    let scale: u32 = 8;
-    let border: u32 = 2;
-    let real_size = (
-        (full_size as u32 + border * 2) * scale as u32,
-        (full_size as u32 + border * 2) * scale as u32,
-    );
-
-    let mut img = ImageBuffer::from_fn(real_size.0, real_size.1, |_x, _y| {
-        image::Rgb([255, 255, 255])
-    });
+#[derive(Debug, Clone)]
+pub struct TileSet {
+    pub tiles: HashMap<i32, Tile>,
+    pub block_size: usize,
+}
 
-    let color = (0x56, 0x67, 0x44);
-    for &(x, y) in pixels {
-        let pixel = image::Rgb([color.0, color.1, color.2]);
-        if x >= 0 && y >= 0 && x < real_size.0 as i32 && y < real_size.1 as i32 {
-            for scaled_y in 0..scale {
-                for scaled_x in 0..scale {
-                    img.put_pixel(
-                        ((x as u32 + border) * scale + scaled_x) as u32,
-                        ((y as u32 + border) * scale + scaled_y) as u32,
-                        pixel,
-                    );
-                }
-            }
-        }
-    }
+pub struct Day20;
 
-    img.save(format!("frames/day20.frame{:05}.png", frame))
-        .unwrap();
-}
+impl Solution for Day20 {
+    const DAY: usize = 20;
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let block_size = (contents.split("\n\n").count() as f32).sqrt() as usize;
-    let mut tiles = HashMap::<i32, Tile>::new();
-
-    contents
-        .split("\n\n")
-        .enumerate()
-        .for_each(|(index, tile_block)| {
-            let tile = Tile::new(index, block_size, tile_block);
-            tiles.entry(tile.tile_id).or_insert(tile);
-        });
+    type Input = TileSet;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    tiles.iter().fold(1, |product, (tile_id, tile)| {
-        if tile.is_cornerpiece(&tiles) {
-            product * *tile_id as usize
-        } else {
-            product
-        }
-    })
-}
+    fn parse(contents: &str) -> Result<TileSet, ParseError> {
+        let block_size = (contents.split("\n\n").count() as f32).sqrt() as usize;
+        let mut tiles = HashMap::<i32, Tile>::new();
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-    let block_size = (contents.split("\n\n").count() as f32).sqrt() as usize;
-    let mut tiles = HashMap::<i32, Tile>::new();
-
-    contents
-        .split("\n\n")
-        .enumerate()
-        .for_each(|(index, tile_block)| {
-            let tile = Tile::new(index, block_size, tile_block);
+        for (index, tile_block) in contents.split("\n\n").enumerate() {
+            let tile = Tile::parse(contents, index, block_size, tile_block)?;
             tiles.entry(tile.tile_id).or_insert(tile);
-        });
+        }
 
-    let enable_visualization = true;
-    let tile_border = 1;
-    let mut frame = 0;
+        Ok(TileSet { tiles, block_size })
+    }
 
-    visualize(&tiles, block_size, tile_border, frame);
+    fn part1(tile_set: &TileSet) -> usize {
+        let tiles = &tile_set.tiles;
 
-    let cornerpiece_id = tiles
-        .iter()
-        .filter_map(|(tile_id, tile)| {
-            if tile.is_cornerpiece(&tiles) {
-                Some(*tile_id)
+        tiles.iter().fold(1, |product, (tile_id, tile)| {
+            if tile.is_cornerpiece(tiles) {
+                product * *tile_id as usize
             } else {
-                None
+                product
             }
         })
-        .next()
-        .unwrap();
-
-    println!(
-        "cornerpiece: {}: {:?}",
-        cornerpiece_id,
-        tiles.get(&cornerpiece_id).unwrap().location
-    );
-
-    // Place a cornerpiece at the top left tile
-    let mut tiles_to_move = vec![(cornerpiece_id, (0, 0))];
-    let mut completed_tiles = vec![];
-
-    while tiles_to_move.len() > 0 {
-        println!("Movelist: {:?}", tiles_to_move);
-        println!("Completed list: {:?}", completed_tiles);
-        let (tile_id_to_move, wanted_location) = tiles_to_move.pop().unwrap();
-
-        let bordering_tiles = tiles
-            .get(&tile_id_to_move)
-            .unwrap()
-            .find_bordering_tiles(&tiles);
-
-        let location = tiles.get(&tile_id_to_move).unwrap().location;
-        if location != wanted_location {
-            println!("Moving tile from {:?} to {:?}", location, wanted_location);
-            switch_tiles(&mut tiles, location, wanted_location);
-
-            if enable_visualization {
-                frame += 1;
-                visualize(&tiles, block_size, tile_border, frame)
+    }
+
+    fn part2(tile_set: &TileSet) -> usize {
+        let block_size = tile_set.block_size;
+        let mut tiles = tile_set.tiles.clone();
+
+        let tile_border = 1;
+        let mut animation = Animation::new("day20");
+
+        animation.frame(|| draw_tiles(&tiles, block_size, tile_border));
+
+        let cornerpiece_id = tiles
+            .iter()
+            .filter_map(|(tile_id, tile)| {
+                if tile.is_cornerpiece(&tiles) {
+                    Some(*tile_id)
+                } else {
+                    None
+                }
+            })
+            .next()
+            .unwrap();
+
+        debug!(
+            "cornerpiece: {}: {:?}",
+            cornerpiece_id,
+            tiles.get(&cornerpiece_id).unwrap().location
+        );
+
+        // Place a cornerpiece at the top left tile
+        let mut tiles_to_move = vec![(cornerpiece_id, (0, 0))];
+        let mut completed_tiles = vec![];
+
+        while !tiles_to_move.is_empty() {
+            trace!("Movelist: {:?}", tiles_to_move);
+            trace!("Completed list: {:?}", completed_tiles);
+            let (tile_id_to_move, wanted_location) = tiles_to_move.pop().unwrap();
+
+            let bordering_tiles = tiles
+                .get(&tile_id_to_move)
+                .unwrap()
+                .find_bordering_tiles(&tiles);
+
+            let location = tiles.get(&tile_id_to_move).unwrap().location;
+            if location != wanted_location {
+                debug!("Moving tile from {:?} to {:?}", location, wanted_location);
+                switch_tiles(&mut tiles, location, wanted_location);
+                animation.frame(|| draw_tiles(&tiles, block_size, tile_border));
             }
-        }
 
-        bordering_tiles.iter().for_each(|bordering_tile_id| {
-            if !completed_tiles.contains(bordering_tile_id) {
-                let mut placed_bordering_tile = false;
-                while !placed_bordering_tile {
-                    let bordering_tile_location = tiles.get(&bordering_tile_id).unwrap().location;
-
-                    match place_tile_next_to(
-                        tile_id_to_move,
-                        *bordering_tile_id,
-                        &mut tiles,
-                        block_size,
-                    ) {
-                        Some(new_location) => {
-                            switch_tiles(&mut tiles, bordering_tile_location, new_location);
-                            if !completed_tiles.contains(bordering_tile_id)
-                                && !tiles_to_move.contains(&(*bordering_tile_id, new_location))
-                            {
-                                tiles_to_move.push((*bordering_tile_id, new_location));
+            bordering_tiles.iter().for_each(|bordering_tile_id| {
+                if !completed_tiles.contains(bordering_tile_id) {
+                    let mut placed_bordering_tile = false;
+                    while !placed_bordering_tile {
+                        let bordering_tile_location =
+                            tiles.get(bordering_tile_id).unwrap().location;
+
+                        match place_tile_next_to(
+                            tile_id_to_move,
+                            *bordering_tile_id,
+                            &tiles,
+                            block_size,
+                        ) {
+                            Some(new_location) => {
+                                switch_tiles(&mut tiles, bordering_tile_location, new_location);
+                                if !completed_tiles.contains(bordering_tile_id)
+                                    && !tiles_to_move.contains(&(*bordering_tile_id, new_location))
+                                {
+                                    tiles_to_move.push((*bordering_tile_id, new_location));
+                                }
+                                placed_bordering_tile = true;
+                            }
+                            None => {
+                                trace!(
+                                    "!!!!!!!!!!!!!!1 {}: OutOfBounds, rotating",
+                                    tile_id_to_move
+                                );
+                                tiles.get_mut(&tile_id_to_move).unwrap().rotate();
                             }
-                            placed_bordering_tile = true;
-                        }
-                        None => {
-                            println!("!!!!!!!!!!!!!!1 {}: OutOfBounds, rotating", tile_id_to_move);
-                            tiles.get_mut(&tile_id_to_move).unwrap().rotate();
                         }
+                        animation.frame(|| draw_tiles(&tiles, block_size, tile_border));
                     }
-                    if enable_visualization {
-                        frame += 1;
-                        visualize(&tiles, block_size, tile_border, frame)
-                    }
-                }
 
-                while !is_placed_correctly(tile_id_to_move, *bordering_tile_id, &mut tiles) {
-                    println!("{}: Misplaced, frame: {}", bordering_tile_id, frame);
-                    match needs_to_flip(tile_id_to_move, *bordering_tile_id, &mut tiles) {
-                        Some(direction) => match direction {
-                            Location::Top | Location::Bottom => {
-                                println!("flipping horizontal");
-                                tiles.get_mut(bordering_tile_id).unwrap().flip_horizontal();
-                            }
-                            Location::Left | Location::Right => {
-                                println!("flipping vertical");
-                                tiles.get_mut(bordering_tile_id).unwrap().flip_vertical();
+                    while !is_placed_correctly(tile_id_to_move, *bordering_tile_id, &tiles) {
+                        trace!("{}: Misplaced", bordering_tile_id);
+                        match needs_to_flip(tile_id_to_move, *bordering_tile_id, &tiles) {
+                            Some(direction) => match direction {
+                                Location::Top | Location::Bottom => {
+                                    trace!("flipping horizontal");
+                                    tiles.get_mut(bordering_tile_id).unwrap().flip_horizontal();
+                                }
+                                Location::Left | Location::Right => {
+                                    trace!("flipping vertical");
+                                    tiles.get_mut(bordering_tile_id).unwrap().flip_vertical();
+                                }
+                            },
+                            None => {
+                                trace!("rotating");
+                                tiles.get_mut(bordering_tile_id).unwrap().rotate();
                             }
-                        },
-                        None => {
-                            println!("rotating");
-                            tiles.get_mut(bordering_tile_id).unwrap().rotate();
                         }
-                    }
 
-                    if enable_visualization {
-                        frame += 1;
-                        visualize(&tiles, block_size, tile_border, frame)
+                        animation.frame(|| draw_tiles(&tiles, block_size, tile_border));
                     }
                 }
-            }
+            });
+            completed_tiles.push(tile_id_to_move);
+        }
+
+        animation.frame(|| draw_tiles(&tiles, block_size, tile_border));
+
+        remove_borders(&mut tiles);
+
+        for remove_border in 0..2 {
+            animation.frame(|| draw_tiles(&tiles, block_size, tile_border - remove_border));
+        }
+
+        let mut all_pixels = Grid::<bool>::sparse(Bounds::default());
+        tiles.iter().for_each(|(_, tile)| {
+            tile.pixels.iter().for_each(|((x, y), _)| {
+                all_pixels.insert(
+                    (
+                        (tile.location.0 * tile.dimension as i32 + x),
+                        (tile.location.1 * tile.dimension as i32 + y),
+                    ),
+                    true,
+                );
+            })
         });
-        completed_tiles.push(tile_id_to_move);
-    }
 
-    frame += 1;
-    visualize(&tiles, block_size, tile_border, frame);
+        let pixel_max_x = all_pixels.bounds().max.0 - 1;
 
-    remove_borders(&mut tiles);
+        debug!("Visualizing full map (width: {})", pixel_max_x);
+        animation.frame(|| draw_full_map(&all_pixels, pixel_max_x as usize));
 
-    for remove_border in 0..2 {
-        frame += 1;
-        visualize(&tiles, block_size, tile_border - remove_border, frame);
-    }
+        let seamonster = "
+    ..................#..
+    #....##....##....###.
+    .#..#..#..#..#..#....";
 
-    let mut all_pixels = Vec::<Coordinate>::new();
-    tiles.iter().for_each(|(_, tile)| {
-        tile.pixels.iter().for_each(|&(x, y)| {
-            all_pixels.push((
-                (tile.location.0 * tile.dimension as i32 + x),
-                (tile.location.1 * tile.dimension as i32 + y),
-            ));
-        })
-    });
+        let mut pattern = Vec::<Coordinate>::new();
 
-    let pixel_max_x = all_pixels
-        .iter()
-        .fold(0, |max, &(x, _)| if x > max { x } else { max });
-    let pixel_max_y = all_pixels
-        .iter()
-        .fold(0, |max, &(_, y)| if y > max { y } else { max });
-
-    frame += 1;
-    println!(
-        "Visualizing full map on frame {}, (width: {})",
-        frame, pixel_max_x
-    );
-    visualize_full_map(&all_pixels, pixel_max_x as usize, frame);
-
-    let seamonster = "
-..................#..
-#....##....##....###.
-.#..#..#..#..#..#....";
-
-    let mut pattern = Vec::<Coordinate>::new();
-
-    seamonster.lines().skip(1).enumerate().for_each(|(y, row)| {
-        row.chars().enumerate().for_each(|(x, value)| {
-            let coord = (x as i32, y as i32);
-            if value != '.' {
-                pattern.push(coord);
-            }
+        seamonster.lines().skip(1).enumerate().for_each(|(y, row)| {
+            row.chars().enumerate().for_each(|(x, value)| {
+                let coord = (x as i32, y as i32);
+                if value != '.' {
+                    pattern.push(coord);
+                }
+            });
         });
-    });
 
-    fn find_pattern(all_pixels: &Vec<Coordinate>, pattern: &Vec<Coordinate>) -> Vec<Coordinate> {
-        let mut patterns_found = Vec::<Coordinate>::new();
-        let pixel_max_x = all_pixels
-            .iter()
-            .fold(0, |max, &(x, _)| if x > max { x } else { max });
-        let pixel_max_y = all_pixels
-            .iter()
-            .fold(0, |max, &(_, y)| if y > max { y } else { max });
+        fn find_pattern(all_pixels: &Grid<bool>, pattern: &[Coordinate]) -> Vec<Coordinate> {
+            let mut patterns_found = Vec::<Coordinate>::new();
+            let Bounds { min, max } = all_pixels.bounds();
 
-        for y in 0..pixel_max_y {
-            for x in 0..pixel_max_x {
-                let check_coord = (x, y);
+            for y in min.1..max.1 {
+                for x in min.0..max.0 {
+                    let check_coord = (x, y);
 
-                let mut subpattern_found = Vec::<Coordinate>::new();
+                    let mut subpattern_found = Vec::<Coordinate>::new();
 
-                if pattern.iter().all(|(pattern_x, pattern_y)| {
-                    let pattern_coord = (x + pattern_x, y + pattern_y);
-                    if all_pixels.contains(&pattern_coord) {
-                        subpattern_found.push(pattern_coord);
-                        true
-                    } else {
-                        false
+                    if pattern.iter().all(|(pattern_x, pattern_y)| {
+                        let pattern_coord = (x + pattern_x, y + pattern_y);
+                        if all_pixels.contains(pattern_coord) {
+                            subpattern_found.push(pattern_coord);
+                            true
+                        } else {
+                            false
+                        }
+                    }) {
+                        debug!("Seamonster found at: {:?}", check_coord);
+                        patterns_found.extend(subpattern_found);
                     }
-                }) {
-                    println!("Seamonster found at: {:?}", check_coord);
-                    patterns_found.extend(subpattern_found);
                 }
             }
+            patterns_found
         }
-        patterns_found
-    }
 
-    let mut found_patterns = find_pattern(&all_pixels, &pattern);
+        let mut found_patterns = find_pattern(&all_pixels, &pattern);
 
-    while found_patterns.len() == 0 {
-        println!("Rotating");
-        all_pixels = all_pixels
-            .iter()
-            .map(|(x, y)| (pixel_max_x - 1 * *y as i32, *x as i32))
-            .collect::<Vec<Coordinate>>();
+        while found_patterns.is_empty() {
+            trace!("Rotating");
+            all_pixels.rotate();
 
-        frame += 1;
-        visualize_full_map(&all_pixels, pixel_max_x as usize, frame);
-        found_patterns = find_pattern(&all_pixels, &pattern);
-    }
+            animation.frame(|| draw_full_map(&all_pixels, pixel_max_x as usize));
+            found_patterns = find_pattern(&all_pixels, &pattern);
+        }
 
-    all_pixels
-        .iter()
-        .filter(|coord| !found_patterns.contains(coord))
-        .count()
+        if let Err(error) = animation.finish() {
+            info!("day20: could not write the animation: {}", error);
+        }
+
+        all_pixels
+            .iter()
+            .filter(|(coord, _)| !found_patterns.contains(coord))
+            .count()
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()),);
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day20 {
+    /// A square of `size` by `size` tiles, 2 to 12 a side, cut from a random
+    /// image and each rotated or flipped at random.
+    ///
+    /// Neighbouring tiles share their border, and every border is unique
+    /// even when reversed, so each tile only fits next to its neighbours.
+    /// Ten-pixel borders run out of unique patterns much beyond 12 tiles a
+    /// side.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const TILE: usize = 10;
+
+        let size = size.clamp(2, 12);
+        let canonical = |border: u16| {
+            let reversed = border.reverse_bits() >> (16 - TILE);
+            border.min(reversed)
+        };
+
+        // Corner pixels are shared by up to four tiles; a border's middle is
+        // redrawn until the whole border is unique
+        let (horizontal, vertical) = 'image: loop {
+            let corners = (0..=size)
+                .map(|_| (0..=size).map(|_| rng.gen::<bool>() as u16).collect())
+                .collect::<Vec<Vec<u16>>>();
+            let mut seen = HashSet::new();
+            let mut border = |rng: &mut StdRng, first: u16, last: u16| {
+                (0..1000)
+                    .map(|_| first << (TILE - 1) | rng.gen_range(0, 256) << 1 | last)
+                    .find(|&border| seen.insert(canonical(border)))
+            };
+
+            // horizontal[row][column] runs along the top of a tile and
+            // vertical[row][column] down its left side, in pixel order
+            let mut horizontal = vec![vec![0; size]; size + 1];
+            let mut vertical = vec![vec![0; size + 1]; size];
+            for row in 0..=size {
+                for column in 0..size {
+                    match border(rng, corners[row][column], corners[row][column + 1]) {
+                        Some(pixels) => horizontal[row][column] = pixels,
+                        None => continue 'image,
+                    }
+                }
+            }
+            for row in 0..size {
+                for column in 0..=size {
+                    match border(rng, corners[row][column], corners[row + 1][column]) {
+                        Some(pixels) => vertical[row][column] = pixels,
+                        None => continue 'image,
+                    }
+                }
+            }
+            break (horizontal, vertical);
+        };
+
+        let ids = rand::seq::index::sample(rng, 9000, size * size);
+        let mut tiles = (0..size * size)
+            .map(|index| {
+                let (row, column) = (index / size, index % size);
+                let pixel = |border: u16, offset: usize| border >> (TILE - 1 - offset) & 1 == 1;
+                let cells = (0..TILE * TILE)
+                    .map(|cell| match (cell % TILE, cell / TILE) {
+                        (x, 0) => pixel(horizontal[row][column], x),
+                        (x, y) if y == TILE - 1 => pixel(horizontal[row + 1][column], x),
+                        (0, y) => pixel(vertical[row][column], y),
+                        (x, y) if x == TILE - 1 => pixel(vertical[row][column + 1], y),
+                        _ => rng.gen(),
+                    })
+                    .collect();
+
+                let mut pixels = Grid::dense(TILE as i32, TILE as i32, cells);
+                for _ in 0..rng.gen_range(0, 4) {
+                    pixels.rotate();
+                }
+                if rng.gen() {
+                    pixels.flip_horizontal();
+                }
+                format!(
+                    "Tile {}:\n{}",
+                    1000 + ids.index(index),
+                    pixels.render(|lit| if lit == Some(&true) { '#' } else { '.' })
+                )
+            })
+            .collect::<Vec<String>>();
+        tiles.shuffle(rng);
+        tiles.join("\n")
+    }
 }
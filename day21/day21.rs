@@ -1,63 +1,42 @@
+use crate::generate::Generator;
+use crate::{debug, trace, ParseError, Solution};
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use regex::Regex;
-use std::collections::HashMap;
-use std::env;
+use std::collections::{BTreeSet, HashMap};
 
-type Range = (usize, usize);
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+#[derive(Debug, Clone)]
+pub struct Food {
+    pub ingredients: Vec<String>,
+    pub allergens: Vec<String>,
+}
 
-    let rule_pattern = Regex::new(r"(?P<food_words>.+)\s\(contains (?P<allergens>.+)\)$").unwrap();
+pub struct Day21;
 
+fn resolve_allergens(foods: &[Food]) -> HashMap<String, String> {
     let mut allergens_map = HashMap::<String, HashMap<String, i32>>::new();
     let mut allergens_count = HashMap::<String, i32>::new();
-    let mut all_ingredients = Vec::<String>::new();
-
-    contents.lines().for_each(|line| {
-        let parsed = rule_pattern.captures(line).unwrap();
-        let food_words = parsed["food_words"]
-            .split(" ")
-            .map(|entry| entry.to_string())
-            .collect::<Vec<String>>();
 
-        let allergens = parsed["allergens"]
-            .split(", ")
-            .map(|entry| entry.to_string())
-            .collect::<Vec<String>>();
+    foods.iter().for_each(|food| {
+        let food_words = &food.ingredients;
 
-        let num_allergens = allergens.len();
-
-        allergens.iter().for_each(|allergen| {
-            println!("{}: in {:?}", allergen, food_words);
+        food.allergens.iter().for_each(|allergen| {
+            trace!("{}: in {:?}", allergen, food_words);
             allergens_count
                 .entry(allergen.to_string())
                 .and_modify(|entry| *entry += 1)
                 .or_insert(1);
 
-            allergens_map
-                .entry(allergen.to_string())
-                .and_modify(|entry| {
-                    food_words.iter().for_each(|food_word| {
-                        entry
-                            .entry(food_word.to_string())
-                            .and_modify(|entry| *entry += 1)
-                            .or_insert(1);
-                    })
-                })
-                .or_insert({
-                    let mut food_word_count = HashMap::<String, i32>::new();
-                    food_words.iter().for_each(|food_word| {
-                        food_word_count
-                            .entry(food_word.to_string())
-                            .and_modify(|entry| *entry += 1)
-                            .or_insert(1);
-                    });
-                    food_word_count
-                });
+            let food_word_count = allergens_map.entry(allergen.to_string()).or_default();
+            food_words.iter().for_each(|food_word| {
+                food_word_count
+                    .entry(food_word.to_string())
+                    .and_modify(|entry| *entry += 1)
+                    .or_insert(1);
+            });
         });
-        all_ingredients.extend(food_words);
     });
 
     let mut known_allergens = HashMap::<String, String>::new();
@@ -77,38 +56,181 @@ fn solve_part1(inputfile: String) -> usize {
                 .collect::<Vec<String>>();
 
             if candidates.len() == 1 {
-                println!("Resolved: {} to {:?}", allergen, candidates);
+                trace!("Resolved: {} to {:?}", allergen, candidates);
                 candidates.iter().for_each(|candidate| {
                     known_allergens
                         .entry(candidate.to_string())
-                        .or_insert(allergen.to_string());
+                        .or_insert_with(|| allergen.to_string());
                 });
             }
         });
     }
 
-    println!("allergens: {:#?}", allergens_map);
-    println!("allergens_count: {:#?}", allergens_count);
-    println!("known_allergens: {:#?}", known_allergens);
-
-    println!("part2: ");
-    for (allergen, ingredient) in known_allergens
-        .iter()
-        .map(|(ingredient, allergen)| (allergen, ingredient))
-        .sorted()
-    {
-        print!("{},", ingredient);
+    debug!("allergens: {:#?}", allergens_map);
+    debug!("allergens_count: {:#?}", allergens_count);
+    debug!("known_allergens: {:#?}", known_allergens);
+
+    known_allergens
+}
+
+impl Solution for Day21 {
+    const DAY: usize = 21;
+
+    type Input = Vec<Food>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(contents: &str) -> Result<Vec<Food>, ParseError> {
+        let rule_pattern =
+            Regex::new(r"(?P<food_words>.+)\s\(contains (?P<allergens>.+)\)$").unwrap();
+
+        contents
+            .lines()
+            .map(|line| {
+                let parsed = rule_pattern.captures(line).ok_or_else(|| {
+                    ParseError::at(
+                        contents,
+                        line,
+                        "expected `<ingredients> (contains <allergens>)`",
+                    )
+                })?;
+                Ok(Food {
+                    ingredients: parsed["food_words"]
+                        .split(' ')
+                        .map(|entry| entry.to_string())
+                        .collect::<Vec<String>>(),
+                    allergens: parsed["allergens"]
+                        .split(", ")
+                        .map(|entry| entry.to_string())
+                        .collect::<Vec<String>>(),
+                })
+            })
+            .collect()
     }
-    println!();
 
-    all_ingredients
-        .iter()
-        .filter(|ingredient| !known_allergens.contains_key(*ingredient))
-        .count()
+    fn part1(foods: &Vec<Food>) -> usize {
+        let known_allergens = resolve_allergens(foods);
+
+        foods
+            .iter()
+            .flat_map(|food| food.ingredients.iter())
+            .filter(|ingredient| !known_allergens.contains_key(*ingredient))
+            .count()
+    }
+
+    fn part2(foods: &Vec<Food>) -> String {
+        let known_allergens = resolve_allergens(foods);
+
+        known_allergens
+            .iter()
+            .map(|(ingredient, allergen)| (allergen, ingredient))
+            .sorted()
+            .map(|(_, ingredient)| ingredient)
+            .join(",")
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    //println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day21 {
+    /// `size` foods, at least three, with up to eight allergens.
+    ///
+    /// Foods list some of the allergens in them and may contain others
+    /// unlisted. The foods are drawn again until the allergens can be told
+    /// apart the way the solver does, one allergen with a single candidate
+    /// ingredient at a time.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const ALLERGENS: [&str; 8] = [
+            "dairy",
+            "eggs",
+            "fish",
+            "nuts",
+            "peanuts",
+            "sesame",
+            "shellfish",
+            "soy",
+        ];
+
+        let size = size.max(3);
+        let allergens = (size / 3).clamp(1, ALLERGENS.len());
+
+        let mut words = BTreeSet::new();
+        while words.len() < 200 {
+            let length = rng.gen_range(3, 8);
+            words.insert(
+                (0..length)
+                    .map(|_| rng.gen_range(b'a', b'z' + 1) as char)
+                    .collect::<String>(),
+            );
+        }
+        let mut words = words.into_iter().collect::<Vec<String>>();
+        words.shuffle(rng);
+        // Ingredient `index` contains allergen `index` for the first few
+        let safe = allergens..words.len();
+
+        let foods = loop {
+            let foods = (0..size)
+                .map(|food| {
+                    let count = rng.gen_range(1, allergens.min(3) + 1);
+                    let mut listed = rand::seq::index::sample(rng, allergens, count).into_vec();
+                    if food < allergens && !listed.contains(&food) {
+                        listed[0] = food;
+                    }
+                    listed.sort_unstable();
+
+                    let mut ingredients = (0..allergens)
+                        .filter(|allergen| listed.contains(allergen) || rng.gen_bool(0.3))
+                        .collect::<Vec<usize>>();
+                    let count = rng.gen_range(10, 31);
+                    ingredients.extend(
+                        rand::seq::index::sample(rng, safe.len(), count)
+                            .iter()
+                            .map(|index| safe.start + index),
+                    );
+                    ingredients.shuffle(rng);
+                    (ingredients, listed)
+                })
+                .collect::<Vec<(Vec<usize>, Vec<usize>)>>();
+
+            let mut candidates = (0..allergens)
+                .map(|allergen| {
+                    foods
+                        .iter()
+                        .filter(|(_, listed)| listed.contains(&allergen))
+                        .map(|(ingredients, _)| {
+                            ingredients.iter().copied().collect::<BTreeSet<usize>>()
+                        })
+                        .fold1(|common, ingredients| &common & &ingredients)
+                        .unwrap()
+                })
+                .collect::<Vec<BTreeSet<usize>>>();
+            let mut resolved = BTreeSet::new();
+            while let Some(allergen) = (0..allergens)
+                .find(|&allergen| !resolved.contains(&allergen) && candidates[allergen].len() == 1)
+            {
+                let ingredient = *candidates[allergen].iter().next().unwrap();
+                resolved.insert(allergen);
+                for (other, others) in candidates.iter_mut().enumerate() {
+                    if other != allergen {
+                        others.remove(&ingredient);
+                    }
+                }
+            }
+            if resolved.len() == allergens {
+                break foods;
+            }
+        };
+
+        foods
+            .iter()
+            .map(|(ingredients, listed)| {
+                format!(
+                    "{} (contains {})\n",
+                    ingredients.iter().map(|&index| &words[index]).join(" "),
+                    listed
+                        .iter()
+                        .map(|&allergen| ALLERGENS[allergen])
+                        .join(", ")
+                )
+            })
+            .collect()
+    }
 }
@@ -1,144 +1,162 @@
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::{debug, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use std::collections::HashSet;
 use std::collections::VecDeque;
-use std::env;
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+pub type Decks = (VecDeque<usize>, VecDeque<usize>);
 
-    let mut player_cards = contents
-        .split("\n\n")
-        .map(|block| {
-            let mut cards = VecDeque::<usize>::new();
-            block
-                .lines()
-                .skip(1)
-                .for_each(|line| cards.push_back(line.parse::<usize>().unwrap()));
+pub struct Day22;
 
-            cards
-        })
-        .collect::<Vec<VecDeque<usize>>>();
+fn score(deck: &VecDeque<usize>) -> usize {
+    let deck_size = deck.len();
 
-    println!("players: {:?}", player_cards);
+    deck.iter()
+        .enumerate()
+        .fold(0, |sum, (index, card)| sum + card * (deck_size - index))
+}
 
-    while player_cards[0].len() != 0 && player_cards[1].len() != 0 {
-        let player1 = player_cards[0].pop_front().unwrap();
-        let player2 = player_cards[1].pop_front().unwrap();
-        if player1 > player2 {
-            player_cards[0].push_back(player1);
-            player_cards[0].push_back(player2);
-        } else {
-            player_cards[1].push_back(player2);
-            player_cards[1].push_back(player1);
+fn recursive_combat(decks: &mut Decks) -> bool {
+    let mut previous_decks_1 = HashSet::<Vec<usize>>::new();
+    let mut previous_decks_2 = HashSet::<Vec<usize>>::new();
+    let mut player1_won = true;
+
+    while !decks.0.is_empty() && !decks.1.is_empty() {
+        let deck_1_vec = decks.0.iter().copied().collect::<Vec<usize>>();
+        let deck_2_vec = decks.1.iter().copied().collect::<Vec<usize>>();
+
+        if previous_decks_1.contains(&deck_1_vec) || previous_decks_2.contains(&deck_2_vec) {
+            return true;
         }
-    }
-    println!("player1: {:?}", player_cards[0]);
-    println!("player2: {:?}", player_cards[1]);
-    player_cards
-        .iter()
-        .map(|deck| {
-            let deck_size = deck.len();
-
-            deck.iter()
-                .enumerate()
-                .fold(0, |sum, (index, card)| sum + card * (deck_size - index))
-        })
-        .fold(0, |max, result| if result > max { result } else { max })
-}
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+        let cards_key = (deck_1_vec, deck_2_vec);
+        let drawn_cards = (decks.0.pop_front().unwrap(), decks.1.pop_front().unwrap());
+        let deck_sizes = (decks.0.len(), decks.1.len());
+
+        let won_hand = if deck_sizes.0 >= drawn_cards.0 && deck_sizes.1 >= drawn_cards.1 {
+            recursive_combat(&mut (
+                decks
+                    .0
+                    .iter()
+                    .take(drawn_cards.0)
+                    .copied()
+                    .collect::<VecDeque<usize>>(),
+                decks
+                    .1
+                    .iter()
+                    .take(drawn_cards.1)
+                    .copied()
+                    .collect::<VecDeque<usize>>(),
+            ))
+        } else {
+            drawn_cards.0 > drawn_cards.1
+        };
 
-    let player_cards = contents
-        .split("\n\n")
-        .map(|block| {
-            let mut cards = VecDeque::<usize>::new();
-            block
-                .lines()
-                .skip(1)
-                .for_each(|line| cards.push_back(line.parse::<usize>().unwrap()));
+        if won_hand {
+            decks.0.push_back(drawn_cards.0);
+            decks.0.push_back(drawn_cards.1);
+        } else {
+            decks.1.push_back(drawn_cards.1);
+            decks.1.push_back(drawn_cards.0);
+        }
 
-            cards
-        })
-        .collect::<Vec<VecDeque<usize>>>();
+        if decks.0.is_empty() {
+            player1_won = false;
+        }
+        if decks.1.is_empty() {
+            player1_won = true;
+        }
 
-    println!("players: {:?}", player_cards);
+        previous_decks_1.insert(cards_key.0);
+        previous_decks_2.insert(cards_key.1);
+    }
 
-    fn recursive_combat(decks: &mut (VecDeque<usize>, VecDeque<usize>), depth: usize) -> bool {
-        let mut previous_decks_1 = HashSet::<Vec<usize>>::new();
-        let mut previous_decks_2 = HashSet::<Vec<usize>>::new();
-        let mut player1_won = true;
+    player1_won
+}
 
-        while decks.0.len() != 0 && decks.1.len() != 0 {
-            let deck_1_vec = decks.0.iter().map(|&e| e).collect::<Vec<usize>>();
-            let deck_2_vec = decks.1.iter().map(|&e| e).collect::<Vec<usize>>();
+impl Solution for Day22 {
+    const DAY: usize = 22;
+
+    type Input = Decks;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Decks, ParseError> {
+        let player_cards = contents
+            .split("\n\n")
+            .map(|block| {
+                block
+                    .lines()
+                    .skip(1)
+                    .map(|line| parse_at(contents, line))
+                    .collect::<Result<VecDeque<usize>, ParseError>>()
+            })
+            .collect::<Result<Vec<VecDeque<usize>>, ParseError>>()?;
+        if player_cards.len() != 2 {
+            return Err(ParseError::at(
+                contents,
+                &contents[contents.len()..],
+                "expected the decks of two players",
+            ));
+        }
 
-            if previous_decks_1.contains(&deck_1_vec) || previous_decks_2.contains(&deck_2_vec) {
-                return true;
-            }
+        debug!("players: {:?}", player_cards);
 
-            let cards_key = (deck_1_vec, deck_2_vec);
-            let drawn_cards = (decks.0.pop_front().unwrap(), decks.1.pop_front().unwrap());
-            let deck_sizes = (decks.0.len(), decks.1.len());
-
-            let won_hand = if deck_sizes.0 >= drawn_cards.0 && deck_sizes.1 >= drawn_cards.1 {
-                recursive_combat(
-                    &mut (
-                        decks
-                            .0
-                            .iter()
-                            .take(drawn_cards.0)
-                            .map(|e| *e)
-                            .collect::<VecDeque<usize>>(),
-                        decks
-                            .1
-                            .iter()
-                            .take(drawn_cards.1)
-                            .map(|e| *e)
-                            .collect::<VecDeque<usize>>(),
-                    ),
-                    depth + 1,
-                )
-            } else {
-                drawn_cards.0 > drawn_cards.1
-            };
+        Ok((player_cards[0].clone(), player_cards[1].clone()))
+    }
 
-            if won_hand {
-                decks.0.push_back(drawn_cards.0);
-                decks.0.push_back(drawn_cards.1);
-            } else {
-                decks.1.push_back(drawn_cards.1);
-                decks.1.push_back(drawn_cards.0);
-            }
+    fn part1(decks: &Decks) -> usize {
+        let mut player_cards = [decks.0.clone(), decks.1.clone()];
 
-            if decks.0.len() == 0 {
-                player1_won = false;
-            }
-            if decks.1.len() == 0 {
-                player1_won = true;
+        while !player_cards[0].is_empty() && !player_cards[1].is_empty() {
+            let player1 = player_cards[0].pop_front().unwrap();
+            let player2 = player_cards[1].pop_front().unwrap();
+            if player1 > player2 {
+                player_cards[0].push_back(player1);
+                player_cards[0].push_back(player2);
+            } else {
+                player_cards[1].push_back(player2);
+                player_cards[1].push_back(player1);
             }
-
-            previous_decks_1.insert(cards_key.0);
-            previous_decks_2.insert(cards_key.1);
         }
-
-        player1_won
+        debug!("player1: {:?}", player_cards[0]);
+        debug!("player2: {:?}", player_cards[1]);
+        player_cards
+            .iter()
+            .map(score)
+            .fold(0, |max, result| if result > max { result } else { max })
     }
 
-    let mut decks = (player_cards[0].clone(), player_cards[1].clone());
-    let player1_won = recursive_combat(&mut decks, 0);
+    fn part2(decks: &Decks) -> usize {
+        let mut decks = decks.clone();
+        let player1_won = recursive_combat(&mut decks);
 
-    let winner_deck = if player1_won { decks.0 } else { decks.1 };
-    let deck_size = winner_deck.len();
-    winner_deck
-        .iter()
-        .enumerate()
-        .fold(0, |sum, (index, card)| sum + card * (deck_size - index))
+        let winner_deck = if player1_won { decks.0 } else { decks.1 };
+        score(&winner_deck)
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day22 {
+    /// Two decks of `size` cards each, from 1 to 25 like the puzzle's,
+    /// dealt from a shuffled deck numbered from 1. Recursive combat with
+    /// much larger decks can play for minutes.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let size = size.clamp(1, 25);
+        let mut cards = (1..=2 * size).collect::<Vec<usize>>();
+        cards.shuffle(rng);
+
+        let deck = |cards: &[usize]| {
+            cards
+                .iter()
+                .map(|card| format!("{}\n", card))
+                .collect::<String>()
+        };
+        format!(
+            "Player 1:\n{}\nPlayer 2:\n{}",
+            deck(&cards[..size]),
+            deck(&cards[size..])
+        )
+    }
 }
@@ -1,17 +1,19 @@
+use crate::generate::Generator;
+use crate::{debug, trace, ParseError, Solution};
 use itertools::Itertools;
-use std::collections::VecDeque;
-use std::env;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 
-fn print_cups(cups: &Vec<usize>, current_cup: &usize, print_length: usize) {
-    print!("cups: ");
+fn format_cups(cups: &[usize], current_cup: &usize, print_length: usize) -> String {
+    let mut formatted = String::from("cups: ");
     let mut printed_values = 0;
     let mut cursor = current_cup;
-    for index in 1..cups.len() {
+    for _ in 1..cups.len() {
         let cup = cursor;
         if cup == current_cup {
-            print!("({})", cup);
+            formatted.push_str(&format!("({})", cup));
         } else {
-            print!(" {} ", cup);
+            formatted.push_str(&format!(" {} ", cup));
         }
         cursor = &cups[*cursor];
 
@@ -20,11 +22,11 @@ fn print_cups(cups: &Vec<usize>, current_cup: &usize, print_length: usize) {
             break;
         }
     }
-    println!();
+    formatted
 }
 
-fn make_move(cups: &mut Vec<usize>, current_cup: &mut usize) {
-    let taken_cups = vec![
+fn make_move(cups: &mut [usize], current_cup: &mut usize) {
+    let taken_cups = [
         cups[*current_cup],
         cups[cups[*current_cup]],
         cups[cups[cups[*current_cup]]],
@@ -47,126 +49,105 @@ fn make_move(cups: &mut Vec<usize>, current_cup: &mut usize) {
     *current_cup = next_cup;
 }
 
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut current_cup: usize = 0;
-
-    let pairs = contents
-        .chars()
-        .enumerate()
-        .peekable()
-        .batching(|it| match it.next() {
-            None => None,
-            Some((index, c)) => {
-                if c.is_numeric() {
-                    let digit = c.to_digit(10).unwrap() as usize;
-
-                    if current_cup == 0 {
-                        current_cup = digit;
-                    }
-
-                    Some(match it.peek() {
-                        Some((index, next_c)) => {
-                            let next_digit = if next_c.is_numeric() {
-                                next_c.to_digit(10).unwrap() as usize
-                            } else {
-                                current_cup
-                            };
-                            (digit, next_digit)
-                        }
-                        None => (digit, current_cup),
-                    })
-                } else {
-                    None
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: usize = 23;
+
+    type Input = Vec<usize>;
+    type Part1 = String;
+    type Part2 = usize;
+
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        let mut labels = Vec::new();
+        for (index, c) in contents.trim_end().char_indices() {
+            let text = &contents[index..index + c.len_utf8()];
+            let label = match c.to_digit(10) {
+                Some(label) if label > 0 => label as usize,
+                _ => {
+                    return Err(ParseError::at(
+                        contents,
+                        text,
+                        "expected a cup label `1` to `9`",
+                    ))
                 }
+            };
+            if labels.contains(&label) {
+                return Err(ParseError::at(contents, text, "cup label repeated"));
             }
-        })
-        .collect::<Vec<(usize, usize)>>();
-
-    let mut cups = (0..(pairs.len() + 1)).collect::<Vec<usize>>();
-    println!("pairs: {:?}", pairs);
-    println!("cups: {:?}", cups);
-    pairs
-        .iter()
-        .for_each(|(value, next_value)| cups[*value] = *next_value);
-    println!("cups: {:?}", cups);
-
-    for move_count in 0..100 {
-        println!("-- move {} --", move_count + 1);
-        //print_cups(&cups, &current_cup);
-        make_move(&mut cups, &mut current_cup);
+            labels.push(label);
+        }
+        if labels.len() < 9 {
+            return Err(ParseError::at(
+                contents,
+                &contents[contents.len()..],
+                "expected nine cups, labelled `1` to `9`",
+            ));
+        }
+        Ok(labels)
     }
 
-    println!("-- final --");
-    print_cups(&cups, &1, 10);
-    0
-}
+    fn part1(labels: &Vec<usize>) -> String {
+        // cups[label] holds the label of the cup clockwise of it
+        let mut cups = (0..(labels.len() + 1)).collect::<Vec<usize>>();
+        labels
+            .iter()
+            .enumerate()
+            .for_each(|(index, value)| cups[*value] = labels[(index + 1) % labels.len()]);
+        debug!("cups: {:?}", cups);
+
+        let mut current_cup = labels[0];
+        for move_count in 0..100 {
+            trace!("-- move {} --", move_count + 1);
+            make_move(&mut cups, &mut current_cup);
+        }
 
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut current_cup: usize = 0;
-
-    let pairs = contents
-        .chars()
-        .enumerate()
-        .peekable()
-        .batching(|it| match it.next() {
-            None => None,
-            Some((index, c)) => {
-                if c.is_numeric() {
-                    let digit = c.to_digit(10).unwrap() as usize;
-
-                    if current_cup == 0 {
-                        current_cup = digit;
-                    }
-
-                    Some(match it.peek() {
-                        Some((index, next_c)) => {
-                            let next_digit = if next_c.is_numeric() {
-                                next_c.to_digit(10).unwrap() as usize
-                            } else {
-                                10
-                            };
-                            (digit, next_digit)
-                        }
-                        None => (digit, 10),
-                    })
-                } else {
-                    None
-                }
-            }
-        })
-        .collect::<Vec<(usize, usize)>>();
-
-    let mut cups = (0..1000001)
-        .map(|value| value + 1 as usize)
-        .collect::<Vec<usize>>();
-    let last_value = cups.len() - 1;
-    cups[last_value] = current_cup.clone();
-
-    pairs
-        .iter()
-        .for_each(|(value, next_value)| cups[*value] = *next_value);
-
-    for move_count in 0..10000000 {
-        if move_count % 500000 == 0 {
-            println!("-- move {} --", move_count + 1);
+        debug!("-- final --");
+        debug!("{}", format_cups(&cups, &1, 10));
+
+        let mut cursor = cups[1];
+        let mut order = String::new();
+        while cursor != 1 {
+            order.push_str(&cursor.to_string());
+            cursor = cups[cursor];
         }
-        //print_cups(&cups, &current_cup);
-        make_move(&mut cups, &mut current_cup);
+        order
     }
 
-    println!("-- final --");
-    print_cups(&cups, &1, 10);
-    0
+    fn part2(labels: &Vec<usize>) -> usize {
+        let mut cups = (0..1000001)
+            .map(|value| value + 1_usize)
+            .collect::<Vec<usize>>();
+        let last_value = cups.len() - 1;
+        cups[last_value] = labels[0];
+
+        labels.iter().enumerate().for_each(|(index, value)| {
+            cups[*value] = match labels.get(index + 1) {
+                Some(next_value) => *next_value,
+                None => labels.len() + 1,
+            }
+        });
+
+        let mut current_cup = labels[0];
+        for move_count in 0..10000000 {
+            if move_count % 500000 == 0 {
+                debug!("-- move {} --", move_count + 1);
+            }
+            make_move(&mut cups, &mut current_cup);
+        }
+
+        debug!("-- final --");
+        debug!("{}", format_cups(&cups, &1, 10));
+        cups[1] * cups[cups[1]]
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day23 {
+    /// The cups 1 to 9 in a random order; labels are single digits, so
+    /// `size` is ignored.
+    fn generate(rng: &mut StdRng, _size: usize) -> String {
+        let mut cups = (1..=9).collect::<Vec<usize>>();
+        cups.shuffle(rng);
+        cups.iter().join("") + "\n"
+    }
 }
@@ -1,76 +1,63 @@
-use image::ImageBuffer;
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::env;
-
-type Color = (u8, u8, u8);
-type Coordinate = (i32, i32);
-
-#[derive(Debug, PartialEq, Eq)]
-enum TileSide {
+use crate::generate::Generator;
+use crate::grid::{Bounds, Coordinate, Grid};
+use crate::render::{Animation, Canvas, WHITE};
+use crate::{debug, info, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Offsets of the six tiles around a tile; a step east or west is two
+/// columns wide so that the diagonal steps land on whole coordinates.
+const NEIGHBOURS: [Coordinate; 6] = [(-2, 0), (2, 0), (-1, -1), (-1, 1), (1, 1), (1, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileSide {
     Black,
     White,
 }
 
-#[derive(Debug)]
-struct Tile {
+#[derive(Debug, Clone)]
+pub struct Tile {
     location: Coordinate,
     color: TileSide,
 }
 
 impl Tile {
-    fn parse(line: &str) -> Tile {
+    fn parse(contents: &str, line: &str) -> Result<Tile, ParseError> {
+        let directions = [
+            ("w", (-2, 0)),
+            ("e", (2, 0)),
+            ("sw", (-1, 1)),
+            ("se", (1, 1)),
+            ("nw", (-1, -1)),
+            ("ne", (1, -1)),
+        ];
+
+        if line.is_empty() {
+            return Err(ParseError::at(contents, line, "expected a path to a tile"));
+        }
+
         let mut current_tile = (0, 0);
-        let path = line
-            .chars()
-            .batching(|it| match it.next() {
-                None => None,
-                Some(c) => match c {
-                    'w' => {
-                        current_tile = (current_tile.0 - 2, current_tile.1);
-                        Some(current_tile)
-                    }
-                    'e' => {
-                        current_tile = (current_tile.0 + 2, current_tile.1);
-                        Some(current_tile)
-                    }
-                    's' => match it.next() {
-                        Some(c2) => match c2 {
-                            'w' => {
-                                current_tile = (current_tile.0 - 1, current_tile.1 + 1);
-                                Some(current_tile)
-                            }
-                            'e' => {
-                                current_tile = (current_tile.0 + 1, current_tile.1 + 1);
-                                Some(current_tile)
-                            }
-                            _ => None,
-                        },
-                        None => None,
-                    },
-                    'n' => match it.next() {
-                        Some(c2) => match c2 {
-                            'w' => {
-                                current_tile = (current_tile.0 - 1, current_tile.1 - 1);
-                                Some(current_tile)
-                            }
-                            'e' => {
-                                current_tile = (current_tile.0 + 1, current_tile.1 - 1);
-                                Some(current_tile)
-                            }
-                            _ => None,
-                        },
-                        None => None,
-                    },
-                    _ => None,
-                },
-            })
-            .collect::<Vec<Coordinate>>();
+        let mut path = line;
+        while let Some(c) = path.chars().next() {
+            let (direction, step) = directions
+                .iter()
+                .find(|(direction, _)| path.starts_with(direction))
+                .ok_or_else(|| {
+                    ParseError::at(
+                        contents,
+                        &path[..c.len_utf8()],
+                        "expected one of `e`, `se`, `sw`, `w`, `nw` or `ne`",
+                    )
+                })?;
+            current_tile = (current_tile.0 + step.0, current_tile.1 + step.1);
+            path = &path[direction.len()..];
+        }
 
-        Tile {
-            location: *path.last().unwrap(),
+        Ok(Tile {
+            location: current_tile,
             color: TileSide::Black,
-        }
+        })
     }
 
     fn flip(&mut self) {
@@ -81,147 +68,111 @@ impl Tile {
         };
     }
 
-    fn draw(&self, pixels: &mut Vec<(Coordinate, Color)>) {
+    fn draw(&self, canvas: &mut Canvas, offset: Coordinate) {
         let inner_white_color = (0x96, 0xA7, 0x74);
         let inner_black_color = (0x32, 0x17, 0x04);
         let border_color = (0x33, 0xCC, 0x66);
-        let missing_color = (0xB3, 0xCC, 0xA5);
 
-        let scale = 4;
-        let tile = [
-            "....%...", //
-            "...%%%..", //
-            "..%%%%%.", //
-            ".%%%%%%%", //
-            ".%%%%%%%", //
-            "..%%%%%.", //
-            "...%%%..", //
-            "....%...", //
-        ];
+        let inner_color = if self.color == TileSide::Black {
+            inner_black_color
+        } else {
+            inner_white_color
+        };
 
-        tile.iter().enumerate().for_each(|(y, line)| {
-            line.chars().enumerate().for_each(|(x, c)| match c {
-                '#' | '%' => {
-                    let pixel_color = match c {
-                        '#' => border_color,
-                        '%' => {
-                            if self.color == TileSide::Black {
-                                inner_black_color
-                            } else {
-                                inner_white_color
-                            }
-                        }
-                        _ => missing_color,
-                    };
-                    pixels.push((
-                        (
-                            (self.location.0 * scale + x as i32),
-                            (self.location.1 * scale + y as i32),
-                        ),
-                        pixel_color,
-                    ));
-                }
-                _ => (),
-            })
-        })
+        canvas.stamp(
+            (
+                offset.0 + self.location.0 * TILE_SPACING,
+                offset.1 + self.location.1 * TILE_SPACING,
+            ),
+            TILE_SPRITE,
+            &[('#', border_color), ('%', inner_color)],
+        );
     }
 }
 
-fn visualize(tiles: &HashMap<Coordinate, Tile>, frame: i32) {
-    let max_dimension: u32 = 10;
-    let scale: u32 = 8;
-    let border: u32 = 2;
-    let mut pixels = Vec::<(Coordinate, Color)>::new();
+/// Distance between neighbouring coordinates on the canvas.
+const TILE_SPACING: i32 = 4;
+
+const TILE_SIZE: i32 = 8;
+
+const TILE_SPRITE: &str = "\
+....%...
+...%%%..
+..%%%%%.
+.%%%%%%%
+.%%%%%%%
+..%%%%%.
+...%%%..
+....%...";
+
+fn draw(tiles: &Floor) -> Canvas {
+    let scale = 8;
+    let border = 2;
+
+    let Bounds { min, max } = tiles.bounds();
+    let size =
+        |min: i32, max: i32| ((max - 1 - min) * TILE_SPACING + TILE_SIZE + border * 2).max(0);
+    let mut canvas = Canvas::new(
+        size(min.0, max.0) as u32,
+        size(min.1, max.1) as u32,
+        scale,
+        WHITE,
+    );
 
-    println!("frame: {}, ", frame);
+    let offset = (border - min.0 * TILE_SPACING, border - min.1 * TILE_SPACING);
+    tiles
+        .values()
+        .for_each(|tile| tile.draw(&mut canvas, offset));
+    canvas
+}
 
-    tiles.values().for_each(|tile| {
-        tile.draw(&mut pixels);
-    });
+pub type Floor = Grid<Tile>;
 
-    let x_min = pixels.iter().map(|(pos, _)| pos.0).min().unwrap();
-    let x_max = pixels.iter().map(|(pos, _)| pos.0).max().unwrap();
-    let y_min = pixels.iter().map(|(pos, _)| pos.1).min().unwrap();
-    let y_max = pixels.iter().map(|(pos, _)| pos.1).max().unwrap();
-    let x_range = (x_max - x_min) as u32;
-    let y_range = (y_max - y_min) as u32;
-    let dimensions = (1 + x_range, 1 + y_range);
+pub struct Day24;
 
-    let real_size = (
-        (dimensions.0 + border * 2) * scale as u32,
-        (dimensions.1 + border * 2) * scale as u32,
-    );
+impl Solution for Day24 {
+    const DAY: usize = 24;
 
-    let mut img = ImageBuffer::from_fn(real_size.0, real_size.1, |_x, _y| {
-        image::Rgb([255, 255, 255])
-    });
+    type Input = Floor;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    for ((raw_x, raw_y), color) in pixels {
-        let pixel = image::Rgb([color.0, color.1, color.2]);
-        let (x, y) = (raw_x - x_min, raw_y - y_min);
-        if x >= 0 && y >= 0 && x < real_size.0 as i32 && y < real_size.1 as i32 {
-            for scaled_y in 0..scale {
-                for scaled_x in 0..scale {
-                    img.put_pixel(
-                        ((x as u32 + border) * scale + scaled_x) as u32,
-                        ((y as u32 + border) * scale + scaled_y) as u32,
-                        pixel,
-                    );
+    fn parse(contents: &str) -> Result<Floor, ParseError> {
+        let mut tiles = Floor::sparse(Bounds::default());
+        for line in contents.lines() {
+            let tile = Tile::parse(contents, line)?;
+            match tiles.get_mut(tile.location) {
+                Some(flipped_tile) => flipped_tile.flip(),
+                None => {
+                    tiles.insert(tile.location, tile);
                 }
             }
         }
+        Ok(tiles)
     }
 
-    img.save(format!("frames/day24.frame{:05}.png", frame))
-        .unwrap();
-}
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let mut tiles = HashMap::<Coordinate, Tile>::new();
-    contents.lines().for_each(|line| {
-        let tile = Tile::parse(line);
+    fn part1(tiles: &Floor) -> usize {
         tiles
-            .entry(tile.location)
-            .and_modify(|e| e.flip())
-            .or_insert(tile);
-    });
-
-    // let mut frame = 0;
-    // visualize(&tiles, frame);
-    tiles.values().fold(0, |sum, tile| {
-        sum + if tile.color == TileSide::Black { 1 } else { 0 }
-    })
-}
-
-fn solve_part2(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
+            .values()
+            .filter(|tile| tile.color == TileSide::Black)
+            .count()
+    }
 
-    let mut tiles = HashMap::<Coordinate, Tile>::new();
-    contents.lines().for_each(|line| {
-        let tile = Tile::parse(line);
-        tiles
-            .entry(tile.location)
-            .and_modify(|e| e.flip())
-            .or_insert(tile);
-    });
+    fn part2(tiles: &Floor) -> usize {
+        let mut tiles = tiles.clone();
+        let mut animation = Animation::new("day24");
 
-    for frame in 0..100 {
-        visualize(&tiles, frame);
-        let mut tiles_to_construct = Vec::<Coordinate>::new();
+        for _ in 0..100 {
+            animation.frame(|| draw(&tiles));
+            let mut tiles_to_construct = Vec::<Coordinate>::new();
 
-        let mut tiles_to_flip = tiles
-            .values()
-            .filter_map(|tile| {
-                if tile.color == TileSide::Black {
-                    let neighbours = [(-2, 0), (2, 0), (-1, -1), (-1, 1), (1, 1), (1, -1)]
-                        .iter()
-                        .fold(0, |sum, coord| {
+            let mut tiles_to_flip = tiles
+                .values()
+                .filter_map(|tile| {
+                    if tile.color == TileSide::Black {
+                        let neighbours = NEIGHBOURS.iter().fold(0, |sum, coord| {
                             let neighbour = (tile.location.0 + coord.0, tile.location.1 + coord.1);
-                            sum + match tiles.get(&neighbour) {
+                            sum + match tiles.get(neighbour) {
                                 Some(adjacent_tile) => {
                                     if adjacent_tile.color == TileSide::Black {
                                         1
@@ -235,65 +186,69 @@ fn solve_part2(inputfile: String) -> usize {
                                 }
                             }
                         });
-                    if neighbours == 0 || neighbours > 2 {
-                        Some(tile.location)
+                        if neighbours == 0 || neighbours > 2 {
+                            Some(tile.location)
+                        } else {
+                            None
+                        }
                     } else {
                         None
                     }
-                } else {
-                    None
+                })
+                .collect::<Vec<Coordinate>>();
+
+            tiles_to_construct.iter().for_each(|location| {
+                tiles.insert(
+                    *location,
+                    Tile {
+                        location: *location,
+                        color: TileSide::White,
+                    },
+                );
+            });
+            tiles.values().for_each(|tile| {
+                if tile.color == TileSide::White {
+                    let neighbours = tiles
+                        .neighbours(tile.location, &NEIGHBOURS)
+                        .filter(|(_, adjacent_tile)| adjacent_tile.color == TileSide::Black)
+                        .count();
+                    if neighbours == 2 {
+                        tiles_to_flip.push(tile.location);
+                    }
                 }
-            })
-            .collect::<Vec<Coordinate>>();
+            });
 
-        tiles_to_construct.iter().for_each(|location| {
-            tiles.insert(
-                *location,
-                Tile {
-                    location: *location,
-                    color: TileSide::White,
-                },
-            );
-        });
-        tiles.values().for_each(|tile| {
-            if tile.color == TileSide::White {
-                let neighbours = [(-2, 0), (2, 0), (-1, -1), (-1, 1), (1, 1), (1, -1)]
-                    .iter()
-                    .fold(0, |sum, coord| {
-                        let neighbour = (tile.location.0 + coord.0, tile.location.1 + coord.1);
-                        sum + match tiles.get(&neighbour) {
-                            Some(adjacent_tile) => {
-                                if adjacent_tile.color == TileSide::Black {
-                                    1
-                                } else {
-                                    0
-                                }
-                            }
-                            None => 0,
-                        }
-                    });
-                if neighbours == 2 {
-                    tiles_to_flip.push(tile.location);
+            tiles_to_flip.iter().for_each(|location| {
+                if let Some(tile) = tiles.get_mut(*location) {
+                    tile.flip();
                 }
-            }
-        });
-
-        tiles_to_flip.iter().for_each(|location| {
-            tiles.entry(*location).and_modify(|e| {
-                e.flip();
             });
+        }
+        let faces_up = tiles.values().fold(0, |sum, tile| {
+            sum + if tile.color == TileSide::Black { 1 } else { 0 }
         });
+        debug!("faces up: {}", faces_up);
+        animation.frame(|| draw(&tiles));
+        if let Err(error) = animation.finish() {
+            info!("day24: could not write the animation: {}", error);
+        }
+        faces_up
     }
-    let faces_up = tiles.values().fold(0, |sum, tile| {
-        sum + if tile.color == TileSide::Black { 1 } else { 0 }
-    });
-    println!("faces up: {}", faces_up);
-    visualize(&tiles, 101);
-    faces_up
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day24 {
+    /// Directions to `size` tiles, each between one and twenty steps away.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        const DIRECTIONS: [&str; 6] = ["e", "se", "sw", "w", "nw", "ne"];
+
+        (0..size)
+            .map(|_| {
+                let steps = rng.gen_range(1, 21);
+                (0..steps)
+                    .map(|_| *DIRECTIONS.choose(rng).unwrap())
+                    .chain(Some("\n"))
+                    .collect::<String>()
+            })
+            .collect()
+    }
 }
@@ -1,100 +1,152 @@
-use itertools::Itertools;
+use crate::error::parse_at;
+use crate::generate::Generator;
+use crate::log::Level;
+use crate::{debug, log, NoAnswer, ParseError, Solution};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::collections::HashMap;
-use std::env;
-
-fn solve_part1(inputfile: String) -> usize {
-    let contents =
-        std::fs::read_to_string(inputfile).expect("Something went wrong reading the file");
-
-    let public_keys = contents
-        .lines()
-        .map(|line| line.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-
-    println!("public_keys: {:?}", public_keys);
-
-    fn get_public_key(
-        cache: &mut HashMap<(usize, usize), usize>,
-        subject_number: usize,
-        loop_size: usize,
-    ) -> usize {
-        let mut key = 1;
-        let mut cached_loop_size = 0;
-        for loop_size_in_cache in (0..loop_size).rev() {
-            match cache.get(&(subject_number, loop_size_in_cache)) {
-                Some(cached_key) => {
-                    cached_loop_size = loop_size_in_cache;
-                    key = *cached_key;
-                    break;
-                }
-                None => (),
-            }
-        }
 
-        for smaller_loop_size in cached_loop_size..loop_size {
-            key = *cache
-                .entry((subject_number, smaller_loop_size))
-                .or_insert((key * subject_number) % 20201227);
+/// Keys are powers of the subject number modulo this prime.
+const MODULUS: usize = 20201227;
+
+fn get_public_key(
+    cache: &mut HashMap<(usize, usize), usize>,
+    subject_number: usize,
+    loop_size: usize,
+) -> usize {
+    let mut key = 1;
+    let mut cached_loop_size = 0;
+    for loop_size_in_cache in (0..loop_size).rev() {
+        if let Some(cached_key) = cache.get(&(subject_number, loop_size_in_cache)) {
+            cached_loop_size = loop_size_in_cache;
+            key = *cached_key;
+            break;
         }
+    }
 
-        key
+    for smaller_loop_size in cached_loop_size..loop_size {
+        key = *cache
+            .entry((subject_number, smaller_loop_size))
+            .or_insert((key * subject_number) % MODULUS);
     }
 
-    fn reverse_public_key(
-        cache: &mut HashMap<(usize, usize), usize>,
-        public_key: &usize,
-    ) -> (usize, usize) {
-        let mut trials = 1;
+    key
+}
+
+fn reverse_public_key(
+    cache: &mut HashMap<(usize, usize), usize>,
+    public_key: &usize,
+) -> (usize, usize) {
+    let mut trials = 1;
+
+    let subject_number = 7;
+    loop {
+        for loop_size in 0..trials {
+            let trial_public_key = get_public_key(cache, subject_number, loop_size);
+
+            if trial_public_key == *public_key {
+                return (subject_number, loop_size);
+            }
+        }
+        trials *= 2;
+        debug!("Trials: {}, cache_size: {}", trials, cache.len());
+    }
+}
 
-        let subject_number = 7;
-        loop {
-            for loop_size in 0..trials {
-                let trial_public_key = get_public_key(cache, subject_number, loop_size);
+pub struct Day25;
 
-                if trial_public_key == *public_key {
-                    return (subject_number, loop_size);
+impl Solution for Day25 {
+    const DAY: usize = 25;
+
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = NoAnswer;
+
+    fn parse(contents: &str) -> Result<Vec<usize>, ParseError> {
+        let mut lines = contents.lines();
+        let mut public_keys = Vec::new();
+        for _ in 0..2 {
+            let line = lines.next().ok_or_else(|| {
+                ParseError::at(
+                    contents,
+                    &contents[contents.len()..],
+                    "expected the public keys of the card and the door",
+                )
+            })?;
+            match parse_at(contents, line)? {
+                key if (1..MODULUS).contains(&key) => public_keys.push(key),
+                _ => {
+                    return Err(ParseError::at(
+                        contents,
+                        line,
+                        format!("expected a public key from 1 to {}", MODULUS - 1),
+                    ))
                 }
             }
-            trials *= 2;
-            println!("Trials: {}, cache_size: {}", trials, cache.len());
         }
+        if let Some(line) = lines.next() {
+            return Err(ParseError::at(
+                contents,
+                line,
+                "expected only two public keys",
+            ));
+        }
+        Ok(public_keys)
     }
 
-    let mut cache = HashMap::<(usize, usize), usize>::new();
-    let secrets = public_keys
-        .iter()
-        .map(|key| {
-            let (subject_number, loop_size) = reverse_public_key(&mut cache, key);
-            println!(
-                "public key: {}, reversed: {:?}, ",
-                key,
+    fn part1(public_keys: &Vec<usize>) -> usize {
+        debug!("public_keys: {:?}", public_keys);
+
+        let mut cache = HashMap::<(usize, usize), usize>::new();
+        let secrets = public_keys
+            .iter()
+            .map(|key| {
+                let (subject_number, loop_size) = reverse_public_key(&mut cache, key);
+                debug!(
+                    "public key: {}, reversed: {:?}, ",
+                    key,
+                    (subject_number, loop_size)
+                );
                 (subject_number, loop_size)
-            );
-            (subject_number, loop_size)
-        })
-        .collect::<Vec<(usize, usize)>>();
-
-    secrets.iter().for_each(|(subject_number, loop_size)| {
-        println!(
-            "secret: {}",
-            get_public_key(&mut cache, *subject_number, *loop_size),
-        );
-    });
+            })
+            .collect::<Vec<(usize, usize)>>();
+
+        if log::enabled(Level::Debug) {
+            secrets.iter().for_each(|(subject_number, loop_size)| {
+                debug!(
+                    "secret: {}",
+                    get_public_key(&mut cache, *subject_number, *loop_size),
+                );
+            });
+        }
+
+        debug!("public_keys: {:?}, secrets: {:?}", public_keys, secrets);
 
-    println!("public_keys: {:?}, secrets: {:?}", public_keys, secrets);
+        let encryption_key1 = get_public_key(&mut cache, public_keys[1], secrets[0].1);
+        let encryption_key2 = get_public_key(&mut cache, public_keys[0], secrets[1].1);
 
-    let encryption_key1 = get_public_key(&mut cache, public_keys[1], secrets[0].1);
-    let encryption_key2 = get_public_key(&mut cache, public_keys[0], secrets[1].1);
+        debug!(
+            "Validated encryption keys: {} == {}",
+            encryption_key1, encryption_key2
+        );
+        encryption_key1
+    }
 
-    println!(
-        "Validated encryption keys: {} == {}",
-        encryption_key1, encryption_key2
-    );
-    encryption_key1
+    fn part2(_public_keys: &Vec<usize>) -> NoAnswer {
+        NoAnswer
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    println!("Part1: {}", solve_part1(args[1].to_string()));
-    //println!("Part2: {}", solve_part2(args[1].to_string()));
+impl Generator for Day25 {
+    /// The public keys of the card and the door, for loop sizes from 1 to
+    /// `size`.
+    fn generate(rng: &mut StdRng, size: usize) -> String {
+        let mut cache = HashMap::new();
+        (0..2)
+            .map(|_| {
+                let loop_size = rng.gen_range(1, size.max(1) + 1);
+                format!("{}\n", get_public_key(&mut cache, 7, loop_size))
+            })
+            .collect()
+    }
 }
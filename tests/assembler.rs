@@ -0,0 +1,95 @@
+//! Assembles day 8 boot code written with labels and comments, and reads
+//! programs back as text.
+
+use advent_of_code_2020::day08::assembler::{assemble, disassemble};
+use advent_of_code_2020::day08::vm::{Instruction, InstructionSet, ACC, JMP, NOP};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n";
+
+#[test]
+fn assembles_labels_and_comments() {
+    let instruction_set = InstructionSet::default();
+    let source = "# The puzzle's example
+start:  nop start
+again:  acc +1
+        jmp skip        # over the -99
+back:   acc +3
+        jmp again
+        acc -99
+skip:   acc +1
+        jmp back
+last:
+        acc +6
+";
+    assert_eq!(
+        assemble(source, &instruction_set).unwrap(),
+        instruction_set.load(EXAMPLE).unwrap()
+    );
+
+    // A label at the very end is where the program terminates
+    let program = assemble("jmp done\nacc +1\ndone:", &instruction_set).unwrap();
+    assert_eq!(program[0].argument, 2);
+}
+
+#[test]
+fn reports_errors() {
+    let instruction_set = InstructionSet::default();
+    let error = |source| assemble(source, &instruction_set).unwrap_err().to_string();
+
+    assert!(error("jmp nowhere").contains("unknown label"));
+    assert!(error("a: nop +0\na: nop +0").contains("label defined twice"));
+    assert!(error("hcf +0").contains("unknown operation"));
+    assert!(error("acc").contains("expected an argument"));
+    assert!(error("acc +1 +2").contains("expected one argument"));
+    assert!(error("1up: acc +1").contains("expected a label"));
+}
+
+#[test]
+fn disassembles_with_jump_targets() {
+    let instruction_set = InstructionSet::default();
+    let program = instruction_set.load(EXAMPLE).unwrap();
+    assert_eq!(
+        disassemble(&program, &instruction_set),
+        "nop +0
+acc +1    # <- 4
+jmp +4    # -> 6
+acc +3    # <- 7
+jmp -3    # -> 1
+acc -99
+acc +1    # <- 2
+jmp -4    # -> 3
+acc +6
+"
+    );
+
+    let program = instruction_set.load("jmp +2\njmp -5\n").unwrap();
+    assert_eq!(
+        disassemble(&program, &instruction_set),
+        "jmp +2    # -> end\njmp -5    # -> out of bounds\n# end <- 0\n"
+    );
+}
+
+#[test]
+fn round_trips() {
+    let instruction_set = InstructionSet::default();
+    let mut rng = StdRng::seed_from_u64(20);
+    for _ in 0..500 {
+        let length = rng.gen_range(1, 20);
+        let program = (0..length)
+            .map(|_| Instruction {
+                opcode: [NOP, ACC, JMP][rng.gen_range(0, 3)],
+                argument: rng.gen_range(-25, 26),
+            })
+            .collect::<Vec<_>>();
+
+        let text = disassemble(&program, &instruction_set);
+        assert_eq!(
+            assemble(&text, &instruction_set).unwrap(),
+            program,
+            "{}",
+            text
+        );
+    }
+}
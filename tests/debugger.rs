@@ -0,0 +1,89 @@
+//! Drives the day 8 debugger with scripts, as a user would at its prompt.
+
+use advent_of_code_2020::day08::debugger::{Breakpoint, Debugger, Stop};
+use advent_of_code_2020::day08::vm::{Halt, InstructionSet, Program, Vm};
+
+const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n";
+
+fn program() -> (Program, InstructionSet) {
+    let instruction_set = InstructionSet::default();
+    (instruction_set.load(EXAMPLE).unwrap(), instruction_set)
+}
+
+#[test]
+fn runs_scripts() {
+    let (program, instruction_set) = program();
+    let mut debugger = Debugger::new(Vm::new(&program, &instruction_set));
+
+    let transcript = debugger.run_script(
+        "# stop at the first jmp -3
+        break pc 4
+        watch acc * 2
+        continue
+        trace on
+        step 3
+        back 2
+        print pc + steps
+        delete acc 1
+        frobnicate",
+    );
+    assert_eq!(
+        transcript,
+        "> break pc 4
+breakpoint at pc 4
+> watch acc * 2
+watch acc * 2 = 0
+> continue
+breakpoint at pc 4
+step 6  pc 4: jmp -3  acc 5
+watch acc * 2 = 10
+> trace on
+trace on
+> step 3
+      6  pc    4: jmp -3      acc 5 -> 5
+halted: the next instruction already ran, so it loops forever
+step 7  pc 1: acc +1  acc 5
+watch acc * 2 = 10
+> back 2
+step 5  pc 3: acc +3  acc 2
+watch acc * 2 = 4
+> print pc + steps
+pc + steps = 8
+> delete acc 1
+error: no breakpoint at acc 1
+> frobnicate
+error: unknown command `frobnicate`
+"
+    );
+}
+
+#[test]
+fn steps_back_to_the_start() {
+    let (program, instruction_set) = program();
+    let mut debugger = Debugger::new(Vm::new(&program, &instruction_set));
+    debugger.set_tracing(true);
+
+    assert_eq!(debugger.resume(), Stop::Halted(Halt::InfiniteLoop));
+    assert_eq!(debugger.vm().accumulator(), 5);
+    let pcs = debugger
+        .trace()
+        .iter()
+        .map(|entry| entry.before.pc)
+        .collect::<Vec<_>>();
+    assert_eq!(pcs, [0, 1, 2, 6, 7, 3, 4]);
+
+    while debugger.back() == Stop::Stepped {}
+    assert_eq!((debugger.vm().pc(), debugger.vm().steps()), (0, 0));
+    assert!(!debugger.vm().has_executed(4));
+
+    // Breakpoints on the accumulator stop right after it changes
+    debugger.add_breakpoint(Breakpoint::Accumulator(2));
+    assert_eq!(
+        debugger.resume(),
+        Stop::Breakpoint(Breakpoint::Accumulator(2))
+    );
+    assert_eq!(debugger.vm().pc(), 7);
+    assert_eq!(debugger.watch("acc - (pc * 2)"), Ok(-12));
+    assert!(debugger.watch("acc +").is_err());
+    assert!(debugger.watch("ip").is_err());
+}
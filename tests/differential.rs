@@ -0,0 +1,365 @@
+//! Checks solvers against straightforward reference implementations on
+//! generated inputs, so that optimising a solver can't change its answers.
+//!
+//! The references read the puzzle text themselves and favour the most
+//! obvious approach over speed, so they only run on small inputs. A failure
+//! names the seed, and `aoc DAY --generate SIZE --seed SEED` reproduces the
+//! input.
+
+use advent_of_code_2020::generate::{generate, Generator};
+use advent_of_code_2020::*;
+
+/// Inputs checked per day and part.
+const CASES: u64 = 1000;
+
+/// Compares one part of `S` with `reference` on `CASES` inputs of `size`.
+fn differential<S: Solution + Generator>(part: usize, size: usize, reference: fn(&str) -> String) {
+    for seed in 0..CASES {
+        let contents = generate(S::DAY, size, seed).unwrap();
+        let input = S::parse(&contents).unwrap_or_else(|error| {
+            panic!("day {} seed {}: {}\n{}", S::DAY, seed, error, contents)
+        });
+        let answer = match part {
+            1 => S::part1(&input).to_string(),
+            _ => S::part2(&input).to_string(),
+        };
+
+        assert_eq!(
+            answer,
+            reference(&contents),
+            "day {} part {} disagrees with the reference for `--generate {} --seed {}`",
+            S::DAY,
+            part,
+            size,
+            seed
+        );
+    }
+}
+
+fn numbers(contents: &str) -> Vec<usize> {
+    contents.lines().map(|line| line.parse().unwrap()).collect()
+}
+
+#[test]
+fn day01() {
+    differential::<day01::Day01>(1, 20, |contents| {
+        let e = numbers(contents);
+        for i in 0..e.len() {
+            for j in i + 1..e.len() {
+                if e[i] + e[j] == 2020 {
+                    return (e[i] * e[j]).to_string();
+                }
+            }
+        }
+        panic!("no two entries sum to 2020");
+    });
+    differential::<day01::Day01>(2, 20, |contents| {
+        let e = numbers(contents);
+        for i in 0..e.len() {
+            for j in i + 1..e.len() {
+                for k in j + 1..e.len() {
+                    if e[i] + e[j] + e[k] == 2020 {
+                        return (e[i] * e[j] * e[k]).to_string();
+                    }
+                }
+            }
+        }
+        panic!("no three entries sum to 2020");
+    });
+}
+
+#[test]
+fn day05() {
+    fn seat_ids(contents: &str) -> Vec<usize> {
+        contents
+            .lines()
+            .map(|pass| {
+                let binary = pass
+                    .chars()
+                    .map(|c| if c == 'B' || c == 'R' { '1' } else { '0' })
+                    .collect::<String>();
+                usize::from_str_radix(&binary, 2).unwrap()
+            })
+            .collect()
+    }
+
+    differential::<day05::Day05>(1, 50, |contents| {
+        seat_ids(contents).iter().max().unwrap().to_string()
+    });
+    differential::<day05::Day05>(2, 50, |contents| {
+        let seats = seat_ids(contents);
+        (1..1023)
+            .find(|seat| {
+                !seats.contains(seat) && seats.contains(&(seat - 1)) && seats.contains(&(seat + 1))
+            })
+            .unwrap()
+            .to_string()
+    });
+}
+
+/// Runs a day 8 program, with the instruction at `flip` swapped between
+/// `jmp` and `nop`, and returns whether it terminated and its accumulator.
+fn run_program(program: &[(&str, i32)], flip: Option<usize>) -> (bool, i32) {
+    let mut visited = vec![false; program.len()];
+    let (mut pc, mut accumulator) = (0_i32, 0);
+    loop {
+        if pc < 0 || pc as usize >= program.len() {
+            return (true, accumulator);
+        }
+        let index = pc as usize;
+        if visited[index] {
+            return (false, accumulator);
+        }
+        visited[index] = true;
+
+        let (operation, argument) = program[index];
+        let operation = match (operation, flip == Some(index)) {
+            ("jmp", true) => "nop",
+            ("nop", true) => "jmp",
+            (operation, _) => operation,
+        };
+        match operation {
+            "acc" => accumulator += argument,
+            "jmp" => pc += argument - 1,
+            _ => (),
+        }
+        pc += 1;
+    }
+}
+
+fn program(contents: &str) -> Vec<(&str, i32)> {
+    contents
+        .lines()
+        .map(|line| {
+            let (operation, argument) = line.split_once(' ').unwrap();
+            (operation, argument.parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn day08() {
+    differential::<day08::Day08>(1, 40, |contents| {
+        run_program(&program(contents), None).1.to_string()
+    });
+    differential::<day08::Day08>(2, 40, |contents| {
+        let program = program(contents);
+        (0..program.len())
+            .map(|flip| run_program(&program, Some(flip)))
+            .find(|&(terminated, _)| terminated)
+            .unwrap()
+            .1
+            .to_string()
+    });
+}
+
+fn first_invalid(sequence: &[usize]) -> usize {
+    (25..sequence.len())
+        .map(|index| sequence[index])
+        .zip(sequence.windows(25))
+        .find(|(number, window)| {
+            !window
+                .iter()
+                .enumerate()
+                .any(|(i, a)| window[i + 1..].iter().any(|b| a + b == *number))
+        })
+        .unwrap()
+        .0
+}
+
+#[test]
+fn day09() {
+    differential::<day09::Day09>(1, 60, |contents| {
+        first_invalid(&numbers(contents)).to_string()
+    });
+    differential::<day09::Day09>(2, 60, |contents| {
+        let sequence = numbers(contents);
+        let invalid = first_invalid(&sequence);
+        (0..sequence.len())
+            .flat_map(|start| (start + 2..=sequence.len()).map(move |end| (start, end)))
+            .map(|(start, end)| &sequence[start..end])
+            .find(|range| range.iter().sum::<usize>() == invalid)
+            .map(|range| range.iter().min().unwrap() + range.iter().max().unwrap())
+            .unwrap()
+            .to_string()
+    });
+}
+
+#[test]
+fn day10() {
+    fn chain(contents: &str) -> Vec<usize> {
+        let mut jolts = numbers(contents);
+        jolts.push(0);
+        jolts.sort_unstable();
+        jolts.push(jolts.last().unwrap() + 3);
+        jolts
+    }
+
+    differential::<day10::Day10>(1, 40, |contents| {
+        let differences = chain(contents)
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<usize>>();
+        let count = |difference| differences.iter().filter(|&&d| d == difference).count();
+        (count(1) * count(3)).to_string()
+    });
+    differential::<day10::Day10>(2, 40, |contents| {
+        let jolts = chain(contents);
+        // ways[i] counts the arrangements that end with jolts[i]
+        let mut ways = vec![0_usize; jolts.len()];
+        ways[0] = 1;
+        for i in 1..jolts.len() {
+            ways[i] = (0..i)
+                .filter(|&j| jolts[i] - jolts[j] <= 3)
+                .map(|j| ways[j])
+                .sum();
+        }
+        ways.last().unwrap().to_string()
+    });
+}
+
+/// Where the ship ends up, steering by its heading or, with `waypoint`, by
+/// a waypoint, turning both a quarter at a time.
+fn navigate(contents: &str, waypoint: bool) -> (i32, i32) {
+    let (mut ship, mut heading) = ((0, 0), if waypoint { (10, -1) } else { (1, 0) });
+    for line in contents.lines() {
+        let value = line[1..].parse::<i32>().unwrap();
+        let moved = match &line[..1] {
+            "N" => (0, -value),
+            "S" => (0, value),
+            "E" => (value, 0),
+            "W" => (-value, 0),
+            "F" => {
+                ship = (ship.0 + heading.0 * value, ship.1 + heading.1 * value);
+                (0, 0)
+            }
+            turn => {
+                for _ in 0..value / 90 {
+                    heading = match turn {
+                        "R" => (-heading.1, heading.0),
+                        _ => (heading.1, -heading.0),
+                    };
+                }
+                (0, 0)
+            }
+        };
+        if waypoint {
+            heading = (heading.0 + moved.0, heading.1 + moved.1);
+        } else {
+            ship = (ship.0 + moved.0, ship.1 + moved.1);
+        }
+    }
+    ship
+}
+
+#[test]
+fn day12() {
+    // This one ends up farther north than east of the start, which once
+    // gave a negative answer
+    let contents = generate(12, 100, 7).unwrap();
+    assert_eq!(navigate(&contents, false), (20, -81));
+    assert_eq!(
+        day12::Day12::part1(&day12::Day12::parse(&contents).unwrap()),
+        101
+    );
+
+    fn distance((x, y): (i32, i32)) -> String {
+        (x.abs() + y.abs()).to_string()
+    }
+    differential::<day12::Day12>(1, 100, |contents| distance(navigate(contents, false)));
+    differential::<day12::Day12>(2, 100, |contents| distance(navigate(contents, true)));
+}
+
+#[test]
+fn day13() {
+    fn notes(contents: &str) -> (i128, Vec<(i128, i128)>) {
+        let mut lines = contents.lines();
+        let timestamp = lines.next().unwrap().parse().unwrap();
+        let buses = lines
+            .next()
+            .unwrap()
+            .split(',')
+            .enumerate()
+            .filter(|&(_, id)| id != "x")
+            .map(|(offset, id)| (offset as i128, id.parse().unwrap()))
+            .collect();
+        (timestamp, buses)
+    }
+
+    differential::<day13::Day13>(1, 20, |contents| {
+        let (timestamp, buses) = notes(contents);
+        (timestamp..)
+            .find_map(|time| {
+                buses
+                    .iter()
+                    .find(|(_, id)| time % id == 0)
+                    .map(|(_, id)| (time - timestamp) * id)
+            })
+            .unwrap()
+            .to_string()
+    });
+    // The chinese remainder theorem, with inverses from the extended
+    // Euclidean algorithm rather than the solver's sieve
+    differential::<day13::Day13>(2, 20, |contents| {
+        fn inverse(a: i128, modulus: i128) -> i128 {
+            let (mut old_r, mut r) = (a.rem_euclid(modulus), modulus);
+            let (mut old_s, mut s) = (1, 0);
+            while r != 0 {
+                let quotient = old_r / r;
+                let (next_r, next_s) = (old_r - quotient * r, old_s - quotient * s);
+                old_r = r;
+                r = next_r;
+                old_s = s;
+                s = next_s;
+            }
+            old_s.rem_euclid(modulus)
+        }
+
+        let (_, buses) = notes(contents);
+        let product = buses.iter().map(|(_, id)| id).product::<i128>();
+        buses
+            .iter()
+            .map(|&(offset, id)| {
+                let others = product / id;
+                (-offset).rem_euclid(id) * others % product * inverse(others, id) % product
+            })
+            .sum::<i128>()
+            .rem_euclid(product)
+            .to_string()
+    });
+}
+
+#[test]
+fn day15() {
+    differential::<day15::Day15>(1, 5, |contents| {
+        let mut spoken = contents
+            .trim()
+            .split(',')
+            .map(|number| number.parse().unwrap())
+            .collect::<Vec<usize>>();
+        while spoken.len() < 2020 {
+            let (last, before) = spoken.split_last().unwrap();
+            let next = match before.iter().rposition(|number| number == last) {
+                Some(index) => before.len() - index,
+                None => 0,
+            };
+            spoken.push(next);
+        }
+        spoken[2019].to_string()
+    });
+}
+
+#[test]
+fn day25() {
+    differential::<day25::Day25>(1, 2000, |contents| {
+        let keys = numbers(contents);
+        let (mut value, mut loops) = (1, 0);
+        while value != keys[0] {
+            value = value * 7 % 20_201_227;
+            loops += 1;
+        }
+        (0..loops)
+            .fold(1, |key, _| key * keys[1] % 20_201_227)
+            .to_string()
+    });
+}
@@ -0,0 +1,152 @@
+//! Checks the day 1 k-sum solvers against trying every combination.
+
+use advent_of_code_2020::day01::{all_k_sums, audit, k_sum, Day01, Entry};
+use advent_of_code_2020::Solution;
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic;
+
+const EXAMPLE: [usize; 6] = [1721, 979, 366, 299, 675, 1456];
+
+fn entry(line: usize, value: usize) -> Entry {
+    Entry { line, value }
+}
+
+#[test]
+fn finds_the_example_entries() {
+    assert_eq!(
+        k_sum(&EXAMPLE, 2, 2020),
+        Some(vec![entry(1, 1721), entry(4, 299)])
+    );
+    assert_eq!(
+        k_sum(&EXAMPLE, 3, 2020),
+        Some(vec![entry(2, 979), entry(3, 366), entry(5, 675)])
+    );
+    assert_eq!(k_sum(&EXAMPLE, 1, 366), Some(vec![entry(3, 366)]));
+    assert_eq!(k_sum(&EXAMPLE, 0, 0), Some(vec![]));
+    assert_eq!(k_sum(&EXAMPLE, 2, 2021), None);
+    assert_eq!(k_sum(&EXAMPLE, 7, 5496), None);
+    assert_eq!(
+        k_sum(&EXAMPLE, 6, 5496),
+        Some(
+            (1..=6)
+                .zip(EXAMPLE.iter().copied())
+                .map(|(line, value)| entry(line, value))
+                .collect()
+        )
+    );
+}
+
+#[test]
+fn fails_without_a_solution() {
+    let expenses = vec![1, 2, 3];
+    assert!(panic::catch_unwind(|| Day01::part1(&expenses)).is_err());
+    assert!(panic::catch_unwind(|| Day01::part2(&expenses)).is_err());
+    assert_eq!(Day01::part1(&EXAMPLE.to_vec()), 514579);
+}
+
+#[test]
+fn agrees_with_brute_force() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..500 {
+        let expenses = (0..rng.gen_range(0, 12))
+            .map(|_| rng.gen_range(0, 30))
+            .collect::<Vec<usize>>();
+        let k = rng.gen_range(0, 5);
+        let target = rng.gen_range(0, 60);
+
+        let expected = (0..expenses.len())
+            .combinations(k)
+            .any(|indices| indices.iter().map(|&i| expenses[i]).sum::<usize>() == target);
+        match k_sum(&expenses, k, target) {
+            Some(entries) => {
+                assert_eq!(entries.len(), k);
+                assert!(entries.windows(2).all(|pair| pair[0].line < pair[1].line));
+                for entry in &entries {
+                    assert_eq!(expenses[entry.line - 1], entry.value);
+                }
+                assert_eq!(
+                    entries.iter().map(|entry| entry.value).sum::<usize>(),
+                    target
+                );
+            }
+            None => assert!(!expected, "{:?} k {} target {}", expenses, k, target),
+        }
+    }
+}
+
+#[test]
+fn uses_repeated_values_once_per_line() {
+    assert_eq!(all_k_sums(&[1010, 7], 2, 2020), Vec::<Vec<Entry>>::new());
+    assert_eq!(
+        all_k_sums(&[1010, 7, 1010, 1010], 2, 2020),
+        [
+            vec![entry(1, 1010), entry(3, 1010)],
+            vec![entry(1, 1010), entry(4, 1010)],
+            vec![entry(3, 1010), entry(4, 1010)],
+        ]
+    );
+    assert_eq!(
+        k_sum(&[1010, 7, 1010], 2, 2020),
+        Some(vec![entry(1, 1010), entry(3, 1010)])
+    );
+    assert_eq!(k_sum(&[1010, 7], 2, 2020), None);
+}
+
+#[test]
+fn enumerates_like_brute_force() {
+    let mut rng = StdRng::seed_from_u64(22);
+    for _ in 0..500 {
+        let expenses = (0..rng.gen_range(0, 12))
+            .map(|_| rng.gen_range(0, 20))
+            .collect::<Vec<usize>>();
+        let k = rng.gen_range(0, 5);
+        let target = rng.gen_range(0, 40);
+
+        let expected = (0..expenses.len())
+            .combinations(k)
+            .filter(|indices| indices.iter().map(|&i| expenses[i]).sum::<usize>() == target)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|&i| entry(i + 1, expenses[i]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            all_k_sums(&expenses, k, target),
+            expected,
+            "{:?} k {} target {}",
+            expenses,
+            k,
+            target
+        );
+    }
+}
+
+#[test]
+fn audits_the_example() {
+    assert_eq!(
+        audit(&EXAMPLE, &[2, 3], 2020),
+        (
+            "2 entries summing to 2020: 1 combination
+  lines 1, 4: 1721 + 299 (product 514579)
+3 entries summing to 2020: 1 combination
+  lines 2, 3, 5: 979 + 366 + 675 (product 241861950)
+"
+            .to_string(),
+            true
+        )
+    );
+
+    let (report, unique) = audit(&[1010, 1010, 1010, 5], &[2, 4], 2020);
+    assert!(!unique);
+    assert_eq!(
+        report.lines().next(),
+        Some(
+            "2 entries summing to 2020: 3 combinations (1 distinct by value), expected exactly one"
+        )
+    );
+    assert!(report.ends_with("4 entries summing to 2020: none, expected exactly one\n"));
+}
@@ -0,0 +1,67 @@
+//! Checks that generated inputs are reproducible and accepted by every
+//! solver, and that the guarantees generators make hold.
+
+use advent_of_code_2020::day08::vm::{Instruction, JMP, NOP};
+use advent_of_code_2020::day08::Day08;
+use advent_of_code_2020::generate::generate;
+use advent_of_code_2020::runner::{run, Source};
+use advent_of_code_2020::Solution;
+
+/// Parts too slow for a debug build, or that don't terminate, by day.
+const SLOW: [(usize, usize); 4] = [(15, 2), (17, 2), (20, 2), (23, 2)];
+
+#[test]
+fn generated_inputs_are_solved() {
+    for day in 1..=25 {
+        let parts = [1, 2]
+            .iter()
+            .copied()
+            .filter(|&part| !SLOW.contains(&(day, part)))
+            .collect::<Vec<usize>>();
+        for seed in 0..3 {
+            let contents = generate(day, 6, seed).unwrap();
+            assert_eq!(generate(day, 6, seed), Some(contents.clone()));
+
+            if let Err(message) = run(day, &Source::Text(&contents), &parts) {
+                panic!("day {} seed {}: {}\n{}", day, seed, message, contents);
+            }
+        }
+    }
+}
+
+/// Whether the program runs off its end, with `flip` swapped between `jmp`
+/// and `nop`.
+fn terminates(program: &[Instruction], flip: Option<usize>) -> bool {
+    let mut visited = vec![false; program.len()];
+    let mut pc = 0_i32;
+    while pc >= 0 && (pc as usize) < program.len() {
+        let index = pc as usize;
+        if visited[index] {
+            return false;
+        }
+        visited[index] = true;
+        let opcode = match (program[index].opcode, flip == Some(index)) {
+            (JMP, true) => NOP,
+            (NOP, true) => JMP,
+            (opcode, _) => opcode,
+        };
+        pc += match opcode {
+            JMP => program[index].argument,
+            _ => 1,
+        };
+    }
+    true
+}
+
+#[test]
+fn day08_programs_have_exactly_one_repair() {
+    for seed in 0..20 {
+        let program = Day08::parse(&generate(8, 200, seed).unwrap()).unwrap();
+        assert!(!terminates(&program, None));
+
+        let repairs = (0..program.len())
+            .filter(|&index| terminates(&program, Some(index)))
+            .count();
+        assert_eq!(repairs, 1, "seed {}", seed);
+    }
+}
@@ -0,0 +1,93 @@
+//! Checks the shared grid against small hand-drawn maps.
+
+use advent_of_code_2020::grid::{Bounds, Grid};
+
+fn parse(text: &str) -> Grid<char> {
+    Grid::parse(text, text, "expected `.` or `#`", |c| match c {
+        '.' | '#' => Some(c),
+        _ => None,
+    })
+    .unwrap()
+}
+
+fn render(grid: &Grid<char>) -> String {
+    grid.render(|cell| cell.copied().unwrap_or(' '))
+}
+
+#[test]
+fn parses_rows_into_bounds() {
+    let grid = parse("#..\n.#.\n");
+    assert_eq!(grid.bounds(), Bounds::new((0, 0), (3, 2)));
+    assert_eq!(grid.get((1, 1)), Some(&'#'));
+    assert_eq!(grid.get((3, 0)), None);
+    assert_eq!(render(&grid), "#..\n.#.\n");
+
+    let text = "#..\n.#\n";
+    let error = Grid::parse(text, text, "", Some).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 1));
+
+    // Blank rows would make a grid with no width
+    for &text in &["\n", "#.\n\n#.\n"] {
+        let error = Grid::parse(text, text, "", Some).unwrap_err();
+        assert_eq!(error.message, "expected a row of cells");
+    }
+    assert_eq!(parse("").bounds(), Bounds::new((0, 0), (0, 0)));
+}
+
+#[test]
+fn wraps_around_in_every_direction() {
+    let grid = parse("#..\n..#\n");
+    assert_eq!(grid.get_wrapped((3, 0)), Some(&'#'));
+    assert_eq!(grid.get_wrapped((-1, -1)), Some(&'#'));
+    assert_eq!(grid.get_wrapped((5, 3)), Some(&'#'));
+}
+
+#[test]
+fn finds_neighbours_and_casts_rays() {
+    let grid = parse("#.#\n...\n#.#\n");
+    assert_eq!(
+        grid.neighbours4((1, 1)).filter(|(_, &c)| c == '#').count(),
+        0
+    );
+    assert_eq!(
+        grid.neighbours8((1, 1)).filter(|(_, &c)| c == '#').count(),
+        4
+    );
+    assert_eq!(grid.neighbours8((0, 0)).count(), 3);
+
+    let ray = grid
+        .ray((0, 0), (1, 1))
+        .map(|(coord, _)| coord)
+        .collect::<Vec<_>>();
+    assert_eq!(ray, vec![(1, 1), (2, 2)]);
+}
+
+#[test]
+fn rotates_and_flips() {
+    let mut grid = parse("#..\n##.\n");
+    grid.rotate();
+    assert_eq!(render(&grid), "##\n#.\n..\n");
+    grid.flip_horizontal();
+    assert_eq!(render(&grid), "##\n.#\n..\n");
+    grid.flip_vertical();
+    assert_eq!(render(&grid), "..\n.#\n##\n");
+    grid.crop(Bounds::new((1, 1), (2, 3)));
+    assert_eq!(render(&grid), "#\n#\n");
+}
+
+#[test]
+fn sparse_grids_grow_and_transform_alike() {
+    let mut dense = parse("#..\n##.\n");
+    let mut sparse = dense.clone().into_sparse(|&c| c == '#');
+    assert_eq!(render(&sparse), "#  \n## \n");
+
+    dense.rotate();
+    sparse.rotate();
+    assert_eq!(render(&sparse), render(&dense.into_sparse(|&c| c == '#')));
+
+    let mut grown = Grid::sparse(Bounds::default());
+    grown.insert((-1, 2), '#');
+    grown.insert((1, 0), '#');
+    assert_eq!(grown.bounds(), Bounds::new((-1, 0), (2, 3)));
+    assert_eq!(render(&grown), "  #\n   \n#  \n");
+}
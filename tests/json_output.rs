@@ -0,0 +1,26 @@
+//! Checks the records printed by `aoc --format json`.
+
+use advent_of_code_2020::runner::{json_error, run, Source};
+use std::path::PathBuf;
+
+#[test]
+fn writes_one_record_per_part() {
+    let source = Source::from("1721\n979\n366\n299\n675\n1456\n");
+    let report = run(1, &source, &[1, 2]).unwrap();
+    let records = report
+        .json_records(&Source::File(PathBuf::from("day01/example.txt")))
+        .collect::<Vec<_>>();
+
+    assert_eq!(records.len(), 2);
+    assert!(records[0].starts_with(r#"{"day":1,"part":1,"answer":"514579","parse_ns":"#));
+    assert!(records[1].starts_with(r#"{"day":1,"part":2,"answer":"241861950","parse_ns":"#));
+    assert!(records[1].ends_with(r#","input":"day01/example.txt"}"#));
+}
+
+#[test]
+fn escapes_error_messages() {
+    assert_eq!(
+        json_error(8, &Source::Stdin, "1:1: bad \"op\"\n\\"),
+        r#"{"day":8,"error":"1:1: bad \"op\"\n\\","input":"-"}"#
+    );
+}
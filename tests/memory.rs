@@ -0,0 +1,46 @@
+//! Counts the allocations of the solvers through the counting allocator,
+//! installed here as it is in the `aoc` binary.
+
+use advent_of_code_2020::memory;
+use advent_of_code_2020::memory::CountingAllocator;
+use advent_of_code_2020::runner::{run, Source};
+use std::hint::black_box;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Counting is switched on for the whole process, so everything is checked
+// in one test rather than racing another that expects it off.
+#[test]
+fn measures_peak_heap_usage_and_allocations() {
+    let (_, usage) = memory::measure(|| vec![0u8; 1 << 20]);
+    assert_eq!(usage, None);
+
+    memory::enable();
+    let (buffers, usage) = memory::measure(|| {
+        // `black_box` keeps release builds from leaving out allocations
+        // that are never read
+        let buffers = (0..4)
+            .map(|_| black_box(vec![0u8; 1 << 20]))
+            .collect::<Vec<_>>();
+        drop(black_box(vec![0u8; 8 << 20]));
+        black_box(buffers)
+    });
+    let usage = usage.unwrap();
+    assert!(usage.peak >= 12 << 20, "peak of {} bytes", usage.peak);
+    assert!(usage.peak < 13 << 20, "peak of {} bytes", usage.peak);
+    assert_eq!(usage.allocations, 6);
+    drop(buffers);
+
+    // Day 1 parses the six entries into a vector of `usize`
+    let source = Source::from("1721\n979\n366\n299\n675\n1456\n");
+    let report = run(1, &source, &[1, 2]).unwrap();
+    let parse = report.parse_memory.unwrap();
+    assert!(parse.peak >= 6 * 8);
+    assert!(parse.allocations >= 1);
+    assert!(report.parts.iter().all(|part| part.memory.is_some()));
+
+    let record = report.json_records(&Source::Stdin).next().unwrap();
+    assert!(record.contains(r#","parse_peak_bytes":"#));
+    assert!(record.contains(r#","solve_allocations":"#));
+}
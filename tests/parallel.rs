@@ -0,0 +1,85 @@
+//! Checks that days run concurrently are reported in order, and that a
+//! panic or a timeout only fails the day it happened in.
+
+use advent_of_code_2020::runner::{default_input, run_parallel, Outcome, Source, Status};
+use std::time::Duration;
+
+fn run_days(
+    days: Vec<(usize, Source<'static>)>,
+    parts: &[usize],
+    timeout: Option<Duration>,
+) -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+    run_parallel(days, parts, 4, timeout, |outcome| outcomes.push(outcome));
+    outcomes
+}
+
+fn answers(outcome: &Outcome) -> Vec<String> {
+    match &outcome.status {
+        Status::Solved(report) => report
+            .parts
+            .iter()
+            .map(|part| part.answer.clone())
+            .collect(),
+        _ => panic!("day {} failed: {:?}", outcome.day, outcome.error()),
+    }
+}
+
+#[test]
+fn reports_days_in_the_order_given() {
+    let days = [6, 1, 5, 2];
+    let outcomes = run_days(
+        days.iter()
+            .map(|&day| (day, Source::File(default_input(day))))
+            .collect(),
+        &[1, 2],
+        None,
+    );
+
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|outcome| outcome.day)
+            .collect::<Vec<_>>(),
+        days
+    );
+    assert_eq!(answers(&outcomes[1]), ["157059", "165080960"]);
+    assert!(outcomes.iter().all(Outcome::passed));
+}
+
+#[test]
+fn isolates_panics() {
+    let outcomes = run_days(
+        vec![
+            (
+                2,
+                Source::from("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n"),
+            ),
+            // No three entries sum to 2020
+            (1, Source::from("1\n2\n3\n")),
+            (12, Source::from("F10\nN3\nF7\nR90\nF11\n")),
+        ],
+        &[2],
+        None,
+    );
+
+    assert_eq!(answers(&outcomes[0]), ["1"]);
+    assert!(matches!(outcomes[1].status, Status::Panicked(_)));
+    assert_eq!(answers(&outcomes[2]), ["286"]);
+}
+
+#[test]
+fn times_out_slow_days() {
+    let outcomes = run_days(
+        vec![
+            (15, Source::from("0,3,6")),
+            (1, Source::from("1721\n979\n366\n299\n675\n1456\n")),
+        ],
+        &[2],
+        Some(Duration::from_millis(50)),
+    );
+
+    assert!(matches!(outcomes[0].status, Status::TimedOut));
+    assert!(outcomes[0].error().unwrap().starts_with("timed out"));
+    assert_eq!(answers(&outcomes[1]), ["241861950"]);
+}
@@ -0,0 +1,131 @@
+//! Checks that malformed input is reported where it goes wrong rather than
+//! panicking inside a parser or the solver it's handed to.
+
+use advent_of_code_2020::runner::day_runner;
+use advent_of_code_2020::ParseError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic;
+
+fn parse_error(day: usize, contents: &str) -> ParseError {
+    day_runner(day).unwrap()(contents, &[1]).expect_err("malformed input was accepted")
+}
+
+#[test]
+fn reports_line_column_and_text() {
+    let error = parse_error(1, "1721\n979\n36x6\n");
+    assert_eq!((error.line, error.column), (3, 1));
+    assert_eq!(error.text, "36x6");
+
+    let error = parse_error(2, "1-3 a: abcde\n1-3 b cdefg\n");
+    assert_eq!((error.line, error.column), (2, 1));
+    assert_eq!(error.text, "1-3 b cdefg");
+
+    let error = parse_error(3, "..##\n#.x.\n");
+    assert_eq!((error.line, error.column), (2, 3));
+    assert_eq!(error.text, "x");
+
+    let error = parse_error(3, "..##\n\n#...\n");
+    assert_eq!((error.line, error.column), (2, 1));
+    assert!(parse_error(3, "\n").message.contains("row of cells"));
+
+    let error = parse_error(24, "esew\nnwwswee\nnwq\n");
+    assert_eq!((error.line, error.column), (3, 3));
+    assert_eq!(error.text, "q");
+}
+
+#[test]
+fn reports_missing_sections_at_the_end() {
+    let error = parse_error(19, "0: 1 2\n1: \"a\"\n2: \"b\"\n");
+    assert_eq!((error.line, error.column), (4, 1));
+}
+
+#[test]
+fn names_the_file_once_attached() {
+    let error = parse_error(18, "1 + (2 * 3\n").in_file("day18/input.txt".as_ref());
+    assert_eq!(error.to_string(), "day18/input.txt:1:5: unmatched `(`: `(`");
+}
+
+#[test]
+fn rejects_input_the_solvers_cannot_use() {
+    let error = parse_error(10, "");
+    assert_eq!(error.message, "expected at least one adapter");
+
+    let error = parse_error(23, "");
+    assert_eq!(error.message, "expected nine cups, labelled `1` to `9`");
+    let error = parse_error(23, "38912546\n");
+    assert_eq!((error.line, error.column), (2, 1));
+    let error = parse_error(23, "389120467\n");
+    assert_eq!((error.column, error.text.as_str()), (6, "0"));
+    let error = parse_error(23, "389125463\n");
+    assert_eq!(
+        (error.column, error.message.as_str()),
+        (9, "cup label repeated")
+    );
+
+    let error = parse_error(25, "5764801\n");
+    assert_eq!(
+        error.message,
+        "expected the public keys of the card and the door"
+    );
+    let error = parse_error(25, "5764801\n17807724\n1\n");
+    assert_eq!((error.line, error.text.as_str()), (3, "1"));
+    let error = parse_error(25, "0\n17807724\n");
+    assert_eq!(error.message, "expected a public key from 1 to 20201226");
+    let error = parse_error(25, "5764801\n20201227\n");
+    assert_eq!(error.line, 2);
+}
+
+#[test]
+fn rejects_turns_off_the_compass() {
+    for &turn in &["R45", "L100"] {
+        let error = parse_error(12, &format!("F10\n{}\nF7\n", turn));
+        assert_eq!((error.line, error.column), (2, 2), "{}", turn);
+        assert_eq!(error.message, "expected a turn by a multiple of 90 degrees");
+    }
+    // Any multiple of 90 goes, even past a full turn or the other way
+    let answers = day_runner(12).unwrap()("F10\nR450\nF7\nL-90\nF1\n", &[1, 2]).unwrap();
+    let answers = answers
+        .parts
+        .iter()
+        .map(|part| part.answer.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(answers, ["16", "158"]);
+}
+
+/// Well-formed input the solvers used to trip over is judged rather than
+/// panicking.
+#[test]
+fn solves_unusual_passports() {
+    let fields = "byr:1980 iyr:2012 eyr:2025 ecl:brn pid:000000001";
+    for &(extra, valid) in &[
+        ("hgt:170cm hcl:#123abc", "1"),
+        ("hgt:5 hcl:#123abc", "0"),
+        ("hgt:cm hcl:#123abc", "0"),
+        ("hgt:60in hcl:#ééé", "0"),
+        ("hgt:60in hcl:#12345g", "0"),
+        ("hgt:60in hcl:#12345f", "1"),
+    ] {
+        let contents = format!("{} {}\n", fields, extra);
+        let report = day_runner(4).unwrap()(&contents, &[2]).unwrap();
+        assert_eq!(report.parts[0].answer, valid, "{}", extra);
+    }
+}
+
+/// Short inputs made of the characters these days expect either fail to
+/// parse or are solved without panicking.
+#[test]
+fn survives_random_input() {
+    let mut rng = StdRng::seed_from_u64(6);
+    for &(day, alphabet) in &[(10, "0123456789\n"), (23, "0123456789\n")] {
+        for _ in 0..200 {
+            let alphabet = alphabet.as_bytes();
+            let contents = (0..rng.gen_range(0, 12))
+                .map(|_| alphabet[rng.gen_range(0, alphabet.len())] as char)
+                .collect::<String>();
+            let run = day_runner(day).unwrap();
+            let result = panic::catch_unwind(|| run(&contents, &[1]).map(|_| ()));
+            assert!(result.is_ok(), "day {} panicked on {:?}", day, contents);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Checks the day 2 password policies, selecting them by name and the
+//! report of the passwords they reject.
+
+use advent_of_code_2020::day02::policy::{
+    count_valid, policy, report, summary, violations, Violation, POLICIES,
+};
+use advent_of_code_2020::day02::{Day02, PasswordEntry};
+use advent_of_code_2020::Solution;
+
+const EXAMPLE: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n";
+
+fn entries(contents: &str) -> Vec<PasswordEntry> {
+    Day02::parse(contents).unwrap()
+}
+
+fn count(spec: &str, contents: &str) -> usize {
+    count_valid(policy(spec).unwrap().as_ref(), &entries(contents))
+}
+
+#[test]
+fn counts_the_example() {
+    assert_eq!(count("count", EXAMPLE), 2);
+    assert_eq!(count("positions", EXAMPLE), 1);
+    assert_eq!(count("length", EXAMPLE), 1);
+    assert_eq!(count("classes=1", EXAMPLE), 3);
+    assert_eq!(count("classes=2", EXAMPLE), 0);
+    assert_eq!(count("forbid=cd", EXAMPLE), 1);
+    assert_eq!(count("forbid=xyz,ccc,de", EXAMPLE), 0);
+}
+
+#[test]
+fn counts_character_classes() {
+    let contents = "1-2 a: aB3_\n1-2 a: aB3\n1-2 a: 42\n";
+    assert_eq!(count("classes=4", contents), 1);
+    assert_eq!(count("classes=3", contents), 2);
+    assert_eq!(count("classes=2", contents), 2);
+    assert_eq!(count("classes=1", contents), 3);
+}
+
+#[test]
+fn reports_the_counts() {
+    let policies = [policy("count").unwrap(), policy("length").unwrap()];
+    assert_eq!(
+        report(&policies, &entries(EXAMPLE)),
+        "count: 2 of 3 passwords valid\nlength: 1 of 3 passwords valid\n"
+    );
+}
+
+#[test]
+fn keeps_punctuation_in_passwords() {
+    let contents = "1-3 a: abc!def\n2-4 b: b-b.\n";
+    assert_eq!(entries(contents)[0].password, "abc!def");
+    assert_eq!(count("forbid=!", contents), 1);
+    assert_eq!(count("classes=2", contents), 2);
+    assert_eq!(count("count", contents), 2);
+
+    let error = Day02::parse("1-3 a: abc\n1-3 b: x y z\n").unwrap_err();
+    assert_eq!(
+        (error.line, error.column, error.text.as_str()),
+        (2, 9, " y z")
+    );
+}
+
+#[test]
+fn selects_policies_by_name() {
+    for spec in &["count", "positions", "length", "classes=3", "forbid=a,bc"] {
+        assert_eq!(policy(spec).unwrap().name(), *spec);
+    }
+    for spec in &["counts", "classes", "classes=5", "forbid=", "length=3", ""] {
+        assert!(policy(spec).is_err(), "{}", spec);
+    }
+    assert!(policy("nope")
+        .err()
+        .unwrap()
+        .ends_with(&POLICIES.join(", ")));
+}
+
+#[test]
+fn reports_violations() {
+    let policies = ["count", "positions", "length", "classes=2", "forbid=cc"]
+        .iter()
+        .map(|spec| policy(spec).unwrap())
+        .collect::<Vec<_>>();
+    let report = violations(&policies, &entries(EXAMPLE))
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        report,
+        [
+            "line 1: `abcde` breaks length: 5 characters long, allowed 1-3",
+            "line 1: `abcde` breaks classes=2: uses 1 of the character classes, needs 2",
+            "line 2: `cdefg` breaks count: 'b' occurs 0 times, allowed 1-3",
+            "line 2: `cdefg` breaks positions: neither position 1 nor 3 matches",
+            "line 2: `cdefg` breaks length: 5 characters long, allowed 1-3",
+            "line 2: `cdefg` breaks classes=2: uses 1 of the character classes, needs 2",
+            "line 3: `ccccccccc` breaks positions: positions 2 and 9 both match",
+            "line 3: `ccccccccc` breaks classes=2: uses 1 of the character classes, needs 2",
+            "line 3: `ccccccccc` breaks forbid=cc: contains 'cc'",
+        ]
+    );
+
+    let found = violations(&policies, &entries(EXAMPLE));
+    assert_eq!(summary(&found, 3), "9 violations in 3 of 3 passwords");
+
+    // Positions outside the password are a mistake in the database
+    let positions = [policy("positions").unwrap()];
+    let reasons = violations(
+        &positions,
+        &entries("1-30 a: abc\n0-2 a: abc\n3-1 a: aba\n"),
+    )
+    .into_iter()
+    .map(|violation| violation.reason)
+    .collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            "position 30 is past the end of a 3-character password",
+            "positions start at 1",
+            "positions 3 and 1 both match",
+        ]
+    );
+
+    let once = violations(&[policy("count").unwrap()], &entries("2-3 a: xax\n"));
+    assert_eq!(once[0].reason, "'a' occurs once, allowed 2-3");
+}
+
+#[test]
+fn writes_violations_as_json() {
+    let violation = Violation {
+        line: 7,
+        password: "ab\"c".to_string(),
+        policy: "forbid=\"".to_string(),
+        reason: "contains '\"'".to_string(),
+    };
+    assert_eq!(
+        violation.to_json(),
+        r#"{"line":7,"password":"ab\"c","policy":"forbid=\"","reason":"contains '\"'"}"#
+    );
+}
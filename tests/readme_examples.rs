@@ -0,0 +1,115 @@
+//! Runs every solver on the examples found in its day's README, checking
+//! the answers the puzzle text states for them.
+
+use advent_of_code_2020::readme::{examples, Example};
+use advent_of_code_2020::runner::day_runner;
+use std::path::Path;
+
+/// Examples the README parser is known to pair with the wrong input or
+/// answer, or that can't run in a test, by day, part and stated answer.
+const SKIPPED: [(usize, usize, &str, &str); 12] = [
+    (
+        9,
+        1,
+        "127",
+        "the example uses a preamble of 5, the solver 25",
+    ),
+    (
+        9,
+        2,
+        "62",
+        "the example uses a preamble of 5, the solver 25",
+    ),
+    (10, 1, "3", "the answer is only given as its two factors"),
+    (11, 2, "26", "paired with a line-of-sight illustration"),
+    (13, 2, "1068788", "read from a sentence about a single bus"),
+    (16, 1, "71", "paired with the drawing of a ticket"),
+    (16, 2, "13", "read from a sentence about a single field"),
+    (
+        18,
+        2,
+        "1",
+        "read from the evaluation steps of one expression",
+    ),
+    (18, 2, "51", "the line reads `still becomes`"),
+    (19, 1, "2", "paired with a rule set that has no messages"),
+    (20, 2, "273", "part 2 doesn't terminate"),
+    (22, 2, "291", "paired with the infinite game example"),
+];
+
+fn is_skipped(day: usize, example: &Example) -> bool {
+    SKIPPED
+        .iter()
+        .any(|&(d, part, answer, _)| d == day && part == example.part && answer == example.answer)
+}
+
+fn readme_examples(day: usize) -> Vec<Example> {
+    let readme = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("day{:02}/README", day));
+    let contents = std::fs::read_to_string(readme).expect("Something went wrong reading the file");
+    examples(&contents)
+}
+
+#[test]
+fn readme_examples_match_stated_answers() {
+    let mut checked = 0;
+    let mut failures = Vec::new();
+
+    for day in 1..=25 {
+        let runner = day_runner(day).unwrap();
+        for example in readme_examples(day) {
+            if is_skipped(day, &example) {
+                continue;
+            }
+
+            checked += 1;
+            let answer = match runner(&example.input, &[example.part]) {
+                Ok(mut report) => report.parts.remove(0).answer,
+                Err(error) => {
+                    failures.push(format!(
+                        "day {:02} part {}: {} in\n{}",
+                        day, example.part, error, example.input
+                    ));
+                    continue;
+                }
+            };
+            if answer != example.answer {
+                failures.push(format!(
+                    "day {:02} part {}: expected {}, got {} for\n{}",
+                    day, example.part, example.answer, answer, example.input
+                ));
+            }
+        }
+    }
+
+    assert!(checked > 0, "no examples found");
+    assert!(
+        failures.is_empty(),
+        "{} of {} examples failed:\n{}",
+        failures.len(),
+        checked,
+        failures.join("\n")
+    );
+}
+
+/// Keeps the skip list honest: an entry that no longer matches an example
+/// means the parser improved and the entry should go.
+#[test]
+fn skipped_examples_are_still_extracted() {
+    let stale = SKIPPED
+        .iter()
+        .filter(|&&(day, part, answer, _)| {
+            !readme_examples(day)
+                .iter()
+                .any(|example| example.part == part && example.answer == answer)
+        })
+        .map(|(day, part, answer, reason)| {
+            format!("day {:02} part {} ({}): {}", day, part, answer, reason)
+        })
+        .collect::<Vec<String>>();
+
+    assert!(
+        stale.is_empty(),
+        "stale skip entries:\n{}",
+        stale.join("\n")
+    );
+}
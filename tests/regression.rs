@@ -0,0 +1,115 @@
+//! Runs every solver on its example and puzzle inputs and compares the
+//! answers against the checked-in manifest in `tests/answers.txt`.
+
+use advent_of_code_2020::runner::{run, Source};
+use std::path::{Path, PathBuf};
+
+struct Case {
+    day: usize,
+    part: usize,
+    input: PathBuf,
+    expected: String,
+    slow: bool,
+}
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn load_cases() -> Vec<Case> {
+    let manifest = manifest_dir().join("tests/answers.txt");
+    let contents =
+        std::fs::read_to_string(&manifest).expect("Something went wrong reading the file");
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            let malformed = || {
+                panic!(
+                    "{}:{}: malformed case: {}",
+                    manifest.display(),
+                    index + 1,
+                    line
+                )
+            };
+            if fields.len() < 4 || fields.len() > 5 || (fields.len() == 5 && fields[4] != "slow") {
+                malformed();
+            }
+
+            let day = fields[0].parse::<usize>().unwrap_or_else(|_| malformed());
+            Case {
+                day,
+                part: fields[1].parse::<usize>().unwrap_or_else(|_| malformed()),
+                input: manifest_dir()
+                    .join(format!("day{:02}", day))
+                    .join(fields[2]),
+                expected: fields[3].to_string(),
+                slow: fields.len() == 5,
+            }
+        })
+        .collect()
+}
+
+/// Runs the selected cases and fails with a list of every mismatch.
+fn check(select: impl Fn(&Case) -> bool) {
+    let cases = load_cases()
+        .into_iter()
+        .filter(select)
+        .collect::<Vec<Case>>();
+    assert!(!cases.is_empty(), "no cases selected");
+
+    let failures = cases
+        .iter()
+        .filter_map(|case| {
+            let source = Source::File(case.input.clone());
+            let answer = match run(case.day, &source, &[case.part]) {
+                Ok(mut report) => report.parts.remove(0).answer,
+                Err(message) => return Some(message),
+            };
+
+            if answer == case.expected {
+                None
+            } else {
+                Some(format!(
+                    "day {:02} part {} on {}: expected {}, got {}",
+                    case.day,
+                    case.part,
+                    case.input.display(),
+                    case.expected,
+                    answer
+                ))
+            }
+        })
+        .collect::<Vec<String>>();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} cases failed:\n{}",
+        failures.len(),
+        cases.len(),
+        failures.join("\n")
+    );
+}
+
+fn is_example(case: &Case) -> bool {
+    case.input.file_name().unwrap() != "input.txt"
+}
+
+#[test]
+fn examples() {
+    check(|case| is_example(case) && !case.slow);
+}
+
+#[test]
+fn puzzle_inputs() {
+    check(|case| !is_example(case) && !case.slow);
+}
+
+#[test]
+#[ignore]
+fn slow_cases() {
+    check(|case| case.slow);
+}
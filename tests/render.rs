@@ -0,0 +1,113 @@
+//! Writes small animations in both file formats and checks the files'
+//! headers, and plays one live as it would in a terminal.
+
+use advent_of_code_2020::render;
+use advent_of_code_2020::render::{Animation, Canvas, Format, RenderOptions, WHITE};
+use std::cell::RefCell;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+/// Everything written to a terminal, shared with the animation playing on
+/// it.
+#[derive(Clone, Default)]
+struct Played(Rc<RefCell<Vec<u8>>>);
+
+impl Played {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Played {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn draw(step: i32) -> Canvas {
+    let mut canvas = Canvas::new(4, 3, 2, WHITE);
+    canvas.put((step, 1), (0, 0, 0));
+    canvas.stamp((0, 0), "#.\n.#", &[('#', (0xFF, 0, 0))]);
+    canvas
+}
+
+// Rendering options are global, so the formats are checked one after the
+// other rather than in parallel tests.
+#[test]
+fn writes_animations_only_when_enabled() {
+    let output_dir = std::env::temp_dir().join(format!("aoc-render-{}", std::process::id()));
+
+    let mut animation = Animation::new("disabled");
+    animation.frame(|| panic!("drew a frame with rendering off"));
+    assert_eq!(animation.finish().unwrap(), None);
+
+    for &format in &[Format::Gif, Format::Apng] {
+        render::enable(RenderOptions {
+            output_dir: output_dir.clone(),
+            format,
+            frame_rate: 10,
+            frame_skip: 1,
+        });
+
+        let mut animation = Animation::new("steps");
+        let mut drawn = Vec::new();
+        for step in 0..5 {
+            animation.frame(|| {
+                drawn.push(step);
+                draw(step)
+            });
+        }
+        assert_eq!(drawn, vec![0, 2, 4]);
+
+        let path = animation.finish().unwrap().expect("no animation written");
+        let contents = std::fs::read(&path).unwrap();
+        match format {
+            Format::Gif => {
+                assert_eq!(&contents[..6], b"GIF89a");
+                // Logical screen size, little endian, in pixels
+                assert_eq!(&contents[6..10], &[8, 0, 6, 0]);
+            }
+            _ => {
+                assert_eq!(&contents[..8], b"\x89PNG\r\n\x1a\n");
+                assert_eq!(&contents[12..16], b"IHDR");
+                assert!(contents.windows(4).any(|chunk| chunk == b"acTL"));
+            }
+        }
+    }
+
+    render::enable(RenderOptions {
+        format: Format::Terminal,
+        frame_rate: 50,
+        ..RenderOptions::default()
+    });
+    let play = |size, frames: &[i32]| {
+        let played = Played::default();
+        let mut animation = Animation::new("terminal").play_on(played.clone(), size);
+        for &step in frames {
+            animation.frame(|| draw(step));
+        }
+        let before_finish = played.text();
+        assert_eq!(animation.finish().unwrap(), None);
+        (before_finish, played.text())
+    };
+
+    // Each frame is drawn as it is added. 8 by 6 pixels take 8 columns and
+    // 3 lines, and the second frame moves the cursor back up over the first
+    let (drawn, played) = play((80, 24), &[0, 1]);
+    assert!(drawn.starts_with("\x1b[?25l\x1b[38;2;255;0;0;48;2;255;0;0m"));
+    assert_eq!(drawn.matches('▀').count(), 2 * 8 * 3);
+    assert_eq!(drawn.matches("\x1b[3F").count(), 1);
+    assert_eq!(played, drawn + "\x1b[0m\x1b[?25h");
+    // Half as many pixels each way to fit in 4 columns
+    assert_eq!(play((4, 24), &[0]).1.matches('▀').count(), 4 * 2);
+    // Nothing at all without frames
+    assert_eq!(play((80, 24), &[]).1, "");
+
+    render::disable();
+    std::fs::remove_dir_all(output_dir).unwrap();
+}
@@ -0,0 +1,122 @@
+//! Checks the day 8 repair analysis against swapping every instruction in
+//! turn and running the program.
+
+use advent_of_code_2020::day08::repair::{analyse, Repair};
+use advent_of_code_2020::day08::vm::{Halt, Instruction, InstructionSet, Vm, ACC, JMP, NOP};
+use advent_of_code_2020::day08::Day08;
+use advent_of_code_2020::Solution;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic;
+
+fn load(contents: &str) -> Vec<Instruction> {
+    InstructionSet::default().load(contents).unwrap()
+}
+
+/// Swaps each instruction a broken program runs and keeps those that make
+/// it terminate.
+fn brute_force(program: &[Instruction]) -> Vec<Repair> {
+    let instruction_set = InstructionSet::default();
+    let mut original = Vm::new(program, &instruction_set);
+    if original.run() == Halt::Terminated {
+        return Vec::new();
+    }
+
+    (0..program.len())
+        .filter(|&index| original.has_executed(index))
+        .filter_map(|index| {
+            let mut repaired = program.to_vec();
+            repaired[index].opcode = match repaired[index].opcode {
+                NOP => JMP,
+                JMP => NOP,
+                _ => return None,
+            };
+            let mut vm = Vm::new(&repaired, &instruction_set);
+            match vm.run() {
+                Halt::Terminated => Some(Repair {
+                    index,
+                    accumulator: vm.accumulator(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn repairs_the_example() {
+    let program = load("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n");
+    let analysis = analyse(&program);
+    assert_eq!(analysis.halt, Halt::InfiniteLoop);
+    assert_eq!(
+        analysis.repairs,
+        [Repair {
+            index: 7,
+            accumulator: 8
+        }]
+    );
+}
+
+#[test]
+fn reports_several_repairs_or_none() {
+    let repair = |index, accumulator| Repair { index, accumulator };
+
+    assert_eq!(
+        analyse(&load("nop +3\nacc +1\njmp -1\n")).repairs,
+        [repair(0, 0), repair(2, 1)]
+    );
+    // Jumping past the end runs out of bounds rather than terminating
+    assert_eq!(analyse(&load("nop +0\njmp -1\njmp +9\n")).repairs, []);
+
+    // Nothing to repair, though swapping the `nop +1` changes nothing
+    let analysis = analyse(&load("acc +3\nnop +1\n"));
+    assert_eq!(analysis.halt, Halt::Terminated);
+    assert_eq!(analysis.repairs, []);
+}
+
+#[test]
+fn fails_part2_without_exactly_one_repair() {
+    let message = |contents: &str| {
+        let program = load(contents);
+        let payload = panic::catch_unwind(|| Day08::part2(&program)).unwrap_err();
+        payload.downcast::<String>().map_or_else(
+            |payload| payload.downcast_ref::<&str>().unwrap().to_string(),
+            |message| *message,
+        )
+    };
+
+    assert_eq!(
+        message("nop +3\nacc +1\njmp -1\n"),
+        "2 swaps make the program terminate, at 0, 2"
+    );
+    assert_eq!(
+        message("nop +0\njmp -1\njmp +9\n"),
+        "no single `nop` or `jmp` swap makes the program terminate"
+    );
+    assert_eq!(
+        message("acc +3\nnop +1\n"),
+        "the program terminates without a repair"
+    );
+    assert_eq!(Day08::part2(&load("acc +3\njmp +0\n")), 3);
+}
+
+#[test]
+fn agrees_with_brute_force() {
+    let mut rng = StdRng::seed_from_u64(8);
+    for _ in 0..2000 {
+        let length = rng.gen_range(1, 16);
+        let program = (0..length)
+            .map(|_| Instruction {
+                opcode: [NOP, ACC, JMP][rng.gen_range(0, 3)],
+                argument: rng.gen_range(-5, 6),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            analyse(&program).repairs,
+            brute_force(&program),
+            "{:?}",
+            program
+        );
+    }
+}
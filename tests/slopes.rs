@@ -0,0 +1,168 @@
+//! Checks going down the day 3 forest at any slope, and the search for the
+//! ways down hitting the fewest and most trees.
+
+use advent_of_code_2020::day03::{
+    search, search_report, slopes_within, trajectory, Day03, Forest, Slope, PART2_SLOPES,
+};
+use advent_of_code_2020::Solution;
+use std::fs;
+
+fn forest(contents: &str) -> Forest {
+    Day03::parse(contents).unwrap()
+}
+
+fn example() -> Forest {
+    forest(
+        &fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/day03/small_input.txt"
+        ))
+        .unwrap(),
+    )
+}
+
+#[test]
+fn steps_like_the_puzzle() {
+    let forest = example();
+    let counts = PART2_SLOPES
+        .iter()
+        .map(|&slope| trajectory(&forest, slope).unwrap().trees.len())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [2, 7, 3, 4, 2]);
+
+    assert_eq!(
+        trajectory(&forest, Slope::Step((3, 1))).unwrap().trees,
+        [
+            (6, 2),
+            (12, 4),
+            (15, 5),
+            (21, 7),
+            (24, 8),
+            (27, 9),
+            (30, 10)
+        ]
+    );
+    // Going left wraps around the other way
+    assert_eq!(
+        trajectory(&forest, Slope::Step((-3, 1)))
+            .unwrap()
+            .trees
+            .first(),
+        Some(&(-3, 1))
+    );
+}
+
+#[test]
+fn slides_along_lines() {
+    // Every square is a tree, so the trajectory is every square touched
+    let forest = forest(&"##\n".repeat(6));
+
+    // Steep lines touch one square a row, the nearest to the line
+    let trees = trajectory(&forest, Slope::Line((1, 3))).unwrap().trees;
+    assert_eq!(trees, [(0, 0), (0, 1), (1, 2), (1, 3), (1, 4), (2, 5)]);
+
+    // Shallow ones touch every square in between too, one a column
+    let trees = trajectory(&forest, Slope::Line((5, 2))).unwrap().trees;
+    let nearest = (0..14).map(|x| (x, (4 * x + 5) / 10)).collect::<Vec<_>>();
+    assert_eq!(trees, nearest);
+
+    // A whole number of squares right per row is the same either way
+    let forest = example();
+    assert_eq!(
+        trajectory(&forest, Slope::Line((1, 1))).unwrap().trees,
+        trajectory(&forest, Slope::Step((1, 1))).unwrap().trees
+    );
+}
+
+#[test]
+fn rejects_slopes_it_cannot_follow() {
+    let forest = example();
+    // Slopes as parsed never go sideways, but they can be built directly
+    for &slope in &[
+        Slope::Step((3, 0)),
+        Slope::Line((1, 0)),
+        Slope::Step((1, -1)),
+    ] {
+        assert_eq!(
+            trajectory(&forest, slope),
+            Err(format!("slope `{}` doesn't go down", slope))
+        );
+    }
+    for &slope in &[
+        Slope::Step((i32::MAX / 11, 1)),
+        Slope::Line((i32::MAX / 24, 1)),
+        Slope::Line((1, i32::MAX / 11)),
+        Slope::Line((i32::MIN, 1)),
+    ] {
+        assert!(trajectory(&forest, slope).is_err(), "{}", slope);
+    }
+    assert!(search(&forest, vec![Slope::Step((1, 1)), Slope::Step((2, 0))]).is_err());
+
+    // Far enough right to wrap around many times over is fine
+    let trajectory = trajectory(&forest, Slope::Step((i32::MAX / 100, 1))).unwrap();
+    assert!(trajectory
+        .trees
+        .iter()
+        .all(|&(x, y)| x == y * (i32::MAX / 100)));
+}
+
+#[test]
+fn parses_slopes() {
+    assert_eq!("3,1".parse(), Ok(Slope::Step((3, 1))));
+    assert_eq!("-1/4".parse(), Ok(Slope::Line((-1, 4))));
+    for slope in &["3,0", "1/-2", "3", "a,b", "1,2,3"] {
+        assert!(slope.parse::<Slope>().is_err(), "{}", slope);
+    }
+    for slope in slopes_within(4, 3) {
+        assert_eq!(slope.to_string().parse(), Ok(slope));
+    }
+}
+
+#[test]
+fn searches_for_the_fewest_and_most_trees() {
+    let slopes = slopes_within(3, 2).collect::<Vec<_>>();
+    assert_eq!(slopes.len(), 4 * 2 + 4);
+    assert!(slopes.contains(&Slope::Line((3, 2))));
+    assert!(!slopes.contains(&Slope::Line((2, 2))));
+
+    let forest = example();
+    let counts = slopes
+        .iter()
+        .map(|&slope| trajectory(&forest, slope).unwrap().trees.len())
+        .collect::<Vec<_>>();
+    let (fewest, most) = search(&forest, slopes.clone()).unwrap().unwrap();
+    assert_eq!(fewest.trees.len(), *counts.iter().min().unwrap());
+    assert_eq!(most.trees.len(), *counts.iter().max().unwrap());
+    assert_eq!(fewest, trajectory(&forest, fewest.slope).unwrap());
+
+    // Ties go to the earliest slope
+    let first_fewest = slopes[counts
+        .iter()
+        .position(|&count| count == fewest.trees.len())
+        .unwrap()];
+    assert_eq!(fewest.slope, first_fewest);
+
+    assert_eq!(search(&forest, Vec::new()), Ok(None));
+}
+
+#[test]
+fn bounds_the_search_by_the_forest() {
+    // The example is 11 squares wide and high
+    let forest = example();
+    assert!(search_report(&forest, 11 * 11, 11).is_ok());
+    assert_eq!(
+        search_report(&forest, 23, 2),
+        Err("can't search more than 22 squares right going 2 down a forest 11 wide".to_string())
+    );
+    assert_eq!(
+        search_report(&forest, 1, 12),
+        Err("can't search more than 11 squares down a forest 11 high".to_string())
+    );
+    assert!(search_report(&forest, 2_000_000_000, 1).is_err());
+
+    // Slopes are only made as they're searched
+    assert_eq!(
+        slopes_within(i32::MAX, i32::MAX).nth(2),
+        Some(Slope::Step((2, 1)))
+    );
+}
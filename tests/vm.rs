@@ -0,0 +1,83 @@
+//! Runs small programs on the day 8 handheld's virtual machine.
+
+use advent_of_code_2020::day08::vm::{Halt, InstructionSet, Registers, Vm, JMP, NOP};
+
+const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6\n";
+
+fn run(contents: &str) -> (Halt, Registers) {
+    let instruction_set = InstructionSet::default();
+    let program = instruction_set.load(contents).unwrap();
+    let mut vm = Vm::new(&program, &instruction_set);
+    let halt = vm.run();
+    (halt, vm.registers())
+}
+
+#[test]
+fn halts_for_every_reason() {
+    let registers = |pc, accumulator| Registers { pc, accumulator };
+
+    assert_eq!(run(EXAMPLE), (Halt::InfiniteLoop, registers(1, 5)));
+    assert_eq!(
+        run(&EXAMPLE.replace("jmp -4", "nop -4")),
+        (Halt::Terminated, registers(9, 8))
+    );
+    assert_eq!(
+        run("acc +1\njmp +2\n"),
+        (Halt::OutOfBounds, registers(3, 1))
+    );
+    assert_eq!(run("jmp -1\n"), (Halt::OutOfBounds, registers(-1, 0)));
+    assert_eq!(run(""), (Halt::Terminated, registers(0, 0)));
+}
+
+#[test]
+fn steps_one_instruction_at_a_time() {
+    let instruction_set = InstructionSet::default();
+    let program = instruction_set.load(EXAMPLE).unwrap();
+    let mut vm = Vm::new(&program, &instruction_set);
+
+    assert_eq!(vm.next_instruction().map(|i| i.opcode), Some(NOP));
+    assert_eq!(vm.step(), Ok(()));
+    assert_eq!(vm.step(), Ok(()));
+    assert_eq!(vm.next_instruction().map(|i| i.opcode), Some(JMP));
+    assert_eq!((vm.pc(), vm.accumulator(), vm.steps()), (2, 1, 2));
+    assert!(vm.has_executed(1) && !vm.has_executed(2));
+
+    assert_eq!(vm.run(), Halt::InfiniteLoop);
+    // Halting leaves the machine as it was
+    assert_eq!(vm.step(), Err(Halt::InfiniteLoop));
+    assert_eq!((vm.pc(), vm.steps()), (1, 7));
+}
+
+#[test]
+fn runs_extended_instruction_sets() {
+    let extended = InstructionSet::default().with("mul", |registers, argument| {
+        registers.accumulator *= argument;
+        registers.pc += 1;
+    });
+    let program = extended.load("acc +2\nmul +5\nacc -1\n").unwrap();
+
+    let mut vm = Vm::new(&program, &extended);
+    assert_eq!(vm.run(), Halt::Terminated);
+    assert_eq!(vm.accumulator(), 9);
+
+    // The boot code doesn't know `mul`
+    let boot_code = InstructionSet::default();
+    let mut vm = Vm::new(&program, &boot_code);
+    assert_eq!(vm.run(), Halt::IllegalInstruction);
+    assert_eq!((vm.pc(), vm.accumulator(), vm.steps()), (1, 2, 1));
+}
+
+#[test]
+fn reports_malformed_programs() {
+    let load = |contents| InstructionSet::default().load(contents).unwrap_err();
+
+    let error = load("nop +0\nhlt +0\n");
+    assert_eq!((error.line, error.column), (2, 1));
+    assert_eq!(
+        error.message,
+        "unknown operation, expected one of `nop`, `acc`, `jmp`"
+    );
+    assert_eq!(load("jmp 3\n").message, "expected a sign");
+    assert_eq!(load("acc +x\n").text, "+x");
+    assert_eq!(load("acc\n").text, "acc");
+}